@@ -5,14 +5,20 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use crate::{detect_backup, detect_compose, detect_entropy, detect_git, detect_patterns, detect_platform, detect_rewrite, detect_structured, detect_walk};
+
 #[derive(Debug, Clone, Serialize)]
 pub struct DetectionResult {
     pub found: bool,
     pub path: String,
     pub install_kind: String,
+    /// OS the detection ran on (`macos`, `windows`, `linux`).
+    pub platform: String,
     pub cli_version: String,
     pub has_config: bool,
     pub plaintext_keys: Vec<PlaintextKey>,
+    /// Prominent risks found during detection (e.g. secrets in git-tracked files).
+    pub warnings: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -20,6 +26,21 @@ pub struct PlaintextKey {
     pub file: String,
     pub key_name: String,
     pub preview: String,
+    /// Dotted key path inside TOML/INI files (e.g. `openai.api_key`), or the
+    /// service/container env entry in Compose and Kubernetes manifests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_path: Option<String>,
+    /// 1-based line of the finding; 0 when it could not be located.
+    pub line: usize,
+    /// `pattern` for known key patterns, `entropy` for the randomness heuristic.
+    pub source: String,
+    /// 0.0–1.0 likelihood for heuristic (`entropy`) findings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    /// The file is committed to git.
+    pub git_tracked: bool,
+    /// The file sits in a git work tree without being ignored.
+    pub git_unignored: bool,
 }
 
 const SEARCH_DIRS: &[&str] = &[
@@ -40,6 +61,8 @@ const CONFIG_FILES: &[&str] = &[
     "config.json",
     "config.yaml",
     "config.yml",
+    "config.toml",
+    "config.ini",
     ".openclaw/openclaw.json",
     "openclaw.config.json",
     "openclaw.config.yaml",
@@ -47,20 +70,7 @@ const CONFIG_FILES: &[&str] = &[
     ".openclaw/config.yaml",
 ];
 
-const KEY_PATTERNS: &[(&str, &str)] = &[
-    ("OPENAI_API_KEY", "sk-"),
-    ("ANTHROPIC_API_KEY", "sk-ant-"),
-    ("GROK_API_KEY", "xai-"),
-    ("TELEGRAM_BOT_TOKEN", ""),
-    ("SLACK_TOKEN", "xoxb-"),
-    ("DISCORD_TOKEN", ""),
-    ("GITHUB_TOKEN", "ghp_"),
-    ("API_KEY", ""),
-    ("SECRET_KEY", ""),
-    ("PRIVATE_KEY", ""),
-];
-
-fn home_dir() -> Option<PathBuf> {
+pub(crate) fn home_dir() -> Option<PathBuf> {
     dirs::home_dir()
 }
 
@@ -76,9 +86,41 @@ fn is_openclaw_dir(path: &Path) -> bool {
         || path.join("logs").is_dir()
 }
 
-fn scan_for_keys(dir: &Path) -> Vec<PlaintextKey> {
+/// Scan the fixed top-level `CONFIG_FILES` (used for the home directory).
+pub(crate) fn scan_for_keys(dir: &Path) -> Vec<PlaintextKey> {
+    let files: Vec<String> = CONFIG_FILES.iter().map(|f| f.to_string()).collect();
+    scan_files(dir, &files)
+}
+
+/// Scan every config file found by a bounded recursive walk of an install directory.
+pub(crate) fn scan_install_dir(dir: &Path) -> Vec<PlaintextKey> {
+    scan_files(dir, &detect_walk::config_files(dir))
+}
+
+/// 1-based line of the first occurrence of `needle`, or 0 if absent.
+pub(crate) fn line_of(content: &str, needle: &str) -> usize {
+    content.lines().position(|l| l.contains(needle)).map(|i| i + 1).unwrap_or(0)
+}
+
+fn finding(file: &str, key_name: String, value: &str, line: usize) -> PlaintextKey {
+    PlaintextKey {
+        file: file.to_string(),
+        key_name,
+        preview: detect_patterns::preview(value, false),
+        key_path: None,
+        line,
+        source: "pattern".to_string(),
+        confidence: None,
+        git_tracked: false,
+        git_unignored: false,
+    }
+}
+
+/// `PlaintextKey.file` is the path relative to `dir`.
+fn scan_files(dir: &Path, files: &[String]) -> Vec<PlaintextKey> {
     let mut found = Vec::new();
-    for config_file in CONFIG_FILES {
+    let patterns = detect_patterns::effective_patterns();
+    for config_file in files {
         let file_path = dir.join(config_file);
         if !file_path.exists() || !file_path.is_file() {
             continue;
@@ -87,38 +129,81 @@ fn scan_for_keys(dir: &Path) -> Vec<PlaintextKey> {
             Ok(c) => c,
             Err(_) => continue,
         };
-        for (key_name, prefix) in KEY_PATTERNS {
-            for line in content.lines() {
-                let trimmed = line.trim();
-                if !trimmed.contains(key_name) {
-                    continue;
-                }
-                let value = extract_value(trimmed);
-                if value.is_empty() || value.starts_with("${") || value.starts_with("$") {
-                    continue;
-                }
-                if value == "your-key-here" || value == "CHANGE_ME" || value == "xxx" {
-                    continue;
-                }
-                if !prefix.is_empty() && !value.starts_with(prefix) {
-                    continue;
-                }
-                let preview = if value.len() > 8 {
-                    format!("{}****", &value[..4])
-                } else {
-                    "****".to_string()
-                };
-                found.push(PlaintextKey {
-                    file: config_file.to_string(),
-                    key_name: key_name.to_string(),
-                    preview,
-                });
+        let file_start = found.len();
+        scan_content(config_file, &content, &patterns, &mut found);
+        // Only shell out to git for files that actually hold secrets.
+        if found.len() > file_start {
+            let git = detect_git::exposure(dir, config_file);
+            for k in &mut found[file_start..] {
+                k.git_tracked = git.tracked;
+                k.git_unignored = git.unignored;
             }
         }
     }
     found
 }
 
+/// Findings in one file's `content`, located by its structured, manifest or line parser.
+fn scan_content(config_file: &str, content: &str, patterns: &[detect_patterns::KeyPattern], found: &mut Vec<PlaintextKey>) {
+    if detect_structured::is_structured(config_file) {
+        for (key_name, key_path, value) in detect_structured::find_keys(config_file, content, patterns) {
+            found.push(PlaintextKey {
+                key_path: Some(key_path),
+                ..finding(config_file, key_name, &value, line_of(content, &value))
+            });
+        }
+    } else if let Some(env) = detect_compose::find_keys(config_file, content, patterns) {
+        for e in env {
+            found.push(PlaintextKey {
+                key_path: Some(e.key_path),
+                source: if e.confidence.is_some() { "entropy" } else { "pattern" }.to_string(),
+                confidence: e.confidence,
+                ..finding(config_file, e.key_name, &e.value, line_of(content, &e.value))
+            });
+        }
+    } else {
+        scan_lines(config_file, content, patterns, found);
+    }
+}
+
+/// Line-based pattern matching, then the entropy heuristic on lines no pattern matched.
+fn scan_lines(config_file: &str, content: &str, patterns: &[detect_patterns::KeyPattern], found: &mut Vec<PlaintextKey>) {
+    let mut matched_lines = std::collections::HashSet::new();
+    for pattern in patterns {
+        let key_name = pattern.name.as_str();
+        for (idx, line) in content.lines().enumerate() {
+            let trimmed = line.trim();
+            if !trimmed.contains(key_name) {
+                continue;
+            }
+            let value = extract_value(trimmed);
+            if value.is_empty() || value.starts_with("${") || value.starts_with("$") {
+                continue;
+            }
+            if value == "your-key-here" || value == "CHANGE_ME" || value == "xxx" {
+                continue;
+            }
+            if !pattern.matches(&value) {
+                continue;
+            }
+            matched_lines.insert(idx);
+            found.push(finding(config_file, key_name.to_string(), &value, idx + 1));
+        }
+    }
+    for (idx, line) in content.lines().enumerate() {
+        if matched_lines.contains(&idx) {
+            continue;
+        }
+        if let Some((key, value, confidence)) = detect_entropy::assess_line(line) {
+            found.push(PlaintextKey {
+                source: "entropy".to_string(),
+                confidence: Some(confidence),
+                ..finding(config_file, key, &value, idx + 1)
+            });
+        }
+    }
+}
+
 fn extract_value(line: &str) -> String {
     let cleaned = line.trim();
     if let Some(eq) = cleaned.find('=') {
@@ -133,18 +218,9 @@ fn extract_value(line: &str) -> String {
 }
 
 fn detect_global_cli() -> Option<(String, String)> {
-    let path_output = Command::new("sh")
-        .args(["-lc", "command -v openclaw"])
-        .output()
-        .ok()?;
-    if !path_output.status.success() {
-        return None;
-    }
-    let cli_path = String::from_utf8_lossy(&path_output.stdout).trim().to_string();
-    if cli_path.is_empty() {
-        return None;
-    }
-    let version_output = Command::new("openclaw").arg("--version").output().ok()?;
+    let cli = detect_platform::find_on_path("openclaw")?;
+    let cli_path = cli.to_string_lossy().to_string();
+    let version_output = Command::new(&cli).arg("--version").output().ok()?;
     if !version_output.status.success() {
         return None;
     }
@@ -155,77 +231,155 @@ fn detect_global_cli() -> Option<(String, String)> {
     Some((cli_path, version_text))
 }
 
+fn global_cli_install(home: &Path) -> Option<DetectionResult> {
+    let (cli_path, cli_version) = detect_global_cli()?;
+    let keys = scan_for_keys(home);
+    let home_keys = scan_install_dir(&home.join(".openclaw"));
+    let all_keys: Vec<PlaintextKey> = keys.into_iter().chain(home_keys).collect();
+    let has_config = home.join(".openclaw").join("openclaw.json").exists();
+    Some(DetectionResult {
+        found: true,
+        path: cli_path,
+        install_kind: "global_cli".to_string(),
+        platform: detect_platform::platform().to_string(),
+        cli_version,
+        has_config,
+        warnings: detect_git::warnings(&all_keys),
+        plaintext_keys: all_keys,
+    })
+}
+
+fn config_dir_install(home: &Path) -> Option<DetectionResult> {
+    let openclaw_config_dir = home.join(".openclaw");
+    if !openclaw_config_dir.join("openclaw.json").exists() {
+        return None;
+    }
+    let keys = scan_install_dir(&openclaw_config_dir);
+    let home_keys = scan_for_keys(home);
+    let all_keys: Vec<PlaintextKey> = keys.into_iter().chain(home_keys).collect();
+    Some(DetectionResult {
+        found: true,
+        path: openclaw_config_dir.to_string_lossy().to_string(),
+        install_kind: "config_dir".to_string(),
+        platform: detect_platform::platform().to_string(),
+        cli_version: String::new(),
+        has_config: true,
+        warnings: detect_git::warnings(&all_keys),
+        plaintext_keys: all_keys,
+    })
+}
+
+fn directory_install(candidate: &Path) -> Option<DetectionResult> {
+    if !is_openclaw_dir(candidate) {
+        return None;
+    }
+    let keys = scan_install_dir(candidate);
+    let has_config = CONFIG_FILES
+        .iter()
+        .any(|f| candidate.join(f).exists());
+    Some(DetectionResult {
+        found: true,
+        path: candidate.to_string_lossy().to_string(),
+        install_kind: "directory".to_string(),
+        platform: detect_platform::platform().to_string(),
+        cli_version: String::new(),
+        has_config,
+        warnings: detect_git::warnings(&keys),
+        plaintext_keys: keys,
+    })
+}
+
+/// User-added dirs first, then `SEARCH_DIRS` under every platform and user root.
+fn search_candidates() -> Vec<PathBuf> {
+    let extra = detect_platform::extra_search_dirs();
+    let roots: Vec<PathBuf> = detect_platform::search_roots().into_iter().chain(extra.clone()).collect();
+    extra
+        .into_iter()
+        .chain(roots.iter().flat_map(|root| SEARCH_DIRS.iter().map(move |d| root.join(d))))
+        .collect()
+}
+
+/// Find the first install. An explicit `path` is checked before anything else.
 #[tauri::command]
-pub fn detect_openclaw() -> Result<DetectionResult, String> {
+pub fn detect_openclaw(path: Option<String>) -> Result<DetectionResult, String> {
     let home = home_dir().ok_or_else(|| "Home directory not found".to_string())?;
 
-    if let Some((cli_path, cli_version)) = detect_global_cli() {
-        let keys = scan_for_keys(&home);
-        let home_keys = scan_for_keys(&home.join(".openclaw"));
-        let all_keys: Vec<PlaintextKey> = keys.into_iter().chain(home_keys).collect();
-        let has_config = home.join(".openclaw").join("openclaw.json").exists();
-        return Ok(DetectionResult {
-            found: true,
-            path: cli_path,
-            install_kind: "global_cli".to_string(),
-            cli_version,
-            has_config,
-            plaintext_keys: all_keys,
-        });
+    if let Some(found) = path.as_deref().and_then(|p| directory_install(Path::new(p))) {
+        return Ok(found);
     }
 
-    let openclaw_config_dir = home.join(".openclaw");
-    if openclaw_config_dir.join("openclaw.json").exists() {
-        let keys = scan_for_keys(&openclaw_config_dir);
-        let home_keys = scan_for_keys(&home);
-        let all_keys: Vec<PlaintextKey> = keys.into_iter().chain(home_keys).collect();
-        return Ok(DetectionResult {
-            found: true,
-            path: openclaw_config_dir.to_string_lossy().to_string(),
-            install_kind: "config_dir".to_string(),
-            cli_version: String::new(),
-            has_config: true,
-            plaintext_keys: all_keys,
-        });
+    if let Some(found) = global_cli_install(&home) {
+        return Ok(found);
     }
-
-    for search_dir in SEARCH_DIRS {
-        let candidate = home.join(search_dir);
-        if is_openclaw_dir(&candidate) {
-            let keys = scan_for_keys(&candidate);
-            let has_config = CONFIG_FILES
-                .iter()
-                .any(|f| candidate.join(f).exists());
-            return Ok(DetectionResult {
-                found: true,
-                path: candidate.to_string_lossy().to_string(),
-                install_kind: "directory".to_string(),
-                cli_version: String::new(),
-                has_config,
-                plaintext_keys: keys,
-            });
-        }
+    if let Some(found) = config_dir_install(&home) {
+        return Ok(found);
+    }
+    if let Some(found) = search_candidates().iter().find_map(|c| directory_install(c)) {
+        return Ok(found);
     }
 
     Ok(DetectionResult {
         found: false,
         path: String::new(),
         install_kind: "none".to_string(),
+        platform: detect_platform::platform().to_string(),
         cli_version: String::new(),
         has_config: false,
         plaintext_keys: Vec::new(),
+        warnings: Vec::new(),
     })
 }
 
+/// Every distinct install: global CLI, config dir and each matching search dir,
+/// deduplicated by canonical path so the user can pick which one to harden.
+#[tauri::command]
+pub fn detect_all_openclaw() -> Result<Vec<DetectionResult>, String> {
+    let home = home_dir().ok_or_else(|| "Home directory not found".to_string())?;
+    let candidates = search_candidates();
+    let found = global_cli_install(&home)
+        .into_iter()
+        .chain(config_dir_install(&home))
+        .chain(candidates.iter().filter_map(|c| directory_install(c)));
+    let mut seen: std::collections::HashSet<PathBuf> = std::collections::HashSet::new();
+    Ok(found
+        .filter(|r| {
+            let p = Path::new(&r.path);
+            seen.insert(p.canonicalize().unwrap_or_else(|_| p.to_path_buf()))
+        })
+        .collect())
+}
+
+/// Replace `value` with `placeholder` only on the 1-based `lines` where a key was
+/// located, so the same text elsewhere in the file (comments, unrelated fields) is kept.
+fn replace_on_lines(content: &str, lines: &std::collections::HashSet<usize>, value: &str, placeholder: &str) -> String {
+    if value.is_empty() {
+        return content.to_string();
+    }
+    content
+        .split_inclusive('\n')
+        .enumerate()
+        .map(|(idx, line)| {
+            if lines.contains(&(idx + 1)) {
+                line.replace(value, placeholder)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect()
+}
+
+/// Returns a warning for each file that could not be rewritten safely.
 #[tauri::command]
-pub fn secure_config_keys(install_path: String, keys_to_secure: Vec<(String, String)>) -> Result<(), String> {
+pub fn secure_config_keys(install_path: String, keys_to_secure: Vec<(String, String)>) -> Result<Vec<String>, String> {
     for (alias, value) in &keys_to_secure {
         let mut state = crate::proxy::state().write().map_err(|_| "state lock")?;
-        state.vault.insert(alias.clone(), value.clone());
+        state.vault.insert(alias.clone(), zeroize::Zeroizing::new(value.clone()));
     }
     let dir = Path::new(&install_path);
-    for config_file in CONFIG_FILES {
-        let file_path = dir.join(config_file);
+    let patterns = detect_patterns::effective_patterns();
+    let mut warnings = Vec::new();
+    for config_file in detect_walk::config_files(dir) {
+        let file_path = dir.join(&config_file);
         if !file_path.exists() || !file_path.is_file() {
             continue;
         }
@@ -233,16 +387,25 @@ pub fn secure_config_keys(install_path: String, keys_to_secure: Vec<(String, Str
             Ok(c) => c,
             Err(_) => continue,
         };
+        let mut located = Vec::new();
+        scan_content(&config_file, &content, &patterns, &mut located);
+        let lines: std::collections::HashSet<usize> = located.iter().map(|k| k.line).filter(|l| *l > 0).collect();
+        if lines.is_empty() {
+            continue;
+        }
         let mut new_content = content.clone();
         for (alias, value) in &keys_to_secure {
-            new_content = new_content.replace(value.as_str(), &format!("VAULT0_ALIAS:{}", alias));
+            new_content = replace_on_lines(&new_content, &lines, value, &format!("VAULT0_ALIAS:{}", alias));
         }
         if new_content != content {
-            let _ = fs::write(&file_path, &new_content);
+            if let Err(e) = detect_rewrite::write_checked(&file_path, &config_file, &content, &new_content) {
+                crate::evidence::push("warning", &e);
+                warnings.push(e);
+            }
         }
     }
     crate::evidence::push("info", &format!("Secured {} keys in {}", keys_to_secure.len(), install_path));
-    Ok(())
+    Ok(warnings)
 }
 
 #[derive(Debug, Serialize)]
@@ -268,17 +431,15 @@ pub fn harden_openclaw(install_path: String) -> Result<HardenResult, String> {
     }
 
     // 1. Backup
-    let backup_dir = dirs::data_dir()
-        .ok_or("Cannot determine app data directory")?
-        .join("Vault0")
-        .join("backups")
-        .join(format!("{}", std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs()));
+    let backup_ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs();
+    let backup_dir = detect_backup::backups_root()?.join(format!("{}", backup_ts));
     match fs::create_dir_all(&backup_dir) {
         Ok(_) => {
+            detect_backup::write_manifest(&backup_dir, &install_path, backup_ts);
             let mut backed = 0u32;
             let mut backup_items: Vec<String> = Vec::new();
-            for config_file in CONFIG_FILES {
-                let file_path = src.join(config_file);
+            for config_file in detect_walk::config_files(src) {
+                let file_path = src.join(&config_file);
                 if file_path.exists() && file_path.is_file() {
                     if let Ok(content) = fs::read(&file_path) {
                         match crate::vault_store::encrypt_bytes_with_vault_key(&content) {
@@ -293,7 +454,7 @@ pub fn harden_openclaw(install_path: String) -> Result<HardenResult, String> {
                                 }
                             }
                             Err(_) => {
-                                let dest = backup_dir.join(config_file);
+                                let dest = backup_dir.join(&config_file);
                                 if let Some(parent) = dest.parent() {
                                     let _ = fs::create_dir_all(parent);
                                 }
@@ -332,29 +493,42 @@ pub fn harden_openclaw(install_path: String) -> Result<HardenResult, String> {
     }
 
     // 2. Migrate secrets to encrypted vault
-    let keys = scan_for_keys(src);
+    let keys = scan_install_dir(src);
     let home = home_dir().unwrap_or_default();
     let home_keys = scan_for_keys(&home);
     let all_keys: Vec<PlaintextKey> = keys.into_iter().chain(home_keys).collect();
 
     let mut migrated = 0u32;
     let mut migrate_items: Vec<String> = Vec::new();
+    let mut secured_names: Vec<String> = Vec::new();
     for pk in &all_keys {
-        let raw_value = read_raw_key_value(src, &pk.file, &pk.key_name)
-            .or_else(|| read_raw_key_value(&home, &pk.file, &pk.key_name));
+        let raw_value = match &pk.key_path {
+            Some(path) if detect_structured::is_structured(&pk.file) => detect_structured::raw_value(src, &pk.file, path)
+                .or_else(|| detect_structured::raw_value(&home, &pk.file, path)),
+            Some(path) => detect_compose::raw_value(src, &pk.file, path)
+                .or_else(|| detect_compose::raw_value(&home, &pk.file, path)),
+            None => read_raw_key_value(src, &pk.file, &pk.key_name)
+                .or_else(|| read_raw_key_value(&home, &pk.file, &pk.key_name)),
+        };
         if let Some(val) = raw_value {
             let alias = pk.key_name.to_lowercase().replace(' ', "_");
             let provider = guess_provider(&pk.key_name);
-            let preview = if val.len() > 8 {
-                format!("{}...{}", &val[..4], &val[val.len()-4..])
-            } else {
-                "****".to_string()
-            };
-            match crate::vault_store::vault_add_entry(alias.clone(), val.clone(), provider) {
+            let preview = detect_patterns::preview(&val, true);
+            match crate::vault_store::vault_add_entry(alias.clone(), val.clone(), provider, None, None) {
                 Ok(_) => {
-                    replace_key_in_file(src, &pk.file, &val, &format!("VAULT0_ALIAS:{alias}"));
-                    replace_key_in_file(&home, &pk.file, &val, &format!("VAULT0_ALIAS:{alias}"));
+                    let placeholder = format!("VAULT0_ALIAS:{alias}");
+                    for base in [src, home.as_path()] {
+                        if let Err(e) = replace_key_in_file(base, &pk.file, &val, &placeholder) {
+                            steps.push(HardenStep {
+                                step: "migrate".into(),
+                                status: "warn".into(),
+                                detail: e,
+                                items: vec![],
+                            });
+                        }
+                    }
                     migrate_items.push(format!("{} ({}) -> VAULT0_ALIAS:{}", pk.key_name, preview, alias));
+                    secured_names.push(pk.key_name.clone());
                     migrated += 1;
                 }
                 Err(e) => {
@@ -371,6 +545,7 @@ pub fn harden_openclaw(install_path: String) -> Result<HardenResult, String> {
     if migrate_items.is_empty() {
         migrate_items.push("No plaintext secrets found to migrate (already secured or none detected)".to_string());
     }
+    crate::health_drift::record_baseline(&install_path, secured_names);
     steps.push(HardenStep {
         step: "migrate".into(),
         status: "ok".into(),
@@ -408,7 +583,7 @@ pub fn harden_openclaw(install_path: String) -> Result<HardenResult, String> {
             status: "ok".into(),
             detail: "Vault-0 secure proxy started".into(),
             items: vec![
-                "Listening: 127.0.0.1:3840".into(),
+                format!("Listening: {}", crate::settings::proxy_addr()),
                 "Mode: transparent forwarding + secret injection".into(),
                 "Keys are decrypted in memory only, never written to disk".into(),
             ],
@@ -422,6 +597,15 @@ pub fn harden_openclaw(install_path: String) -> Result<HardenResult, String> {
     }
 
     crate::evidence::push("info", &format!("Hardened OpenClaw at {install_path}: {migrated} secrets migrated"));
+    let pruned = detect_backup::prune_backups();
+    if !pruned.is_empty() {
+        steps.push(HardenStep {
+            step: "backup".into(),
+            status: "ok".into(),
+            detail: format!("Pruned {} old backup(s) per retention policy", pruned.len()),
+            items: pruned,
+        });
+    }
     Ok(HardenResult { success: true, steps })
 }
 
@@ -455,17 +639,18 @@ fn read_raw_key_value(base: &Path, config_file: &str, key_name: &str) -> Option<
     None
 }
 
-fn replace_key_in_file(base: &Path, config_file: &str, old_value: &str, new_value: &str) {
+fn replace_key_in_file(base: &Path, config_file: &str, old_value: &str, new_value: &str) -> Result<(), String> {
     let file_path = base.join(config_file);
     if let Ok(content) = fs::read_to_string(&file_path) {
         let updated = content.replace(old_value, new_value);
         if updated != content {
-            let _ = fs::write(&file_path, &updated);
+            return detect_rewrite::write_checked(&file_path, config_file, &content, &updated);
         }
     }
+    Ok(())
 }
 
-fn guess_provider(key_name: &str) -> String {
+pub(crate) fn guess_provider(key_name: &str) -> String {
     let lower = key_name.to_lowercase();
     if lower.contains("openai") { return "openai".into(); }
     if lower.contains("anthropic") { return "anthropic".into(); }
@@ -476,271 +661,3 @@ fn guess_provider(key_name: &str) -> String {
     if lower.contains("github") { return "github".into(); }
     "unknown".into()
 }
-
-// --- Ephemeral .env Writer (Option C) ---
-
-#[derive(Debug, Serialize)]
-pub struct SecureLaunchResult {
-    pub success: bool,
-    pub keys_injected: u32,
-    pub daemon_restarted: bool,
-    pub env_cleaned: bool,
-    pub detail: String,
-}
-
-fn openclaw_env_path() -> Result<PathBuf, String> {
-    let home = home_dir().ok_or("Home directory not found")?;
-    Ok(home.join(".openclaw").join(".env"))
-}
-
-#[tauri::command]
-pub async fn launch_secure_agent() -> Result<SecureLaunchResult, String> {
-    // 1. Check vault is unlocked and get all entries
-    let entries = crate::vault_store::vault_list_entries()?;
-    if entries.is_empty() {
-        return Ok(SecureLaunchResult {
-            success: false,
-            keys_injected: 0,
-            daemon_restarted: false,
-            env_cleaned: false,
-            detail: "No secrets in vault. Add secrets first.".into(),
-        });
-    }
-
-    // 2. Build .env content from vault secrets
-    let mut env_lines: Vec<String> = Vec::new();
-    let mut count = 0u32;
-    for entry in &entries {
-        match crate::vault_store::vault_get_secret(entry.alias.clone()) {
-            Ok(value) => {
-                let key_name = entry.alias.to_uppercase().replace('-', "_");
-                env_lines.push(format!("{}={}", key_name, value));
-                count += 1;
-            }
-            Err(_) => continue,
-        }
-    }
-
-    if env_lines.is_empty() {
-        return Ok(SecureLaunchResult {
-            success: false,
-            keys_injected: 0,
-            daemon_restarted: false,
-            env_cleaned: false,
-            detail: "Could not read any secrets from vault.".into(),
-        });
-    }
-
-    // 3. Write ephemeral .env
-    let env_path = openclaw_env_path()?;
-    let env_content = env_lines.join("\n") + "\n";
-    fs::write(&env_path, &env_content).map_err(|e| format!("Write .env failed: {e}"))?;
-    tracing::info!("Ephemeral .env written with {} keys", count);
-
-    // 4. Restart OpenClaw daemon
-    let daemon_restarted = restart_openclaw_daemon();
-
-    // 5. Sleep 2 seconds to let daemon read .env
-    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
-
-    // 6. Delete/zero .env
-    let env_cleaned = match fs::write(&env_path, "# Managed by Vault-0 - secrets injected at runtime\n") {
-        Ok(_) => {
-            tracing::info!("Ephemeral .env cleaned");
-            true
-        }
-        Err(e) => {
-            tracing::error!("Failed to clean .env: {e}");
-            false
-        }
-    };
-
-    // 7. Log to evidence
-    crate::evidence::push("info", &format!(
-        "Secure launch: {} keys injected, daemon restarted: {}, .env cleaned: {}",
-        count, daemon_restarted, env_cleaned
-    ));
-
-    Ok(SecureLaunchResult {
-        success: true,
-        keys_injected: count,
-        daemon_restarted,
-        env_cleaned,
-        detail: format!(
-            "{} secrets injected. Daemon {}. .env {}.",
-            count,
-            if daemon_restarted { "restarted" } else { "restart failed (try manually)" },
-            if env_cleaned { "cleaned" } else { "cleanup failed" }
-        ),
-    })
-}
-
-fn restart_openclaw_daemon() -> bool {
-    use std::process::Command;
-
-    // Try launchctl first (macOS daemon)
-    let uid = Command::new("id").arg("-u").output().ok()
-        .and_then(|o| String::from_utf8(o.stdout).ok())
-        .map(|s| s.trim().to_string())
-        .unwrap_or_default();
-
-    if !uid.is_empty() {
-        let service = format!("gui/{}/ai.openclaw.gateway", uid);
-        let result = Command::new("launchctl")
-            .args(["kickstart", "-k", &service])
-            .output();
-        if let Ok(out) = result {
-            if out.status.success() {
-                tracing::info!("Daemon restarted via launchctl kickstart");
-                return true;
-            }
-        }
-    }
-
-    // Fallback: try openclaw restart
-    let result = Command::new("sh")
-        .args(["-lc", "openclaw restart 2>/dev/null || openclaw gateway --restart 2>/dev/null"])
-        .output();
-    if let Ok(out) = result {
-        if out.status.success() {
-            tracing::info!("Daemon restarted via openclaw restart");
-            return true;
-        }
-    }
-
-    // Fallback: find and HUP the gateway process
-    let result = Command::new("sh")
-        .args(["-lc", "pgrep -f 'openclaw.*gateway' | head -1"])
-        .output();
-    if let Ok(out) = result {
-        let pid = String::from_utf8_lossy(&out.stdout).trim().to_string();
-        if !pid.is_empty() {
-            let kill = Command::new("kill").args(["-HUP", &pid]).output();
-            if let Ok(k) = kill {
-                if k.status.success() {
-                    tracing::info!("Daemon signaled via HUP on PID {}", pid);
-                    return true;
-                }
-            }
-        }
-    }
-
-    tracing::warn!("Could not restart OpenClaw daemon automatically");
-    false
-}
-
-// --- Scan for New Secrets ---
-
-#[derive(Debug, Serialize)]
-pub struct NewSecretFound {
-    pub key_name: String,
-    pub file: String,
-    pub provider: String,
-    pub preview: String,
-}
-
-#[tauri::command]
-pub fn scan_for_new_secrets() -> Result<Vec<NewSecretFound>, String> {
-    let home = home_dir().ok_or("Home directory not found")?;
-    let openclaw_dir = home.join(".openclaw");
-
-    // Get existing vault entries for comparison
-    let vault_entries = crate::vault_store::vault_list_entries().unwrap_or_default();
-    let vault_aliases: std::collections::HashSet<String> = vault_entries.iter()
-        .map(|e| e.alias.to_lowercase().replace('-', "_"))
-        .collect();
-
-    let mut new_secrets: Vec<NewSecretFound> = Vec::new();
-
-    // Scan .env
-    let env_path = openclaw_dir.join(".env");
-    if env_path.exists() {
-        if let Ok(content) = fs::read_to_string(&env_path) {
-            for line in content.lines() {
-                let trimmed = line.trim();
-                if trimmed.is_empty() || trimmed.starts_with('#') {
-                    continue;
-                }
-                if let Some(eq) = trimmed.find('=') {
-                    let key = trimmed[..eq].trim();
-                    let val = trimmed[eq + 1..].trim().trim_matches('"').trim_matches('\'');
-                    if val.is_empty() || val.starts_with("VAULT0_ALIAS") || val == "your-key-here" {
-                        continue;
-                    }
-                    let normalized = key.to_lowercase().replace('-', "_");
-                    if !vault_aliases.contains(&normalized) {
-                        let preview = if val.len() > 8 {
-                            format!("{}...{}", &val[..4], &val[val.len()-4..])
-                        } else {
-                            "****".to_string()
-                        };
-                        new_secrets.push(NewSecretFound {
-                            key_name: key.to_string(),
-                            file: ".env".to_string(),
-                            provider: guess_provider(key),
-                            preview,
-                        });
-                    }
-                }
-            }
-        }
-    }
-
-    // Scan openclaw.json for inline apiKey values
-    let config_path = openclaw_dir.join("openclaw.json");
-    if config_path.exists() {
-        if let Ok(content) = fs::read_to_string(&config_path) {
-            for (key_name, prefix) in KEY_PATTERNS {
-                for line in content.lines() {
-                    let trimmed = line.trim();
-                    if !trimmed.contains(key_name) && !trimmed.contains("apiKey") {
-                        continue;
-                    }
-                    let val = extract_value(trimmed);
-                    if val.is_empty() || val.starts_with("$") || val.starts_with("VAULT0_ALIAS") {
-                        continue;
-                    }
-                    if !prefix.is_empty() && !val.starts_with(prefix) {
-                        continue;
-                    }
-                    let normalized = key_name.to_lowercase().replace('-', "_");
-                    if !vault_aliases.contains(&normalized) {
-                        let preview = if val.len() > 8 {
-                            format!("{}...{}", &val[..4], &val[val.len()-4..])
-                        } else {
-                            "****".to_string()
-                        };
-                        new_secrets.push(NewSecretFound {
-                            key_name: key_name.to_string(),
-                            file: "openclaw.json".to_string(),
-                            provider: guess_provider(key_name),
-                            preview,
-                        });
-                    }
-                }
-            }
-        }
-    }
-
-    // Scan auth-profiles.json
-    let auth_path = openclaw_dir.join("auth-profiles.json");
-    if auth_path.exists() {
-        if let Ok(content) = fs::read_to_string(&auth_path) {
-            for (key_name, _) in KEY_PATTERNS {
-                if content.contains(key_name) {
-                    let normalized = key_name.to_lowercase().replace('-', "_");
-                    if !vault_aliases.contains(&normalized) {
-                        new_secrets.push(NewSecretFound {
-                            key_name: key_name.to_string(),
-                            file: "auth-profiles.json".to_string(),
-                            provider: guess_provider(key_name),
-                            preview: "****".to_string(),
-                        });
-                    }
-                }
-            }
-        }
-    }
-
-    Ok(new_secrets)
-}
@@ -0,0 +1,206 @@
+//! Hardening backups: listing, restoring and pruning the encrypted config
+//! snapshots that `harden_openclaw` writes to <data dir>/Vault0/backups/<unix secs>/.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Written next to the backed-up files so a restore knows where they came from.
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    install_path: String,
+    created_at: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BackupInfo {
+    pub id: String,
+    pub path: String,
+    pub created_at: u64,
+    /// Install the backup was taken from; `None` for backups made before manifests existed.
+    pub install_path: Option<String>,
+    pub files: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestoreResult {
+    /// False when this was a preview (`confirm` not set).
+    pub restored: bool,
+    pub install_path: String,
+    /// Existing files that are (or would be) overwritten.
+    pub overwritten: Vec<String>,
+    /// Files that do not exist in the install and are (or would be) created.
+    pub created: Vec<String>,
+}
+
+pub(crate) fn backups_root() -> Result<PathBuf, String> {
+    Ok(dirs::data_dir()
+        .ok_or("Cannot determine app data directory")?
+        .join("Vault0")
+        .join("backups"))
+}
+
+pub(crate) fn write_manifest(backup_dir: &Path, install_path: &str, created_at: u64) {
+    let manifest = Manifest { install_path: install_path.to_string(), created_at };
+    if let Ok(json) = serde_json::to_string_pretty(&manifest) {
+        let _ = fs::write(backup_dir.join(MANIFEST_FILE), json);
+    }
+}
+
+fn read_manifest(backup_dir: &Path) -> Option<Manifest> {
+    let content = fs::read_to_string(backup_dir.join(MANIFEST_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Backed-up files as `/`-separated paths relative to the backup dir (manifest excluded).
+fn backup_files(dir: &Path, rel: &str, out: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let rel_path = if rel.is_empty() { name.clone() } else { format!("{}/{}", rel, name) };
+        match entry.file_type() {
+            Ok(t) if t.is_dir() => backup_files(&entry.path(), &rel_path, out),
+            Ok(t) if t.is_file() && rel_path != MANIFEST_FILE => out.push(rel_path),
+            _ => {}
+        }
+    }
+}
+
+/// Resolve a backup id or path, refusing anything outside the backups directory.
+fn resolve_backup_dir(backup_dir: &str) -> Result<PathBuf, String> {
+    let root = backups_root()?;
+    let candidate = if Path::new(backup_dir).is_absolute() {
+        PathBuf::from(backup_dir)
+    } else {
+        root.join(backup_dir)
+    };
+    let dir = candidate.canonicalize().map_err(|_| format!("Backup not found: {backup_dir}"))?;
+    let root = root.canonicalize().map_err(|_| "No backups exist yet".to_string())?;
+    if !dir.starts_with(&root) || dir == root {
+        return Err(format!("Not a Vault-0 backup: {backup_dir}"));
+    }
+    Ok(dir)
+}
+
+#[tauri::command]
+pub fn list_backups() -> Result<Vec<BackupInfo>, String> {
+    let root = backups_root()?;
+    let Ok(entries) = fs::read_dir(&root) else { return Ok(Vec::new()) };
+    let mut backups: Vec<BackupInfo> = entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| {
+            let path = e.path();
+            let id = e.file_name().to_string_lossy().to_string();
+            let manifest = read_manifest(&path);
+            let mut files = Vec::new();
+            backup_files(&path, "", &mut files);
+            BackupInfo {
+                created_at: manifest.as_ref().map(|m| m.created_at).unwrap_or_else(|| id.parse().unwrap_or(0)),
+                install_path: manifest.map(|m| m.install_path),
+                path: path.to_string_lossy().to_string(),
+                files: files.len(),
+                id,
+            }
+        })
+        .collect();
+    backups.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+    Ok(backups)
+}
+
+/// Restore a backup into its install path (or `install_path` for backups without a
+/// manifest). Without `confirm: true` nothing is written and the result lists what
+/// would be overwritten. Encrypted files need the vault unlocked.
+#[tauri::command]
+pub fn restore_backup(
+    backup_dir: String,
+    install_path: Option<String>,
+    confirm: Option<bool>,
+) -> Result<RestoreResult, String> {
+    let dir = resolve_backup_dir(&backup_dir)?;
+    let install_path = install_path
+        .or_else(|| read_manifest(&dir).map(|m| m.install_path))
+        .ok_or("Backup has no manifest; pass the install path to restore into")?;
+    let dest_root = Path::new(&install_path);
+    if !dest_root.is_dir() {
+        return Err(format!("Install path does not exist: {install_path}"));
+    }
+
+    let mut files = Vec::new();
+    backup_files(&dir, "", &mut files);
+    let mut plan: Vec<(PathBuf, String)> = Vec::new();
+    let (mut overwritten, mut created) = (Vec::new(), Vec::new());
+    for rel in files {
+        let target = rel.strip_suffix(".enc").unwrap_or(&rel).to_string();
+        if dest_root.join(&target).exists() {
+            overwritten.push(target.clone());
+        } else {
+            created.push(target.clone());
+        }
+        plan.push((dir.join(&rel), target));
+    }
+
+    let confirm = confirm.unwrap_or(false);
+    if confirm {
+        // Decrypt everything first so a wrong or locked vault leaves the install untouched.
+        let mut contents: Vec<(String, Vec<u8>)> = Vec::new();
+        for (src, target) in &plan {
+            let data = fs::read(src).map_err(|e| format!("read {}: {e}", src.display()))?;
+            let data = if src.extension().is_some_and(|e| e == "enc") {
+                crate::vault_store::decrypt_bytes_with_vault_key(&data)?
+            } else {
+                data
+            };
+            contents.push((target.clone(), data));
+        }
+        for (target, data) in contents {
+            let dest = dest_root.join(&target);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("mkdir: {e}"))?;
+            }
+            fs::write(&dest, data).map_err(|e| format!("write {target}: {e}"))?;
+        }
+        crate::evidence::push(
+            "info",
+            &format!("Restored backup {} into {} ({} files)", backup_dir, install_path, plan.len()),
+        );
+    }
+
+    Ok(RestoreResult { restored: confirm, install_path, overwritten, created })
+}
+
+/// Retention sweep: a backup survives if it is among the newest
+/// `backup_keep_count` or younger than `backup_keep_days`. Returns pruned ids.
+pub(crate) fn prune_backups() -> Vec<String> {
+    let settings = crate::settings::get();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let max_age = settings.backup_keep_days.saturating_mul(24 * 60 * 60);
+    let mut pruned = Vec::new();
+    // list_backups is sorted newest first.
+    for backup in list_backups().unwrap_or_default().into_iter().skip(settings.backup_keep_count) {
+        if now.saturating_sub(backup.created_at) < max_age {
+            continue;
+        }
+        match fs::remove_dir_all(&backup.path) {
+            Ok(_) => pruned.push(backup.id),
+            Err(e) => tracing::warn!("Could not prune backup {}: {}", backup.path, e),
+        }
+    }
+    if !pruned.is_empty() {
+        crate::evidence::push("info", &format!("Pruned {} old backup(s): {}", pruned.len(), pruned.join(", ")));
+    }
+    pruned
+}
+
+#[tauri::command]
+pub fn delete_backup(backup_dir: String) -> Result<(), String> {
+    let dir = resolve_backup_dir(&backup_dir)?;
+    fs::remove_dir_all(&dir).map_err(|e| format!("delete backup: {e}"))?;
+    crate::evidence::push("info", &format!("Deleted backup {}", dir.display()));
+    Ok(())
+}
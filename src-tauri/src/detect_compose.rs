@@ -0,0 +1,163 @@
+//! Secret search in Docker Compose files and Kubernetes manifests. Only
+//! container environment blocks are read, and each finding names the service or
+//! container it belongs to (`services.api.environment.OPENAI_API_KEY`,
+//! `Deployment/web.containers.app.env.OPENAI_API_KEY`).
+
+use serde::Deserialize;
+use serde_yaml::Value;
+use std::fs;
+use std::path::Path;
+
+use crate::detect_entropy;
+use crate::detect_patterns::{is_placeholder, KeyPattern};
+
+/// A secret-looking env value. `confidence` is set for entropy findings only.
+pub(crate) struct EnvFinding {
+    pub key_name: String,
+    pub key_path: String,
+    pub value: String,
+    pub confidence: Option<f32>,
+}
+
+fn is_yaml(file: &str) -> bool {
+    let lower = file.to_lowercase();
+    lower.ends_with(".yaml") || lower.ends_with(".yml")
+}
+
+/// Every YAML document in the file; a manifest may hold several separated by `---`.
+fn documents(content: &str) -> Vec<Value> {
+    serde_yaml::Deserializer::from_str(content)
+        .map_while(|doc| Value::deserialize(doc).ok())
+        .collect()
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// A Compose `services:` map, rather than any config that happens to use the
+/// key: at least one service defines an image, build or environment.
+fn is_compose_services(services: &Value) -> bool {
+    services.as_mapping().is_some_and(|m| {
+        m.values().any(|svc| ["image", "build", "environment"].iter().any(|k| svc.get(k).is_some()))
+    })
+}
+
+/// Compose `environment:` in either the map or the `- KEY=value` list form.
+fn compose_env(services: &Value, out: &mut Vec<(String, String, String)>) {
+    let Some(services) = services.as_mapping() else { return };
+    for (name, service) in services {
+        let Some(name) = name.as_str() else { continue };
+        let base = format!("services.{}.environment", name);
+        match service.get("environment") {
+            Some(Value::Mapping(env)) => {
+                for (k, v) in env {
+                    if let (Some(k), Some(v)) = (k.as_str(), scalar(v)) {
+                        out.push((format!("{}.{}", base, k), k.to_string(), v));
+                    }
+                }
+            }
+            Some(Value::Sequence(env)) => {
+                for item in env.iter().filter_map(Value::as_str) {
+                    if let Some((k, v)) = item.split_once('=') {
+                        let v = v.trim_matches('"').trim_matches('\'');
+                        out.push((format!("{}.{}", base, k), k.to_string(), v.to_string()));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The pod spec of a workload: directly on a Pod, under `spec.template` for
+/// controllers, and one level deeper for CronJobs.
+fn pod_spec(doc: &Value) -> Option<&Value> {
+    let spec = doc.get("spec")?;
+    if spec.get("containers").is_some() {
+        return Some(spec);
+    }
+    if let Some(pod) = spec.get("template").and_then(|t| t.get("spec")) {
+        return Some(pod);
+    }
+    spec.get("jobTemplate")?.get("spec")?.get("template")?.get("spec")
+}
+
+/// Literal `env[].value` entries of every container and init container.
+/// `valueFrom` references are skipped; they already point at a Secret.
+fn k8s_env(doc: &Value, out: &mut Vec<(String, String, String)>) {
+    let kind = doc.get("kind").and_then(Value::as_str).unwrap_or("Object");
+    let name = doc.get("metadata").and_then(|m| m.get("name")).and_then(Value::as_str).unwrap_or("unnamed");
+    let Some(spec) = pod_spec(doc) else { return };
+    for group in ["initContainers", "containers"] {
+        let Some(containers) = spec.get(group).and_then(Value::as_sequence) else { continue };
+        for container in containers {
+            let cname = container.get("name").and_then(Value::as_str).unwrap_or("unnamed");
+            let Some(env) = container.get("env").and_then(Value::as_sequence) else { continue };
+            for var in env {
+                let (Some(k), Some(v)) = (var.get("name").and_then(Value::as_str), var.get("value").and_then(scalar)) else {
+                    continue;
+                };
+                out.push((format!("{}/{}.{}.{}.env.{}", kind, name, group, cname, k), k.to_string(), v));
+            }
+        }
+    }
+}
+
+/// `(key path, variable name, value)` for every env entry, or `None` when the
+/// file is not a Compose file or Kubernetes manifest.
+fn env_entries(file: &str, content: &str) -> Option<Vec<(String, String, String)>> {
+    if !is_yaml(file) {
+        return None;
+    }
+    let mut out = Vec::new();
+    let mut manifest = false;
+    for doc in documents(content) {
+        if let Some(services) = doc.get("services").filter(|s| is_compose_services(s)) {
+            manifest = true;
+            compose_env(services, &mut out);
+        } else if doc.get("apiVersion").is_some() && doc.get("kind").is_some() {
+            manifest = true;
+            k8s_env(&doc, &mut out);
+        }
+    }
+    manifest.then_some(out)
+}
+
+/// Env values matching a key pattern by variable name (longest name wins), or
+/// failing that, passing the entropy heuristic. `None` when the file is not a
+/// manifest, so the caller can fall back to the line scan.
+pub(crate) fn find_keys(file: &str, content: &str, patterns: &[KeyPattern]) -> Option<Vec<EnvFinding>> {
+    let entries = env_entries(file, content)?;
+    let mut found = Vec::new();
+    for (key_path, var, value) in entries {
+        if is_placeholder(&value) {
+            continue;
+        }
+        let upper = var.to_uppercase();
+        let best = patterns
+            .iter()
+            .filter(|p| upper.contains(&p.name.to_uppercase()) && p.matches(&value))
+            .max_by_key(|p| p.name.len());
+        if let Some(p) = best {
+            found.push(EnvFinding { key_name: p.name.clone(), key_path, value, confidence: None });
+        } else if let Some(confidence) = detect_entropy::assess(&var, &value) {
+            found.push(EnvFinding { key_name: var, key_path, value, confidence: Some(confidence) });
+        }
+    }
+    Some(found)
+}
+
+/// Current value at `key_path` in a manifest, for migrating it into the vault.
+pub(crate) fn raw_value(base: &Path, file: &str, key_path: &str) -> Option<String> {
+    let content = fs::read_to_string(base.join(file)).ok()?;
+    env_entries(file, &content)?
+        .into_iter()
+        .find(|(path, _, value)| path == key_path && !is_placeholder(value))
+        .map(|(_, _, value)| value)
+}
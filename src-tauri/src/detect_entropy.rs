@@ -0,0 +1,74 @@
+//! Entropy heuristic for secrets that don't start with a known prefix
+//! (generic bearer tokens, base64 blobs) assigned to secret-sounding keys.
+
+/// Key name fragments that suggest the value is a credential.
+const SECRET_HINTS: &[&str] = &["token", "secret", "key", "password", "passwd", "credential"];
+const MIN_LEN: usize = 20;
+/// Bits per character; random base64 sits near 6, hex near 4, prose far lower.
+const MIN_ENTROPY: f64 = 3.5;
+
+fn shannon_entropy(s: &str) -> f64 {
+    let mut counts = [0usize; 256];
+    for b in s.bytes() {
+        counts[b as usize] += 1;
+    }
+    let len = s.len() as f64;
+    counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+fn is_uuid(s: &str) -> bool {
+    let parts: Vec<&str> = s.split('-').collect();
+    parts.len() == 5
+        && [8, 4, 4, 4, 12].iter().zip(&parts).all(|(n, p)| p.len() == *n)
+        && parts.iter().all(|p| p.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Values that are high-entropy but clearly not secrets.
+fn is_obvious_non_secret(value: &str) -> bool {
+    value.contains("://")
+        || value.contains(char::is_whitespace)
+        || value.starts_with('/')
+        || value.starts_with("${")
+        || value.starts_with("VAULT0_ALIAS")
+        || is_uuid(value)
+}
+
+/// For a `KEY=value` / `key: value` line, returns `(key, value, confidence)` when the
+/// key hints at a secret and the value looks random.
+pub(crate) fn assess_line(line: &str) -> Option<(String, String, f32)> {
+    let line = line.trim().trim_start_matches("export ");
+    if line.starts_with('#') || line.starts_with("//") {
+        return None;
+    }
+    let sep = line.find(['=', ':'])?;
+    let key = line[..sep].trim().trim_matches('"').trim_matches('\'');
+    let value = line[sep + 1..].trim().trim_end_matches(',').trim_matches('"').trim_matches('\'');
+    let confidence = assess(key, value)?;
+    Some((key.to_string(), value.to_string(), confidence))
+}
+
+/// Confidence (0.5–0.99, rising with entropy and length) that `value` is a
+/// secret, when `key` hints at one and the value looks random.
+pub(crate) fn assess(key: &str, value: &str) -> Option<f32> {
+    let lower = key.to_lowercase();
+    if key.is_empty() || !SECRET_HINTS.iter().any(|h| lower.contains(h)) {
+        return None;
+    }
+    if value.len() < MIN_LEN || is_obvious_non_secret(value) {
+        return None;
+    }
+    let entropy = shannon_entropy(value);
+    if entropy < MIN_ENTROPY {
+        return None;
+    }
+    let length_bonus = ((value.len() - MIN_LEN) as f64 / 40.0).min(1.0) * 0.15;
+    let confidence = (0.5 + (entropy - MIN_ENTROPY) / 2.5 * 0.35 + length_bonus).min(0.99);
+    Some(confidence as f32)
+}
@@ -0,0 +1,58 @@
+//! Git exposure checks for config files holding secrets: a tracked `.env` is
+//! the most common way keys end up on a remote.
+
+use std::path::Path;
+use std::process::Command;
+
+use crate::detect::PlaintextKey;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct GitExposure {
+    /// The file is in the index (`git ls-files --error-unmatch` succeeds).
+    pub tracked: bool,
+    /// The file is inside a work tree and not matched by any ignore rule.
+    pub unignored: bool,
+}
+
+fn git_ok(dir: &Path, args: &[&str]) -> Option<bool> {
+    Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()
+        .map(|o| o.status.success())
+}
+
+/// Exposure of `file` (relative to `dir`). All false when `dir` is not in a git
+/// work tree or git is not installed.
+pub(crate) fn exposure(dir: &Path, file: &str) -> GitExposure {
+    if git_ok(dir, &["rev-parse", "--is-inside-work-tree"]) != Some(true) {
+        return GitExposure::default();
+    }
+    let tracked = git_ok(dir, &["ls-files", "--error-unmatch", "--", file]).unwrap_or(false);
+    // check-ignore exits 0 when the path is ignored, 1 when it is not.
+    let ignored = git_ok(dir, &["check-ignore", "-q", "--", file]).unwrap_or(true);
+    GitExposure { tracked, unignored: !ignored }
+}
+
+/// One warning per exposed file, tracked files first.
+pub(crate) fn warnings(keys: &[PlaintextKey]) -> Vec<String> {
+    let mut files: Vec<(&str, bool)> = Vec::new();
+    for k in keys.iter().filter(|k| k.git_tracked || k.git_unignored) {
+        if !files.iter().any(|(f, _)| *f == k.file) {
+            files.push((&k.file, k.git_tracked));
+        }
+    }
+    files.sort_by_key(|(_, tracked)| !tracked);
+    files
+        .into_iter()
+        .map(|(file, tracked)| {
+            if tracked {
+                format!("{} contains secrets and is tracked by git; they may already be in the repository history", file)
+            } else {
+                format!("{} contains secrets and is not git-ignored; add it to .gitignore before committing", file)
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,74 @@
+//! Structured secret search in JSON configs: walks the parsed tree instead of
+//! matching lines, so multi-line values and provider arrays are handled.
+
+use crate::detect_patterns::KeyPattern;
+
+/// Field names (case-insensitive suffix) that hold credentials.
+const SECRET_FIELDS: &[&str] = &["apikey", "api_key", "token", "secret"];
+
+#[derive(Debug, Clone)]
+pub(crate) struct JsonSecret {
+    /// Name of the pattern whose prefix the value matched.
+    pub key_name: String,
+    /// RFC 6901 pointer to the value, e.g. `/models/providers/0/apiKey`.
+    pub pointer: String,
+    pub value: String,
+}
+
+/// Parse JSON, tolerating whole-line `//` comments (OpenClaw configs are JSON5-ish).
+pub(crate) fn parse_lenient(content: &str) -> Option<serde_json::Value> {
+    let stripped: String = content
+        .lines()
+        .map(|l| if l.trim().starts_with("//") { "" } else { l })
+        .collect::<Vec<&str>>()
+        .join("\n");
+    serde_json::from_str(&stripped)
+        .or_else(|_| serde_json::from_str(content))
+        .ok()
+}
+
+/// Every secret-named string field whose value matches a pattern with a prefix
+/// or regex. Placeholders and `$VAR`/`VAULT0_ALIAS:` references are ignored.
+pub(crate) fn find_secrets(root: &serde_json::Value, patterns: &[KeyPattern]) -> Vec<JsonSecret> {
+    let specific: Vec<&KeyPattern> = patterns.iter().filter(|p| p.regex || !p.prefix.is_empty()).collect();
+    let mut out = Vec::new();
+    walk(root, String::new(), "", &specific, &mut out);
+    out
+}
+
+fn escape(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+fn walk(value: &serde_json::Value, pointer: String, field: &str, patterns: &[&KeyPattern], out: &mut Vec<JsonSecret>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (k, v) in map {
+                walk(v, format!("{}/{}", pointer, escape(k)), k, patterns, out);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for (i, v) in items.iter().enumerate() {
+                walk(v, format!("{}/{}", pointer, i), field, patterns, out);
+            }
+        }
+        serde_json::Value::String(s) => {
+            let lower = field.to_lowercase();
+            if !SECRET_FIELDS.iter().any(|f| lower.ends_with(f)) {
+                return;
+            }
+            if s.is_empty() || s.starts_with('$') || s.starts_with("VAULT0_ALIAS") {
+                return;
+            }
+            // Prefer the most specific pattern (`sk-ant-` over `sk-`).
+            let best = patterns
+                .iter()
+                .filter(|p| p.matches(s))
+                .max_by_key(|p| if p.regex { usize::MAX } else { p.prefix.len() });
+            if let Some(p) = best {
+                out.push(JsonSecret { key_name: p.name.clone(), pointer, value: s.clone() });
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,236 @@
+//! Scan ~/.openclaw for plaintext secrets that are not in the vault yet, either
+//! on demand or continuously via a file watcher that notifies the UI.
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use tracing::{info, warn};
+
+use crate::detect::{guess_provider, home_dir, line_of};
+use crate::{detect_json, detect_patterns, detect_providers, evidence};
+
+/// Tauri event carrying the `NewSecretFound`s that appeared since the last scan.
+const NEW_SECRETS_EVENT: &str = "new-secrets";
+/// Files under ~/.openclaw whose changes trigger a rescan.
+const WATCHED_FILES: &[&str] = &[".env", "openclaw.json", "auth-profiles.json"];
+
+static APP: OnceCell<AppHandle> = OnceCell::new();
+static WATCHER: Lazy<Mutex<Option<RecommendedWatcher>>> = Lazy::new(|| Mutex::new(None));
+/// Findings already reported, so each plaintext key is announced once.
+static SEEN: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Called once from the Tauri setup hook so watcher findings reach the UI.
+pub fn set_app_handle(app: AppHandle) {
+    let _ = APP.set(app);
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NewSecretFound {
+    pub key_name: String,
+    pub file: String,
+    pub provider: String,
+    pub preview: String,
+    /// JSON pointer to the value for secrets found in JSON configs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pointer: Option<String>,
+    /// 1-based line in `file`; 0 when it could not be located.
+    pub line: usize,
+    /// Vault alias for a key inside a providers array (`openai-provider-2`);
+    /// secure it with `secure_provider_key`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+}
+
+#[tauri::command]
+pub fn scan_for_new_secrets() -> Result<Vec<NewSecretFound>, String> {
+    let home = home_dir().ok_or("Home directory not found")?;
+    let openclaw_dir = home.join(".openclaw");
+
+    // Get existing vault entries for comparison
+    let vault_entries = crate::vault_store::vault_list_entries().unwrap_or_default();
+    let vault_aliases: std::collections::HashSet<String> = vault_entries.iter()
+        .map(|e| e.alias.to_lowercase().replace('-', "_"))
+        .collect();
+
+    let mut new_secrets: Vec<NewSecretFound> = Vec::new();
+    let patterns = detect_patterns::effective_patterns();
+
+    // Scan .env
+    let env_path = openclaw_dir.join(".env");
+    if env_path.exists() {
+        if let Ok(content) = fs::read_to_string(&env_path) {
+            for (idx, line) in content.lines().enumerate() {
+                let trimmed = line.trim();
+                if trimmed.is_empty() || trimmed.starts_with('#') {
+                    continue;
+                }
+                if let Some(eq) = trimmed.find('=') {
+                    let key = trimmed[..eq].trim();
+                    let val = trimmed[eq + 1..].trim().trim_matches('"').trim_matches('\'');
+                    if detect_patterns::is_placeholder(val) {
+                        continue;
+                    }
+                    let normalized = key.to_lowercase().replace('-', "_");
+                    if !vault_aliases.contains(&normalized) {
+                        new_secrets.push(NewSecretFound {
+                            key_name: key.to_string(),
+                            file: ".env".to_string(),
+                            provider: guess_provider(key),
+                            preview: detect_patterns::preview(val, true),
+                            pointer: None,
+                            line: idx + 1,
+                            alias: None,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    // Scan openclaw.json: provider array entries first, each reported with its
+    // own alias, then the rest of the tree for inline apiKey/token/secret values
+    let config_path = openclaw_dir.join("openclaw.json");
+    let content = fs::read_to_string(&config_path).unwrap_or_default();
+    if let Some(root) = detect_json::parse_lenient(&content) {
+        let provider_keys = detect_providers::find(&root);
+        for pk in &provider_keys {
+            if vault_aliases.contains(&pk.alias.to_lowercase().replace('-', "_")) {
+                continue;
+            }
+            new_secrets.push(NewSecretFound {
+                key_name: format!("{} ({})", pk.provider, pk.alias),
+                file: "openclaw.json".to_string(),
+                provider: guess_provider(&pk.provider),
+                preview: detect_patterns::preview(&pk.value, true),
+                pointer: Some(pk.pointer.clone()),
+                line: line_of(&content, &pk.value),
+                alias: Some(pk.alias.clone()),
+            });
+        }
+        for hit in detect_json::find_secrets(&root, &patterns) {
+            if provider_keys.iter().any(|pk| pk.pointer == hit.pointer) {
+                continue;
+            }
+            let normalized = hit.key_name.to_lowercase().replace('-', "_");
+            if vault_aliases.contains(&normalized) {
+                continue;
+            }
+            let preview = detect_patterns::preview(&hit.value, true);
+            let provider = match guess_provider(&hit.key_name).as_str() {
+                "unknown" => guess_provider(&hit.pointer),
+                p => p.to_string(),
+            };
+            new_secrets.push(NewSecretFound {
+                key_name: hit.key_name,
+                file: "openclaw.json".to_string(),
+                provider,
+                preview,
+                pointer: Some(hit.pointer),
+                // serde_json keeps no spans; locate the value's text instead.
+                line: line_of(&content, &hit.value),
+                alias: None,
+            });
+        }
+    }
+
+    // Scan auth-profiles.json
+    let auth_path = openclaw_dir.join("auth-profiles.json");
+    if auth_path.exists() {
+        if let Ok(content) = fs::read_to_string(&auth_path) {
+            for pattern in &patterns {
+                let key_name = pattern.name.as_str();
+                if content.contains(key_name) {
+                    let normalized = key_name.to_lowercase().replace('-', "_");
+                    if !vault_aliases.contains(&normalized) {
+                        new_secrets.push(NewSecretFound {
+                            key_name: key_name.to_string(),
+                            file: "auth-profiles.json".to_string(),
+                            provider: guess_provider(key_name),
+                            preview: "****".to_string(),
+                            pointer: None,
+                            line: line_of(&content, key_name),
+                            alias: None,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(new_secrets)
+}
+
+fn finding_key(s: &NewSecretFound) -> String {
+    format!("{}|{}|{}", s.file, s.key_name, s.preview)
+}
+
+/// Rescan and emit only findings not reported before.
+fn rescan() {
+    let found = match scan_for_new_secrets() {
+        Ok(f) => f,
+        Err(e) => {
+            warn!("Secret watcher scan failed: {}", e);
+            return;
+        }
+    };
+    let fresh: Vec<NewSecretFound> = match SEEN.lock() {
+        Ok(mut seen) => found.into_iter().filter(|s| seen.insert(finding_key(s))).collect(),
+        Err(_) => return,
+    };
+    if fresh.is_empty() {
+        return;
+    }
+    for s in &fresh {
+        evidence::push("warning", &format!("New plaintext secret {} in {}", s.key_name, s.file));
+    }
+    if let Some(app) = APP.get() {
+        let _ = app.emit(NEW_SECRETS_EVENT, fresh);
+    }
+}
+
+/// Start or stop watching ~/.openclaw for plaintext keys drifting back in.
+/// Secrets present when the watcher starts are treated as already known.
+#[tauri::command]
+pub fn secret_watch(enabled: bool) -> Result<(), String> {
+    let mut guard = WATCHER.lock().map_err(|_| "watcher lock")?;
+    *guard = None;
+    if !enabled {
+        info!("Secret watcher stopped");
+        return Ok(());
+    }
+    let dir = home_dir().ok_or("Home directory not found")?.join(".openclaw");
+    if let Ok(mut seen) = SEEN.lock() {
+        *seen = scan_for_new_secrets().unwrap_or_default().iter().map(finding_key).collect();
+    }
+    let mut watcher = notify::recommended_watcher(|res: notify::Result<notify::Event>| {
+        let event = match res {
+            Ok(e) => e,
+            Err(e) => {
+                warn!("Secret watcher error: {}", e);
+                return;
+            }
+        };
+        if !(event.kind.is_modify() || event.kind.is_create()) {
+            return;
+        }
+        let relevant = event.paths.iter().any(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| WATCHED_FILES.contains(&n))
+        });
+        if relevant {
+            rescan();
+        }
+    })
+    .map_err(|e| format!("secret watcher: {e}"))?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("watch {}: {e}", dir.display()))?;
+    *guard = Some(watcher);
+    info!("Secret watcher started on {}", dir.display());
+    Ok(())
+}
@@ -0,0 +1,151 @@
+//! Key patterns used when scanning configs for plaintext secrets: the built-in
+//! list plus user-defined patterns persisted in settings.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Built-in (key name, value prefix) pairs. An empty prefix matches any value.
+const BUILTIN_PATTERNS: &[(&str, &str)] = &[
+    ("OPENAI_API_KEY", "sk-"),
+    ("ANTHROPIC_API_KEY", "sk-ant-"),
+    ("GROK_API_KEY", "xai-"),
+    ("TELEGRAM_BOT_TOKEN", ""),
+    ("SLACK_TOKEN", "xoxb-"),
+    ("DISCORD_TOKEN", ""),
+    ("GITHUB_TOKEN", "ghp_"),
+    ("API_KEY", ""),
+    ("SECRET_KEY", ""),
+    ("PRIVATE_KEY", ""),
+];
+
+/// A user-defined pattern as stored in settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserKeyPattern {
+    pub name: String,
+    /// Value prefix, or a regex the whole value must match when `regex` is set.
+    pub prefix: String,
+    #[serde(default)]
+    pub regex: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyPattern {
+    pub name: String,
+    pub prefix: String,
+    pub regex: bool,
+    pub builtin: bool,
+    #[serde(skip)]
+    compiled: Option<Regex>,
+}
+
+impl KeyPattern {
+    /// Whether a config value looks like a secret for this pattern.
+    pub fn matches(&self, value: &str) -> bool {
+        match &self.compiled {
+            Some(re) => re.is_match(value),
+            None => self.prefix.is_empty() || value.starts_with(&self.prefix),
+        }
+    }
+}
+
+/// Anchor a user regex so it must match the whole value.
+fn compile(pattern: &str) -> Result<Regex, String> {
+    Regex::new(&format!("^(?:{})$", pattern)).map_err(|e| format!("invalid pattern regex: {e}"))
+}
+
+/// Built-ins followed by user patterns. User regexes that no longer compile are skipped.
+pub(crate) fn effective_patterns() -> Vec<KeyPattern> {
+    let builtin = BUILTIN_PATTERNS.iter().map(|(name, prefix)| KeyPattern {
+        name: name.to_string(),
+        prefix: prefix.to_string(),
+        regex: false,
+        builtin: true,
+        compiled: None,
+    });
+    let user = crate::settings::get().key_patterns.into_iter().filter_map(|p| {
+        let compiled = if p.regex { Some(compile(&p.prefix).ok()?) } else { None };
+        Some(KeyPattern { name: p.name, prefix: p.prefix, regex: p.regex, builtin: false, compiled })
+    });
+    builtin.chain(user).collect()
+}
+
+/// Empty values, `$VAR` / `VAULT0_ALIAS:` references and sample values that
+/// are not real secrets.
+pub(crate) fn is_placeholder(value: &str) -> bool {
+    value.is_empty()
+        || value.starts_with('$')
+        || value.starts_with("VAULT0_ALIAS")
+        || matches!(value, "your-key-here" | "CHANGE_ME" | "xxx")
+}
+
+/// Masked form of a secret for display: the first four characters, then the
+/// last four when `show_tail` is set. Short values are fully masked. Counts
+/// chars rather than bytes so non-ASCII values can't split a code point.
+pub(crate) fn preview(value: &str, show_tail: bool) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 8 {
+        return "****".to_string();
+    }
+    let head: String = chars[..4].iter().collect();
+    if show_tail {
+        let tail: String = chars[chars.len() - 4..].iter().collect();
+        format!("{}...{}", head, tail)
+    } else {
+        format!("{}****", head)
+    }
+}
+
+fn save_user_patterns(patterns: Vec<UserKeyPattern>) -> Result<(), String> {
+    let value = serde_json::to_value(patterns).map_err(|e| e.to_string())?;
+    crate::settings::update_settings(serde_json::json!({ "key_patterns": value }))?;
+    Ok(())
+}
+
+/// Add (or replace) a user pattern. With `regex`, `prefix` is a regex matched against the whole value.
+#[tauri::command]
+pub fn detect_add_pattern(name: String, prefix: String, regex: Option<bool>) -> Result<Vec<KeyPattern>, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Pattern name is required".into());
+    }
+    let regex = regex.unwrap_or(false);
+    if regex {
+        compile(&prefix)?;
+    }
+    let mut patterns = crate::settings::get().key_patterns;
+    patterns.retain(|p| p.name != name);
+    patterns.push(UserKeyPattern { name, prefix, regex });
+    save_user_patterns(patterns)?;
+    Ok(effective_patterns())
+}
+
+#[tauri::command]
+pub fn detect_remove_pattern(name: String) -> Result<Vec<KeyPattern>, String> {
+    let mut patterns = crate::settings::get().key_patterns;
+    patterns.retain(|p| p.name != name);
+    save_user_patterns(patterns)?;
+    Ok(effective_patterns())
+}
+
+/// Every pattern the scanners currently use, built-ins first.
+#[tauri::command]
+pub fn detect_list_patterns() -> Result<Vec<KeyPattern>, String> {
+    Ok(effective_patterns())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::preview;
+
+    #[test]
+    fn preview_masks_short_values() {
+        assert_eq!(preview("sk-1234", true), "****");
+    }
+
+    #[test]
+    fn preview_slices_on_char_boundaries() {
+        assert_eq!(preview("sk-ÄÖÜ-geheim-ß€", false), "sk-Ä****");
+        assert_eq!(preview("sk-ÄÖÜ-geheim-ß€", true), "sk-Ä...m-ß€");
+        assert_eq!(preview("ключ-секрет-значение", true), "ключ...ение");
+    }
+}
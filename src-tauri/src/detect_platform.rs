@@ -0,0 +1,102 @@
+//! Platform-specific locations used by detection: search roots per OS, user-added
+//! search directories, and a PATH lookup that works without a POSIX shell.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Short OS label reported alongside detection results (`macos`, `windows`, `linux`, ...).
+pub(crate) fn platform() -> &'static str {
+    std::env::consts::OS
+}
+
+/// Directories that `SEARCH_DIRS` are resolved against: the home directory plus
+/// the per-user config/data roots (`%APPDATA%`/`%LOCALAPPDATA%` on Windows,
+/// XDG config/data dirs on Linux, `~/Library/Application Support` on macOS).
+pub(crate) fn search_roots() -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = Vec::new();
+    let candidates = [dirs::home_dir(), dirs::config_dir(), dirs::data_local_dir(), dirs::data_dir()];
+    for dir in candidates.into_iter().flatten() {
+        if !roots.contains(&dir) {
+            roots.push(dir);
+        }
+    }
+    roots
+}
+
+/// Expand a leading `~` to the home directory.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest.trim_start_matches(['/', '\\'])),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// User-added directories from settings. Each is checked as an install itself
+/// and used as an extra root for `SEARCH_DIRS`.
+pub(crate) fn extra_search_dirs() -> Vec<PathBuf> {
+    crate::settings::get().extra_search_dirs.iter().map(|p| expand_home(p)).collect()
+}
+
+fn save_search_dirs(dirs: Vec<String>) -> Result<Vec<String>, String> {
+    crate::settings::update_settings(serde_json::json!({ "extra_search_dirs": dirs }))
+        .map(|s| s.extra_search_dirs)
+}
+
+#[tauri::command]
+pub fn detect_add_search_dir(path: String) -> Result<Vec<String>, String> {
+    let path = path.trim().to_string();
+    if !expand_home(&path).is_dir() {
+        return Err(format!("Not a directory: {path}"));
+    }
+    let mut dirs = crate::settings::get().extra_search_dirs;
+    if !dirs.contains(&path) {
+        dirs.push(path);
+    }
+    save_search_dirs(dirs)
+}
+
+#[tauri::command]
+pub fn detect_remove_search_dir(path: String) -> Result<Vec<String>, String> {
+    let mut dirs = crate::settings::get().extra_search_dirs;
+    dirs.retain(|d| d != path.trim());
+    save_search_dirs(dirs)
+}
+
+#[tauri::command]
+pub fn detect_list_search_dirs() -> Result<Vec<String>, String> {
+    Ok(crate::settings::get().extra_search_dirs)
+}
+
+/// Locate an executable on PATH, honouring PATHEXT on Windows. On Unix a login
+/// shell is consulted as a fallback, since GUI apps often start with a minimal PATH.
+pub(crate) fn find_on_path(bin: &str) -> Option<PathBuf> {
+    let exts: Vec<String> = if cfg!(windows) {
+        std::env::var("PATHEXT")
+            .unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".into())
+            .split(';')
+            .filter(|e| !e.is_empty())
+            .map(|e| e.to_lowercase())
+            .collect()
+    } else {
+        vec![String::new()]
+    };
+    if let Some(path) = std::env::var_os("PATH") {
+        for dir in std::env::split_paths(&path) {
+            for ext in &exts {
+                let candidate = dir.join(format!("{}{}", bin, ext));
+                if candidate.is_file() {
+                    return Some(candidate);
+                }
+            }
+        }
+    }
+    if cfg!(windows) {
+        return None;
+    }
+    let out = Command::new("sh")
+        .args(["-lc", &format!("command -v {}", bin)])
+        .output()
+        .ok()?;
+    let found = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    (out.status.success() && !found.is_empty()).then(|| PathBuf::from(found))
+}
@@ -0,0 +1,111 @@
+//! Inline keys in the `providers` arrays of openclaw.json. Each provider entry
+//! is reported on its own with a vault alias derived from its name and position
+//! (`openai-provider-2`), so the user can secure them one at a time.
+
+use serde_json::Value;
+use std::fs;
+
+use crate::detect::{guess_provider, home_dir};
+use crate::detect_patterns::is_placeholder;
+use crate::{detect_json, detect_rewrite, evidence};
+
+/// Entry fields that hold the provider's credential.
+const KEY_FIELDS: &[&str] = &["apiKey", "api_key", "key", "token"];
+/// Entry fields naming the provider, in order of preference.
+const NAME_FIELDS: &[&str] = &["name", "id", "provider"];
+
+#[derive(Debug, Clone)]
+pub(crate) struct ProviderKey {
+    /// Provider name as written in the entry, or guessed from its base URL.
+    pub provider: String,
+    /// Suggested vault alias, e.g. `openai-provider-2` for the second entry.
+    pub alias: String,
+    /// RFC 6901 pointer to the key, e.g. `/models/providers/1/apiKey`.
+    pub pointer: String,
+    pub value: String,
+}
+
+fn slug(name: &str) -> String {
+    let s: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    s.split('-').filter(|p| !p.is_empty()).collect::<Vec<_>>().join("-")
+}
+
+fn provider_name(entry: &Value) -> String {
+    let named = NAME_FIELDS.iter().find_map(|f| entry.get(f).and_then(Value::as_str)).map(slug);
+    if let Some(name) = named.filter(|n| !n.is_empty()) {
+        return name;
+    }
+    let url = entry.get("baseUrl").or_else(|| entry.get("base_url")).and_then(Value::as_str).unwrap_or("");
+    match guess_provider(url).as_str() {
+        "unknown" => "custom".to_string(),
+        p => p.to_string(),
+    }
+}
+
+/// Every inline key in any `providers` array of the config.
+pub(crate) fn find(root: &Value) -> Vec<ProviderKey> {
+    let mut out = Vec::new();
+    walk(root, String::new(), &mut out);
+    out
+}
+
+fn walk(value: &Value, pointer: String, out: &mut Vec<ProviderKey>) {
+    let Value::Object(map) = value else { return };
+    for (k, v) in map {
+        let path = format!("{}/{}", pointer, k.replace('~', "~0").replace('/', "~1"));
+        match v {
+            Value::Array(entries) if k == "providers" => {
+                for (i, entry) in entries.iter().enumerate() {
+                    let Some((field, key)) =
+                        KEY_FIELDS.iter().find_map(|f| entry.get(f).and_then(Value::as_str).map(|s| (f, s)))
+                    else {
+                        continue;
+                    };
+                    if is_placeholder(key) {
+                        continue;
+                    }
+                    let provider = provider_name(entry);
+                    out.push(ProviderKey {
+                        alias: format!("{}-provider-{}", provider, i + 1),
+                        provider,
+                        pointer: format!("{}/{}/{}", path, i, field),
+                        value: key.to_string(),
+                    });
+                }
+            }
+            _ => walk(v, path, out),
+        }
+    }
+}
+
+/// Move one provider's inline key (by pointer, as reported by
+/// `scan_for_new_secrets`) into the vault and replace it in openclaw.json with
+/// `VAULT0_ALIAS:<alias>`. An identical key used by another entry is replaced
+/// too, since it is now the same vault secret. Returns the alias.
+#[tauri::command]
+pub fn secure_provider_key(pointer: String) -> Result<String, String> {
+    let path = home_dir().ok_or("Home directory not found")?.join(".openclaw").join("openclaw.json");
+    let content = fs::read_to_string(&path).map_err(|e| format!("read openclaw.json: {}", e))?;
+    let root = detect_json::parse_lenient(&content).ok_or("parse openclaw.json: not valid JSON5")?;
+    let hit = find(&root)
+        .into_iter()
+        .find(|k| k.pointer == pointer)
+        .ok_or_else(|| format!("No inline provider key at {}", pointer))?;
+    let vault_provider = match guess_provider(&hit.provider).as_str() {
+        "unknown" => hit.provider.clone(),
+        p => p.to_string(),
+    };
+    crate::vault_store::vault_add_entry(hit.alias.clone(), hit.value.clone(), vault_provider, None, None)?;
+    if let Ok(mut state) = crate::proxy::state().write() {
+        state.vault.insert(hit.alias.clone(), zeroize::Zeroizing::new(hit.value.clone()));
+    }
+    let quoted = serde_json::to_string(&hit.value).map_err(|e| e.to_string())?;
+    let updated = content.replace(&quoted, &format!("\"VAULT0_ALIAS:{}\"", hit.alias));
+    detect_rewrite::write_checked(&path, "openclaw.json", &content, &updated)?;
+    evidence::push("info", &format!("Secured {} key at {} as {}", hit.provider, pointer, hit.alias));
+    Ok(hit.alias)
+}
@@ -0,0 +1,72 @@
+//! Rewriting install config files between plaintext secrets and
+//! `VAULT0_ALIAS:<alias>` placeholders.
+
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+use crate::{detect_json, detect_walk};
+
+/// Write a rewritten config, refusing if a JSON file that parsed before would
+/// no longer parse (a secret containing JSON-significant characters, or a value
+/// that appeared inside a larger token). The original is left untouched then.
+pub(crate) fn write_checked(path: &Path, file: &str, original: &str, updated: &str) -> Result<(), String> {
+    let is_json = file.to_lowercase().ends_with(".json");
+    if is_json
+        && detect_json::parse_lenient(original).is_some()
+        && detect_json::parse_lenient(updated).is_none()
+    {
+        let msg = format!("could not safely secure {file}: the result would not be valid JSON");
+        tracing::warn!("{}", msg);
+        return Err(msg);
+    }
+    fs::write(path, updated).map_err(|e| format!("write {file}: {e}"))
+}
+
+/// Matches `VAULT0_ALIAS:<alias>` only where the alias ends, so `openai` does
+/// not also rewrite `VAULT0_ALIAS:openai_org`.
+fn placeholder_regex(alias: &str) -> Result<Regex, String> {
+    Regex::new(&format!(r"VAULT0_ALIAS:{}([^A-Za-z0-9_\-]|$)", regex::escape(alias)))
+        .map_err(|e| format!("alias pattern: {e}"))
+}
+
+/// Put the real secrets back in place of their placeholders, reading each value
+/// from the unlocked vault. Returns how many placeholders were replaced.
+/// This re-exposes secrets on disk and is logged as an evidence warning.
+#[tauri::command]
+pub fn unsecure_config_keys(install_path: String, aliases: Vec<String>) -> Result<u32, String> {
+    let mut replacements: Vec<(Regex, String)> = Vec::new();
+    for alias in &aliases {
+        let value = crate::vault_store::vault_get_secret(alias.clone())?;
+        replacements.push((placeholder_regex(alias)?, value));
+    }
+    let dir = Path::new(&install_path);
+    if !dir.is_dir() {
+        return Err(format!("Install path does not exist: {install_path}"));
+    }
+    let mut restored = 0u32;
+    for config_file in detect_walk::config_files(dir) {
+        let file_path = dir.join(&config_file);
+        let Ok(content) = fs::read_to_string(&file_path) else { continue };
+        let mut new_content = content.clone();
+        for (re, value) in &replacements {
+            restored += re.find_iter(&new_content).count() as u32;
+            new_content = re
+                .replace_all(&new_content, |caps: &regex::Captures| format!("{}{}", value, &caps[1]))
+                .into_owned();
+        }
+        if new_content != content {
+            write_checked(&file_path, &config_file, &content, &new_content)?;
+        }
+    }
+    crate::evidence::push(
+        "warning",
+        &format!(
+            "Restored {} plaintext secret(s) to disk in {} (aliases: {})",
+            restored,
+            install_path,
+            aliases.join(", ")
+        ),
+    );
+    Ok(restored)
+}
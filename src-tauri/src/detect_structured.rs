@@ -0,0 +1,95 @@
+//! Structured secret search in TOML and INI configs. Values are matched by
+//! their full key path (`[openai] api_key` → `openai.api_key`) instead of by line.
+
+use std::fs;
+use std::path::Path;
+
+use crate::detect_patterns::{is_placeholder, KeyPattern};
+
+/// Whether `file` is parsed structurally here rather than line-scanned.
+pub(crate) fn is_structured(file: &str) -> bool {
+    let lower = file.to_lowercase();
+    lower.ends_with(".toml") || lower.ends_with(".ini")
+}
+
+/// Every string leaf as `(dotted key path, value)`.
+fn leaves(file: &str, content: &str) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    if file.to_lowercase().ends_with(".toml") {
+        if let Ok(root) = content.parse::<toml::Table>() {
+            for (k, v) in &root {
+                walk_toml(v, k.clone(), &mut out);
+            }
+        }
+    } else {
+        parse_ini(content, &mut out);
+    }
+    out
+}
+
+fn walk_toml(value: &toml::Value, path: String, out: &mut Vec<(String, String)>) {
+    match value {
+        toml::Value::String(s) => out.push((path, s.clone())),
+        toml::Value::Table(t) => {
+            for (k, v) in t {
+                walk_toml(v, format!("{}.{}", path, k), out);
+            }
+        }
+        toml::Value::Array(items) => {
+            for (i, v) in items.iter().enumerate() {
+                walk_toml(v, format!("{}.{}", path, i), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Minimal INI: `[section]` headers, `key = value` or `key: value`, `;`/`#` comments.
+fn parse_ini(content: &str, out: &mut Vec<(String, String)>) {
+    let mut section = String::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.trim().to_string();
+            continue;
+        }
+        let Some(sep) = line.find(['=', ':']) else { continue };
+        let key = line[..sep].trim();
+        let value = line[sep + 1..].trim().trim_matches('"').trim_matches('\'');
+        let path = if section.is_empty() { key.to_string() } else { format!("{}.{}", section, key) };
+        out.push((path, value.to_string()));
+    }
+}
+
+/// `(pattern name, key path, value)` for each leaf whose path names a pattern
+/// (e.g. `openai.api_key` ~ `OPENAI_API_KEY`) and whose value matches it.
+/// When several patterns apply, the longest name wins.
+pub(crate) fn find_keys(file: &str, content: &str, patterns: &[KeyPattern]) -> Vec<(String, String, String)> {
+    let mut found = Vec::new();
+    for (path, value) in leaves(file, content) {
+        if is_placeholder(&value) {
+            continue;
+        }
+        let flat = path.to_uppercase().replace(['.', '-'], "_");
+        let best = patterns
+            .iter()
+            .filter(|p| flat.contains(&p.name.to_uppercase()) && p.matches(&value))
+            .max_by_key(|p| p.name.len());
+        if let Some(p) = best {
+            found.push((p.name.clone(), path, value));
+        }
+    }
+    found
+}
+
+/// Current value at `key_path` in a TOML/INI file, for migrating it into the vault.
+pub(crate) fn raw_value(base: &Path, file: &str, key_path: &str) -> Option<String> {
+    let content = fs::read_to_string(base.join(file)).ok()?;
+    leaves(file, &content)
+        .into_iter()
+        .find(|(path, value)| path == key_path && !is_placeholder(value))
+        .map(|(_, value)| value)
+}
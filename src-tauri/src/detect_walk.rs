@@ -0,0 +1,58 @@
+//! Bounded recursive walk of an install directory for config files that may
+//! hold plaintext keys (`.env*`, `*.json`, `*.yaml`/`*.yml`, `*.toml`, `*.ini`).
+
+use std::fs;
+use std::path::Path;
+
+const MAX_DEPTH: usize = 5;
+/// Stop after this many candidate files so huge trees stay fast.
+const MAX_FILES: usize = 500;
+/// Larger files are skipped; real configs are small and this avoids bundles/binaries.
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+const SKIP_DIRS: &[&str] = &["node_modules", ".git", "target", "dist", "build", ".venv", "venv", "__pycache__"];
+const SKIP_FILES: &[&str] = &["package-lock.json", "pnpm-lock.yaml", "yarn.lock", "tsconfig.json", "Cargo.toml", "Cargo.lock"];
+
+fn is_config_name(name: &str) -> bool {
+    if SKIP_FILES.contains(&name) {
+        return false;
+    }
+    let lower = name.to_lowercase();
+    lower.starts_with(".env")
+        || lower.ends_with(".json")
+        || lower.ends_with(".yaml")
+        || lower.ends_with(".yml")
+        || lower.ends_with(".toml")
+        || lower.ends_with(".ini")
+}
+
+/// Candidate config files under `dir`, as `/`-separated paths relative to it.
+/// Symlinks are not followed.
+pub(crate) fn config_files(dir: &Path) -> Vec<String> {
+    let mut out = Vec::new();
+    walk(dir, "", 0, &mut out);
+    out
+}
+
+fn walk(dir: &Path, rel: &str, depth: usize, out: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    let mut entries: Vec<_> = entries.flatten().collect();
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        if out.len() >= MAX_FILES {
+            return;
+        }
+        let Ok(ty) = entry.file_type() else { continue };
+        let name = entry.file_name().to_string_lossy().to_string();
+        let rel_path = if rel.is_empty() { name.clone() } else { format!("{}/{}", rel, name) };
+        if ty.is_dir() {
+            if depth + 1 < MAX_DEPTH && !SKIP_DIRS.contains(&name.as_str()) {
+                walk(&entry.path(), &rel_path, depth + 1, out);
+            }
+        } else if ty.is_file() && is_config_name(&name) {
+            let small = entry.metadata().map(|m| m.len() <= MAX_FILE_BYTES).unwrap_or(false);
+            if small {
+                out.push(rel_path);
+            }
+        }
+    }
+}
@@ -1,36 +1,209 @@
-use once_cell::sync::Lazy;
-use serde::Serialize;
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::VecDeque;
-use std::sync::RwLock;
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, RwLock};
+use tauri::{AppHandle, Emitter};
 
-const LOG_CAP: usize = 500;
+pub use crate::evidence_disk::{read_persisted, read_tail};
 
-#[derive(Debug, Clone, Serialize)]
+/// Tauri event carrying each new `LogEntry`; the ring buffer remains for backfill.
+const EVIDENCE_EVENT: &str = "evidence";
+/// `prev_hash` of the first entry in a fresh log.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+/// Most entries `get_evidence_log` and `get_evidence_log_filtered` read back
+/// from disk, unless the ring is configured larger; the full history is only
+/// read for export and reports.
+const MAX_TAIL: usize = 5000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogEntry {
     pub ts: String,
     pub kind: String,
     pub msg: String,
+    #[serde(flatten)]
+    pub fields: EvidenceFields,
+    /// Position in the log, one more than the entry before it, so pollers can
+    /// resume without relying on `ts`. Zero on entries written before it existed.
+    #[serde(default)]
+    pub seq: u64,
+    /// `hash` of the entry before this one, chaining the log. Empty on entries
+    /// written before the chain existed.
+    #[serde(default)]
+    pub prev_hash: String,
+    /// SHA-256 over `prev_hash`, `ts`, `kind`, `msg`, `seq` and the fields, see `chain_hash`. Hex.
+    #[serde(default)]
+    pub hash: String,
+}
+
+/// Optional structured data attached to an entry so the UI doesn't have to parse `msg`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EvidenceFields {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    /// Guard that produced the entry, e.g. `mcp_origin`, `mcp_ssrf`, `mcp_token_passthrough`, `upstream`, `body_limit`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub check: Option<String>,
+    /// Address the target host resolved to, when it was looked up.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_ip: Option<String>,
+    /// Launched agent the entry belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    /// Vault alias whose secret the proxy injected (the alias only, never the value).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    /// HTTP method of the proxied request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// Amount of an x402 payment, in cents.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_cents: Option<u64>,
 }
 
+/// In-memory ring of recent entries for the live view; the JSONL file is the full history.
 static LOG: Lazy<RwLock<VecDeque<LogEntry>>> = Lazy::new(|| RwLock::new(VecDeque::new()));
+static LOG_CAP: AtomicUsize = AtomicUsize::new(500);
+static APP: OnceCell<AppHandle> = OnceCell::new();
+/// Hash and `seq` of the newest entry, continuing the chain from the persisted log.
+static CHAIN_HEAD: Lazy<Mutex<ChainHead>> = Lazy::new(|| {
+    let last = read_tail(1).pop();
+    Mutex::new(ChainHead {
+        hash: last.as_ref().map(|e| e.hash.clone()).filter(|h| !h.is_empty()).unwrap_or_else(|| GENESIS_HASH.to_string()),
+        seq: last.map_or(0, |e| e.seq),
+        unwritten: 0,
+    })
+});
+
+struct ChainHead {
+    hash: String,
+    seq: u64,
+    /// Entries the disk queue refused since the last one it took; recorded
+    /// in the chain as a `gap` entry once the queue has room.
+    unwritten: u64,
+}
 
 pub fn push(kind: &str, msg: &str) {
-    let ts = chrono_ts();
-    let entry = LogEntry {
-        ts: ts.clone(),
+    push_structured(kind, msg, EvidenceFields::default());
+}
+
+pub fn push_structured(kind: &str, msg: &str, fields: EvidenceFields) {
+    let mut entry = new_entry(kind, msg, fields);
+    // Link and queue under one lock so the file keeps chain order.
+    let gap = {
+        let mut head = CHAIN_HEAD.lock().unwrap_or_else(|e| e.into_inner());
+        let gap = record_gap(&mut head);
+        if !link_and_queue(&mut head, &mut entry) {
+            head.unwritten += 1;
+        }
+        gap
+    };
+    if let Some(gap) = gap {
+        add_to_ring(gap);
+    }
+    crate::metrics::record(&entry);
+    if entry.kind == "blocked" {
+        crate::notify::blocked(entry.fields.domain.as_deref(), &entry.msg);
+    }
+    add_to_ring(entry);
+}
+
+fn new_entry(kind: &str, msg: &str, fields: EvidenceFields) -> LogEntry {
+    LogEntry {
+        ts: chrono_ts(),
         kind: kind.to_string(),
         msg: msg.to_string(),
-    };
+        fields,
+        seq: 0,
+        prev_hash: String::new(),
+        hash: String::new(),
+    }
+}
+
+/// Give `entry` the next `seq`, link it after the chain head and queue it for
+/// disk. The head only moves if the queue took the entry, so the persisted
+/// chain stays unbroken when entries are refused.
+fn link_and_queue(head: &mut ChainHead, entry: &mut LogEntry) -> bool {
+    head.seq += 1;
+    entry.seq = head.seq;
+    entry.prev_hash = head.hash.clone();
+    entry.hash = chain_hash(entry);
+    let queued = crate::evidence_disk::append(entry);
+    if queued {
+        head.hash.clone_from(&entry.hash);
+    }
+    queued
+}
+
+/// A `gap` entry for entries the disk queue refused, chained and queued ahead
+/// of the next entry. `None` when nothing was refused or the queue is still full.
+fn record_gap(head: &mut ChainHead) -> Option<LogEntry> {
+    if head.unwritten == 0 {
+        return None;
+    }
+    let mut gap = new_entry(
+        "gap",
+        &format!("{} evidence entries were not written to disk (write queue full)", head.unwritten),
+        EvidenceFields { check: Some("disk_queue".to_string()), ..Default::default() },
+    );
+    if !link_and_queue(head, &mut gap) {
+        return None;
+    }
+    head.unwritten = 0;
+    Some(gap)
+}
+
+/// Keep `entry` in the in-memory ring and send it to the UI.
+fn add_to_ring(entry: LogEntry) {
+    let emitted = APP.get().map(|app| (app, entry.clone()));
     if let Ok(mut g) = LOG.write() {
         g.push_back(entry);
-        while g.len() > LOG_CAP {
+        let cap = LOG_CAP.load(Ordering::Relaxed);
+        while g.len() > cap {
             g.pop_front();
         }
     }
+    if let Some((app, entry)) = emitted {
+        let _ = app.emit(EVIDENCE_EVENT, entry);
+    }
+}
+
+/// Called once from the Tauri setup hook so new entries are pushed to the UI.
+pub fn set_app_handle(app: AppHandle) {
+    let _ = APP.set(app);
 }
 
-fn chrono_ts() -> String {
+/// Seed the in-memory ring with the most recent persisted entries, so stats and
+/// the live view survive a restart. Called once at startup.
+pub fn load_tail() {
+    Lazy::force(&CHAIN_HEAD);
+    let cap = LOG_CAP.load(Ordering::Relaxed);
+    if let Ok(mut g) = LOG.write() {
+        for entry in read_tail(cap.saturating_sub(g.len())).into_iter().rev() {
+            g.push_front(entry);
+        }
+    }
+}
+
+/// Resize the in-memory ring, dropping the oldest entries if it shrinks. The caller clamps `cap`.
+pub fn set_log_cap(cap: usize) {
+    LOG_CAP.store(cap, Ordering::Relaxed);
+    if let Ok(mut g) = LOG.write() {
+        while g.len() > cap {
+            g.pop_front();
+        }
+    }
+}
+
+pub(crate) fn chrono_ts() -> String {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .map(|d| {
@@ -41,10 +214,71 @@ fn chrono_ts() -> String {
         .unwrap_or_else(|_| "0.000".to_string())
 }
 
+/// Longest read-back: `MAX_TAIL`, or the ring cap when that is larger.
+fn tail_limit() -> usize {
+    MAX_TAIL.max(LOG_CAP.load(Ordering::Relaxed))
+}
+
+/// The most recent `tail` entries, oldest first; without `tail`, the whole
+/// in-memory ring. A `tail` longer than the ring is read from the end of the
+/// persisted log, up to `tail_limit()` entries.
 #[tauri::command]
-pub fn get_evidence_log() -> Result<Vec<LogEntry>, String> {
+pub fn get_evidence_log(tail: Option<usize>) -> Result<Vec<LogEntry>, String> {
     let g = LOG.read().map_err(|_| "lock")?;
-    Ok(g.iter().cloned().collect())
+    let n = tail.unwrap_or(g.len()).min(tail_limit());
+    if n <= g.len() {
+        return Ok(g.iter().skip(g.len() - n).cloned().collect());
+    }
+    let ring: Vec<LogEntry> = g.iter().cloned().collect();
+    drop(g);
+    let persisted = read_tail(n);
+    Ok(if persisted.len() > ring.len() { persisted } else { ring })
+}
+
+fn ts_secs(ts: &str) -> f64 {
+    ts.parse().unwrap_or(0.0)
+}
+
+/// Entries of `kind` after `since_seq` and newer than `since_ts` (unix
+/// seconds, as in `ts`). A poller resumes from the last `seq` it saw: entries
+/// sharing a millisecond `ts` are not skipped. With either bound the oldest
+/// `limit` matches are returned; without, the newest `limit`. Polls that fall
+/// inside the in-memory ring don't touch the disk; others search the last
+/// `tail_limit()` entries.
+#[tauri::command]
+pub fn get_evidence_log_filtered(
+    kind: Option<String>,
+    since_ts: Option<f64>,
+    since_seq: Option<u64>,
+    limit: Option<usize>,
+) -> Result<Vec<LogEntry>, String> {
+    let in_ring = {
+        let g = LOG.read().map_err(|_| "lock")?;
+        let covered = match (since_seq, since_ts, g.front()) {
+            (Some(since), _, Some(oldest)) => oldest.seq <= since,
+            (None, Some(since), Some(oldest)) => ts_secs(&oldest.ts) <= since,
+            _ => false,
+        };
+        covered.then(|| g.iter().cloned().collect::<Vec<_>>())
+    };
+    let source = match in_ring {
+        Some(entries) => entries,
+        None => get_evidence_log(Some(tail_limit()))?,
+    };
+    let mut matched: Vec<LogEntry> = source
+        .into_iter()
+        .filter(|e| kind.as_deref().is_none_or(|k| e.kind.eq_ignore_ascii_case(k)))
+        .filter(|e| since_seq.is_none_or(|since| e.seq > since))
+        .filter(|e| since_ts.is_none_or(|since| ts_secs(&e.ts) > since))
+        .collect();
+    if let Some(n) = limit {
+        if since_seq.is_some() || since_ts.is_some() {
+            matched.truncate(n);
+        } else {
+            matched.drain(..matched.len().saturating_sub(n));
+        }
+    }
+    Ok(matched)
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -53,6 +287,9 @@ pub struct EvidenceStats {
     pub allowed: usize,
     pub blocked: usize,
     pub payment: usize,
+    pub domains: usize,
+    pub total_bytes: u64,
+    pub avg_latency_ms: u64,
 }
 
 #[tauri::command]
@@ -61,6 +298,9 @@ pub fn get_evidence_stats() -> Result<EvidenceStats, String> {
     let mut allowed = 0;
     let mut blocked = 0;
     let mut payment = 0;
+    let mut domains = std::collections::HashSet::new();
+    let mut total_bytes = 0u64;
+    let (mut latency_sum, mut latency_n) = (0u64, 0u64);
     for e in g.iter() {
         match e.kind.as_str() {
             "allowed" => allowed += 1,
@@ -68,20 +308,68 @@ pub fn get_evidence_stats() -> Result<EvidenceStats, String> {
             "payment" => payment += 1,
             _ => {}
         }
+        if let Some(d) = &e.fields.domain {
+            domains.insert(d.as_str());
+        }
+        total_bytes += e.fields.bytes.unwrap_or(0);
+        if let Some(ms) = e.fields.latency_ms {
+            latency_sum += ms;
+            latency_n += 1;
+        }
     }
     Ok(EvidenceStats {
         total: g.len(),
         allowed,
         blocked,
         payment,
+        domains: domains.len(),
+        total_bytes,
+        avg_latency_ms: latency_sum.checked_div(latency_n).unwrap_or(0),
     })
 }
 
+#[derive(Debug, Default, Serialize)]
+pub struct DomainStats {
+    pub domain: String,
+    pub total: usize,
+    pub allowed: usize,
+    pub blocked: usize,
+    pub payment: usize,
+    pub bytes: u64,
+}
+
+/// Per-host counts over the full persisted history, busiest hosts first.
+#[tauri::command]
+pub fn get_evidence_by_domain() -> Result<Vec<DomainStats>, String> {
+    let mut by_domain: std::collections::HashMap<String, DomainStats> = std::collections::HashMap::new();
+    for e in read_persisted() {
+        let Some(domain) = e.fields.domain else { continue };
+        let row = by_domain.entry(domain.clone()).or_insert_with(|| DomainStats {
+            domain,
+            ..Default::default()
+        });
+        row.total += 1;
+        match e.kind.as_str() {
+            "allowed" => row.allowed += 1,
+            "blocked" => row.blocked += 1,
+            "payment" => row.payment += 1,
+            _ => {}
+        }
+        row.bytes += e.fields.bytes.unwrap_or(0);
+    }
+    let mut rows: Vec<DomainStats> = by_domain.into_values().collect();
+    rows.sort_by(|a, b| b.total.cmp(&a.total).then(b.bytes.cmp(&a.bytes)));
+    Ok(rows)
+}
+
 #[derive(Debug, Serialize)]
 pub struct ReceiptEntry {
     pub ts: String,
     pub kind: String,
     pub msg: String,
+    /// Previous entry's hash for chained log entries; empty for standalone receipts.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub prev_hash: String,
     pub hash: String,
 }
 
@@ -93,14 +381,255 @@ fn hash_entry(ts: &str, kind: &str, msg: &str) -> String {
     format!("{:x}", hasher.finalize())
 }
 
+/// SHA-256 over the entry's `prev_hash`, `ts`, `kind`, `msg`, `seq` and its
+/// fields as JSON, each prefixed with its length so that moving bytes from one
+/// field to the next changes the hash.
+fn chain_hash(e: &LogEntry) -> String {
+    let seq = e.seq.to_string();
+    let fields = serde_json::to_vec(&e.fields).unwrap_or_default();
+    let mut hasher = Sha256::new();
+    for part in [e.prev_hash.as_bytes(), e.ts.as_bytes(), e.kind.as_bytes(), e.msg.as_bytes(), seq.as_bytes(), &fields] {
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Walk the persisted log and return the index of the first entry whose hash
+/// doesn't match its content or whose `prev_hash` isn't the previous entry's
+/// hash, or `None` if the chain is intact. Entries from before the chain
+/// existed are skipped; the first chained entry anchors the walk, since
+/// rotation prunes the oldest segments. Entries the write queue refused under
+/// load are left out of the chain and stand behind a `gap` entry instead.
+#[tauri::command]
+pub fn verify_evidence_chain() -> Result<Option<usize>, String> {
+    let entries = read_persisted();
+    let mut prev: Option<&str> = None;
+    for (i, e) in entries.iter().enumerate() {
+        if e.hash.is_empty() && prev.is_none() {
+            continue;
+        }
+        let linked = prev.is_none_or(|p| p == e.prev_hash);
+        if !linked || chain_hash(e) != e.hash {
+            return Ok(Some(i));
+        }
+        prev = Some(&e.hash);
+    }
+    Ok(None)
+}
+
 #[tauri::command]
 pub fn export_receipt(entries: Vec<(String, String, String)>) -> Result<Vec<ReceiptEntry>, String> {
     let out: Vec<ReceiptEntry> = entries
         .into_iter()
         .map(|(ts, kind, msg)| {
             let hash = hash_entry(&ts, &kind, &msg);
-            ReceiptEntry { ts, kind, msg, hash }
+            ReceiptEntry { ts, kind, msg, prev_hash: String::new(), hash }
         })
         .collect();
     Ok(out)
 }
+
+#[derive(Debug, Serialize)]
+pub struct SignedReceipt {
+    pub receipts: Vec<ReceiptEntry>,
+    /// Hash-chain root over the receipt hashes, see `chain_root`.
+    pub root: String,
+    pub signature: String,
+    pub address: String,
+}
+
+/// `export_receipt` plus a wallet signature over the receipts' hash-chain root,
+/// so a third party can check the set against the signer address.
+#[tauri::command]
+pub async fn export_signed_receipt(entries: Vec<(String, String, String)>) -> Result<SignedReceipt, String> {
+    let receipts = export_receipt(entries)?;
+    let root = chain_root(&receipts);
+    let (signature, address) = crate::wallet::sign_digest(root).await?;
+    push("info", &format!("Signed {} receipts by {}", receipts.len(), address));
+    Ok(SignedReceipt {
+        receipts,
+        root: format!("0x{}", hex::encode(root)),
+        signature,
+        address,
+    })
+}
+
+/// Write the full evidence history with its chained hashes to `dest_path` as `csv` or `json`.
+/// Returns the number of entries written.
+#[tauri::command]
+pub fn evidence_export(dest_path: String, format: String) -> Result<usize, String> {
+    let receipts = persisted_receipts()?;
+    write_export(&dest_path, &format, &receipts)?;
+    push("info", &format!("Exported {} evidence entries to {}", receipts.len(), dest_path));
+    Ok(receipts.len())
+}
+
+fn persisted_receipts() -> Result<Vec<ReceiptEntry>, String> {
+    let mut entries = read_persisted();
+    if entries.is_empty() {
+        entries = LOG.read().map_err(|_| "lock")?.iter().cloned().collect();
+    }
+    Ok(entries
+        .into_iter()
+        .map(|e| {
+            let hash = if e.hash.is_empty() { hash_entry(&e.ts, &e.kind, &e.msg) } else { e.hash };
+            ReceiptEntry {
+                ts: e.ts,
+                kind: e.kind,
+                msg: e.msg,
+                prev_hash: e.prev_hash,
+                hash,
+            }
+        })
+        .collect())
+}
+
+fn write_export(dest_path: &str, format: &str, receipts: &[ReceiptEntry]) -> Result<(), String> {
+    let out = match format.to_lowercase().as_str() {
+        "json" => serde_json::to_string_pretty(receipts).map_err(|e| e.to_string())?,
+        "csv" => {
+            let mut csv = String::from("ts,kind,msg,prev_hash,hash\n");
+            for r in receipts {
+                csv.push_str(&format!(
+                    "{},{},{},{},{}\n",
+                    csv_field(&r.ts),
+                    csv_field(&r.kind),
+                    csv_field(&r.msg),
+                    csv_field(&r.prev_hash),
+                    csv_field(&r.hash)
+                ));
+            }
+            csv
+        }
+        other => return Err(format!("Unsupported export format: {other} (use csv or json)")),
+    };
+    fs::write(dest_path, out).map_err(|e| format!("write {dest_path}: {e}"))
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or line break (RFC 4180).
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SignedExport {
+    pub path: String,
+    pub signature_path: String,
+    pub entries: usize,
+    pub root: String,
+    pub signature: String,
+    pub address: String,
+}
+
+/// Hash-chain root over receipt hashes: `h_i = sha256(h_{i-1} || hash_i)`, starting from zeros.
+fn chain_root(receipts: &[ReceiptEntry]) -> [u8; 32] {
+    let mut root = [0u8; 32];
+    for r in receipts {
+        let mut hasher = Sha256::new();
+        hasher.update(root);
+        hasher.update(r.hash.as_bytes());
+        root.copy_from_slice(&hasher.finalize());
+    }
+    root
+}
+
+/// Export the full log as JSON and sign its hash-chain root with the wallet key.
+/// The signature, root and signer address are written to `<dest_path>.sig`.
+#[tauri::command]
+pub async fn evidence_export_signed(dest_path: String) -> Result<SignedExport, String> {
+    let receipts = persisted_receipts()?;
+    let count = receipts.len();
+    write_export(&dest_path, "json", &receipts)?;
+    let root = chain_root(&receipts);
+    let (signature, address) = crate::wallet::sign_digest(root).await?;
+    let root_hex = format!("0x{}", hex::encode(root));
+    let signature_path = format!("{dest_path}.sig");
+    let sig_doc = serde_json::json!({
+        "scheme": "sha256-chain/secp256k1",
+        "entries": count,
+        "root": root_hex,
+        "signature": signature,
+        "address": address,
+    });
+    let sig_json = serde_json::to_string_pretty(&sig_doc).map_err(|e| e.to_string())?;
+    fs::write(&signature_path, sig_json).map_err(|e| format!("write {signature_path}: {e}"))?;
+    push("info", &format!("Signed evidence export {} by {}", dest_path, address));
+    Ok(SignedExport {
+        path: dest_path,
+        signature_path,
+        entries: count,
+        root: root_hex,
+        signature,
+        address,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(kind: &str, msg: &str, fields: EvidenceFields) -> LogEntry {
+        LogEntry {
+            ts: "1700000000.000".into(),
+            kind: kind.into(),
+            msg: msg.into(),
+            fields,
+            seq: 7,
+            prev_hash: GENESIS_HASH.into(),
+            hash: String::new(),
+        }
+    }
+
+    #[test]
+    fn chain_hash_separates_fields() {
+        let a = entry("blocked", "x", EvidenceFields::default());
+        let b = entry("blocke", "dx", EvidenceFields::default());
+        assert_ne!(chain_hash(&a), chain_hash(&b));
+    }
+
+    #[test]
+    fn chain_hash_covers_seq_and_structured_fields() {
+        let plain = entry("allowed", "GET api.openai.com", EvidenceFields::default());
+        let tagged = entry(
+            "allowed",
+            "GET api.openai.com",
+            EvidenceFields { alias: Some("openai".into()), ..Default::default() },
+        );
+        assert_ne!(chain_hash(&plain), chain_hash(&tagged));
+        let mut moved = plain.clone();
+        moved.seq += 1;
+        assert_ne!(chain_hash(&plain), chain_hash(&moved));
+    }
+
+    #[test]
+    fn chain_hash_survives_a_disk_round_trip() {
+        let mut e = entry(
+            "blocked",
+            "POST evil.example",
+            EvidenceFields { domain: Some("evil.example".into()), status: Some(403), ..Default::default() },
+        );
+        e.hash = chain_hash(&e);
+        let line = serde_json::to_string(&e).unwrap();
+        let read: LogEntry = serde_json::from_str(&line).unwrap();
+        assert_eq!(chain_hash(&read), e.hash);
+    }
+
+    #[test]
+    fn untailed_read_returns_a_ring_larger_than_max_tail() {
+        let cap = MAX_TAIL + 1000;
+        set_log_cap(cap);
+        if let Ok(mut g) = LOG.write() {
+            while g.len() < cap {
+                g.push_back(entry("allowed", "GET api.openai.com", EvidenceFields::default()));
+            }
+        }
+        let all = get_evidence_log(None).unwrap();
+        assert!(all.len() > MAX_TAIL, "got {}", all.len());
+        assert!(get_evidence_log(Some(cap)).unwrap().len() > MAX_TAIL);
+    }
+}
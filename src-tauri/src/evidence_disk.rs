@@ -0,0 +1,169 @@
+//! Append-only JSONL history of the evidence log, under
+//! `<config dir>/vault0/evidence.jsonl` with rotated segments beside it. Writes
+//! go through a background thread so `evidence::push` never waits on the disk;
+//! if the queue backs up, entries are dropped from the file (never from the
+//! in-memory ring) rather than stalling the proxy, and `evidence` records the
+//! drop in the hash chain as a `gap` entry.
+
+use once_cell::sync::Lazy;
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::Mutex;
+use tracing::warn;
+
+use crate::evidence::LogEntry;
+
+const LOG_DIR: &str = "vault0";
+const LOG_FILE: &str = "evidence.jsonl";
+/// Rotate the active log once it is older than this, whatever its size.
+const ROTATE_AGE_SECS: u64 = 7 * 24 * 60 * 60;
+/// Rotated segments kept alongside the active file.
+const KEEP_SEGMENTS: usize = 5;
+/// Entries waiting for the writer before new ones are dropped from the file.
+const QUEUE_LEN: usize = 4096;
+
+static DISK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+static WRITER: Lazy<SyncSender<LogEntry>> = Lazy::new(spawn_writer);
+
+fn log_dir() -> Option<PathBuf> {
+    // Tests never read or write the user's evidence log.
+    if cfg!(test) {
+        return None;
+    }
+    dirs::config_dir().map(|p| p.join(LOG_DIR))
+}
+
+fn spawn_writer() -> SyncSender<LogEntry> {
+    let (tx, rx) = mpsc::sync_channel::<LogEntry>(QUEUE_LEN);
+    std::thread::spawn(move || {
+        for entry in rx {
+            write_entry(&entry);
+        }
+    });
+    tx
+}
+
+/// Queue `entry` for the on-disk log. Never blocks; false if the entry was
+/// not queued.
+pub(crate) fn append(entry: &LogEntry) -> bool {
+    match WRITER.try_send(entry.clone()) {
+        Ok(()) => true,
+        Err(TrySendError::Full(_)) => false,
+        Err(TrySendError::Disconnected(_)) => {
+            warn!("Evidence log writer stopped");
+            false
+        }
+    }
+}
+
+fn write_entry(entry: &LogEntry) {
+    let Some(dir) = log_dir() else { return };
+    let Ok(_guard) = DISK.lock() else { return };
+    if fs::create_dir_all(&dir).is_err() {
+        return;
+    }
+    let path = dir.join(LOG_FILE);
+    rotate_if_needed(&dir, &path);
+    let line = match serde_json::to_string(entry) {
+        Ok(l) => l,
+        Err(_) => return,
+    };
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut f| writeln!(f, "{}", line));
+    if let Err(e) = result {
+        warn!("Evidence log write failed: {}", e);
+    }
+}
+
+fn rotate_if_needed(dir: &Path, path: &Path) {
+    let Ok(meta) = fs::metadata(path) else { return };
+    let age = meta
+        .created()
+        .ok()
+        .and_then(|c| c.elapsed().ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let max_bytes = crate::settings::get().evidence_rotate_mb.max(1) * 1024 * 1024;
+    if meta.len() < max_bytes && age < ROTATE_AGE_SECS {
+        return;
+    }
+    let stamp = crate::evidence::chrono_ts().replace('.', "");
+    let rotated = dir.join(format!("evidence.{}.jsonl", stamp));
+    if fs::rename(path, &rotated).is_err() {
+        return;
+    }
+    let mut segments = rotated_segments(dir);
+    while segments.len() > KEEP_SEGMENTS {
+        let _ = fs::remove_file(segments.remove(0));
+    }
+}
+
+/// Rotated segment files, oldest first (names embed the rotation timestamp).
+fn rotated_segments(dir: &Path) -> Vec<PathBuf> {
+    let mut segments: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|rd| {
+            rd.flatten()
+                .map(|e| e.path())
+                .filter(|p| {
+                    let name = p.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                    name.starts_with("evidence.") && name.ends_with(".jsonl") && name != LOG_FILE
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    segments.sort();
+    segments
+}
+
+/// Full persisted history, oldest first, across rotated segments and the active file.
+pub fn read_persisted() -> Vec<LogEntry> {
+    let Some(dir) = log_dir() else { return Vec::new() };
+    let _guard = DISK.lock();
+    let mut files = rotated_segments(&dir);
+    files.push(dir.join(LOG_FILE));
+    let mut out = Vec::new();
+    for file in files {
+        let Ok(f) = fs::File::open(&file) else { continue };
+        for line in BufReader::new(f).lines().map_while(Result::ok) {
+            if let Ok(entry) = serde_json::from_str::<LogEntry>(&line) {
+                out.push(entry);
+            }
+        }
+    }
+    out
+}
+
+/// The last `n` persisted entries, oldest first. Reads the newest files first
+/// and stops once it has `n`, holding at most `n` entries at a time.
+pub fn read_tail(n: usize) -> Vec<LogEntry> {
+    let Some(dir) = log_dir() else { return Vec::new() };
+    let _guard = DISK.lock();
+    let mut files = rotated_segments(&dir);
+    files.push(dir.join(LOG_FILE));
+    let mut out: VecDeque<LogEntry> = VecDeque::new();
+    for file in files.iter().rev() {
+        if out.len() >= n {
+            break;
+        }
+        let Ok(f) = fs::File::open(file) else { continue };
+        let mut segment: VecDeque<LogEntry> = VecDeque::new();
+        for line in BufReader::new(f).lines().map_while(Result::ok) {
+            if let Ok(entry) = serde_json::from_str::<LogEntry>(&line) {
+                segment.push_back(entry);
+                if segment.len() > n - out.len() {
+                    segment.pop_front();
+                }
+            }
+        }
+        while let Some(entry) = segment.pop_back() {
+            out.push_front(entry);
+        }
+    }
+    out.into()
+}
@@ -0,0 +1,96 @@
+//! Gateway connection settings: reads ~/.openclaw/openclaw.json and app
+//! settings to resolve the gateway URL, auth token and TLS connector.
+
+use serde::Deserialize;
+use tokio_tungstenite::Connector;
+use tracing::warn;
+
+#[derive(Debug, Deserialize)]
+struct OcConfig {
+    #[serde(default)]
+    gateway: Option<OcGateway>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OcGateway {
+    #[serde(default = "default_port")]
+    port: u16,
+    #[serde(default)]
+    auth: Option<OcAuth>,
+    /// Optional full gateway URL (e.g. `wss://gateway.example.com`) for non-loopback gateways.
+    #[serde(default)]
+    url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OcAuth {
+    #[serde(default)]
+    token: Option<String>,
+}
+
+/// Gateway port when openclaw.json doesn't set one (`gateway_default_port` in settings).
+pub(crate) fn default_port() -> u16 {
+    crate::settings::get().gateway_default_port
+}
+
+pub(crate) fn read_gateway_config() -> (u16, Option<String>, Option<String>) {
+    let home = match dirs::home_dir() {
+        Some(h) => h,
+        None => return (default_port(), None, None),
+    };
+    let path = home.join(".openclaw").join("openclaw.json");
+    let content = match std::fs::read_to_string(&path) {
+        Ok(c) => c,
+        Err(_) => return (default_port(), None, None),
+    };
+    let config: OcConfig = crate::detect_json::parse_lenient(&content)
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(OcConfig { gateway: None });
+    let port = config.gateway.as_ref().map(|g| g.port).unwrap_or_else(default_port);
+    let token = config
+        .gateway
+        .as_ref()
+        .and_then(|g| g.auth.as_ref())
+        .and_then(|a| a.token.clone());
+    let url = config.gateway.as_ref().and_then(|g| g.url.clone());
+    (port, token, url)
+}
+
+/// Resolve a `VAULT0_ALIAS:<alias>` gateway token from the unlocked vault.
+/// Plain tokens are passed through unchanged.
+pub(crate) fn resolve_gateway_token(token: Option<String>) -> Result<Option<String>, String> {
+    let Some(token) = token else { return Ok(None) };
+    match token.strip_prefix("VAULT0_ALIAS:") {
+        Some(alias) => crate::vault_store::vault_get_secret(alias.trim().to_string())
+            .map(Some)
+            .map_err(|e| format!("gateway token alias '{}': {}", alias.trim(), e)),
+        None => Ok(Some(token)),
+    }
+}
+
+/// Gateway URL from app settings, then openclaw.json, then loopback on the configured port.
+pub(crate) fn resolve_gateway_url(port: u16, config_url: Option<String>) -> String {
+    crate::settings::get()
+        .gateway_url
+        .or(config_url)
+        .filter(|u| !u.trim().is_empty())
+        .unwrap_or_else(|| format!("ws://127.0.0.1:{}", port))
+}
+
+/// TLS connector for `wss://` URLs. Certificates are verified unless the
+/// `gateway_tls_insecure` setting is on.
+pub(crate) fn tls_connector(url: &str) -> Result<Option<Connector>, String> {
+    if !url.starts_with("wss://") {
+        return Ok(None);
+    }
+    let insecure = crate::settings::get().gateway_tls_insecure;
+    if insecure {
+        warn!("Gateway TLS certificate verification disabled");
+    }
+    native_tls::TlsConnector::builder()
+        .danger_accept_invalid_certs(insecure)
+        .danger_accept_invalid_hostnames(insecure)
+        .build()
+        .map(|c| Some(Connector::NativeTls(c)))
+        .map_err(|e| format!("TLS setup failed: {e}"))
+}
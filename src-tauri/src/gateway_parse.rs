@@ -0,0 +1,205 @@
+//! Parsing of OpenClaw gateway frames into `GatewayEvent`s for the monitor view.
+
+use crate::gateway_permissions;
+use crate::gateway_ws::{now_ts, push_event, GatewayEvent};
+
+/// Parse OpenClaw gateway events using the real protocol shapes.
+/// Reference: crabwalk/src/integrations/openclaw/parser.ts
+pub(crate) fn parse_and_store_v2(
+    frame_type: &str,
+    event_name: &str,
+    json: &serde_json::Value,
+    raw: &str,
+) {
+    let payload = json.get("payload").unwrap_or(json);
+
+    let session_id = payload
+        .get("sessionKey")
+        .or_else(|| payload.get("sessionId"))
+        .or_else(|| payload.get("session_id"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+
+    let platform = payload
+        .get("platform")
+        .or_else(|| payload.get("channel"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+
+    match event_name {
+        // Chat events: delta (streaming), final, aborted, error
+        "chat" => {
+            let state = payload.get("state").and_then(|v| v.as_str()).unwrap_or("");
+            let kind = match state {
+                "delta" => "thinking",
+                "final" => "message_out",
+                "aborted" => "error",
+                "error" => "error",
+                _ => "message_out",
+            };
+            let summary = extract_chat_content(payload, state);
+            push_event(GatewayEvent {
+                ts: now_ts(), kind: kind.into(), session_id, platform, summary, payload: raw.into(),
+            });
+        }
+        // Agent events: lifecycle, assistant stream, tool_use, tool_result
+        "agent" => {
+            let stream = payload.get("stream").and_then(|v| v.as_str()).unwrap_or("");
+            let data = payload.get("data").unwrap_or(payload);
+            let data_type = data.get("type").and_then(|v| v.as_str()).unwrap_or("");
+
+            let (kind, summary) = match (stream, data_type) {
+                ("lifecycle", _) => {
+                    let phase = data.get("phase").and_then(|v| v.as_str()).unwrap_or("");
+                    match phase {
+                        "start" => ("thinking", "Run started".to_string()),
+                        "end" => ("message_out", "Run completed".to_string()),
+                        _ => ("thinking", format!("Lifecycle: {}", phase)),
+                    }
+                }
+                (_, "tool_use") => {
+                    let name = data.get("name").and_then(|v| v.as_str()).unwrap_or("unknown");
+                    ("tool_call", format!("Tool: {}", name))
+                }
+                (_, "tool_result") => {
+                    let content = data.get("content").and_then(|v| v.as_str()).unwrap_or("");
+                    let preview = truncate(content, 100);
+                    ("tool_result", format!("Result: {}", preview))
+                }
+                ("assistant", _) => {
+                    let text = data.get("text").and_then(|v| v.as_str()).unwrap_or("");
+                    ("thinking", truncate(text, 100))
+                }
+                _ => {
+                    ("thinking", truncate(&data.to_string(), 100))
+                }
+            };
+            push_event(GatewayEvent {
+                ts: now_ts(), kind: kind.into(), session_id, platform, summary, payload: raw.into(),
+            });
+        }
+        // Exec events
+        "exec.started" => {
+            let cmd = payload.get("command").and_then(|v| v.as_str()).unwrap_or("");
+            push_event(GatewayEvent {
+                ts: now_ts(), kind: "tool_call".into(), session_id, platform,
+                summary: format!("Exec: {}", truncate(cmd, 80)),
+                payload: raw.into(),
+            });
+        }
+        "exec.output" => {
+            let output = payload.get("output").and_then(|v| v.as_str()).unwrap_or("");
+            let stream = payload.get("stream").and_then(|v| v.as_str()).unwrap_or("stdout");
+            push_event(GatewayEvent {
+                ts: now_ts(), kind: "tool_result".into(), session_id, platform,
+                summary: format!("[{}] {}", stream, truncate(output, 80)),
+                payload: raw.into(),
+            });
+        }
+        "exec.completed" => {
+            let exit_code = payload.get("exitCode").and_then(|v| v.as_i64()).unwrap_or(-1);
+            let duration = payload.get("durationMs").and_then(|v| v.as_u64()).unwrap_or(0);
+            push_event(GatewayEvent {
+                ts: now_ts(), kind: "tool_result".into(), session_id, platform,
+                summary: format!("Exec done (exit {}, {}ms)", exit_code, duration),
+                payload: raw.into(),
+            });
+        }
+        // Permission prompts: queue for approval, and clear once resolved
+        name if gateway_permissions::REQUEST_EVENTS.contains(&name) => {
+            let summary = gateway_permissions::record(name, payload, &session_id, &platform, raw);
+            push_event(GatewayEvent {
+                ts: now_ts(), kind: "permission_request".into(), session_id, platform, summary,
+                payload: raw.into(),
+            });
+        }
+        name if gateway_permissions::RESOLVED_EVENTS.contains(&name) => {
+            let id = gateway_permissions::resolved(payload).unwrap_or_default();
+            push_event(GatewayEvent {
+                ts: now_ts(), kind: "permission_resolved".into(), session_id, platform,
+                summary: format!("Permission {} resolved", id),
+                payload: raw.into(),
+            });
+        }
+        // Fallback for any other event
+        _ => {
+            push_event(GatewayEvent {
+                ts: now_ts(),
+                kind: frame_type.to_string(),
+                session_id, platform,
+                summary: truncate(&json.to_string(), 120),
+                payload: raw.into(),
+            });
+        }
+    }
+}
+
+fn extract_chat_content(payload: &serde_json::Value, state: &str) -> String {
+    // Try message.content[].text first (standard shape)
+    if let Some(msg) = payload.get("message") {
+        if let Some(content) = msg.get("content") {
+            if let Some(arr) = content.as_array() {
+                let texts: Vec<&str> = arr.iter()
+                    .filter_map(|b| {
+                        let btype = b.get("type").and_then(|v| v.as_str()).unwrap_or("");
+                        match btype {
+                            "text" => b.get("text").and_then(|v| v.as_str()),
+                            "tool_use" => b.get("name").and_then(|v| v.as_str()),
+                            _ => None,
+                        }
+                    })
+                    .collect();
+                if !texts.is_empty() {
+                    return truncate(&texts.join(""), 120);
+                }
+            }
+            if let Some(s) = content.as_str() {
+                return truncate(s, 120);
+            }
+        }
+        if let Some(s) = msg.get("text").and_then(|v| v.as_str()) {
+            return truncate(s, 120);
+        }
+        if let Some(s) = msg.as_str() {
+            return truncate(s, 120);
+        }
+    }
+    if let Some(err) = payload.get("errorMessage").and_then(|v| v.as_str()) {
+        return truncate(err, 120);
+    }
+    match state {
+        "delta" => "Thinking...".into(),
+        "final" => "Response complete".into(),
+        "aborted" => "Aborted".into(),
+        "error" => "Error".into(),
+        _ => state.to_string(),
+    }
+}
+
+pub(crate) fn truncate(s: &str, max: usize) -> String {
+    if s.len() > max {
+        format!("{}...", &s[..max])
+    } else {
+        s.to_string()
+    }
+}
+
+/// Legacy parser kept for non-gateway events (e.g. from evidence log)
+pub(crate) fn parse_and_store(raw: &str) {
+    if let Ok(json) = serde_json::from_str::<serde_json::Value>(raw) {
+        let frame_type = json.get("type").and_then(|v| v.as_str()).unwrap_or("");
+        let event_name = json.get("event").and_then(|v| v.as_str()).unwrap_or("");
+        parse_and_store_v2(frame_type, event_name, &json, raw);
+    } else {
+        push_event(GatewayEvent {
+            ts: now_ts(),
+            kind: "unknown".into(),
+            session_id: String::new(),
+            platform: String::new(),
+            summary: truncate(raw, 120),
+            payload: raw.to_string(),
+        });
+    }
+}
@@ -0,0 +1,135 @@
+//! Approval gate for agent actions. The gateway asks the operator before an
+//! agent runs a risky tool; those prompts are queued here until answered.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::sync::RwLock;
+use tracing::info;
+
+use crate::evidence;
+use crate::gateway_parse::truncate;
+use crate::gateway_ws::{now_ts, send_frame};
+
+/// Gateway events that open a permission prompt.
+pub(crate) const REQUEST_EVENTS: &[&str] =
+    &["permission.request", "permission.requested", "exec.approval.requested"];
+/// Gateway events that close one (answered here, elsewhere, or timed out).
+pub(crate) const RESOLVED_EVENTS: &[&str] = &["permission.resolved", "exec.approval.resolved"];
+/// Oldest prompts are dropped past this, so a flood can't grow memory unbounded.
+const PENDING_CAP: usize = 200;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionRequest {
+    pub id: String,
+    pub ts: String,
+    pub session_id: String,
+    pub platform: String,
+    /// Tool or command the agent wants to run.
+    pub action: String,
+    pub summary: String,
+    /// Gateway event that raised it; decides the shape of the reply frame.
+    pub source: String,
+    pub payload: String,
+}
+
+static PENDING: Lazy<RwLock<Vec<PermissionRequest>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+fn request_id(payload: &serde_json::Value) -> Option<String> {
+    payload
+        .get("id")
+        .or_else(|| payload.get("requestId"))
+        .and_then(|v| v.as_str().map(String::from).or_else(|| v.as_u64().map(|n| n.to_string())))
+}
+
+/// Queue a permission prompt and return a one-line summary for the event stream.
+pub(crate) fn record(
+    event_name: &str,
+    payload: &serde_json::Value,
+    session_id: &str,
+    platform: &str,
+    raw: &str,
+) -> String {
+    let request = payload.get("request").unwrap_or(payload);
+    let action = request
+        .get("tool")
+        .or_else(|| request.get("command"))
+        .or_else(|| request.get("name"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown")
+        .to_string();
+    let summary = format!("Approval needed: {}", truncate(&action, 80));
+    let Some(id) = request_id(payload) else {
+        return format!("{} (no request id, cannot answer)", summary);
+    };
+    if let Ok(mut g) = PENDING.write() {
+        g.retain(|p| p.id != id);
+        g.push(PermissionRequest {
+            id,
+            ts: now_ts(),
+            session_id: session_id.to_string(),
+            platform: platform.to_string(),
+            action,
+            summary: summary.clone(),
+            source: event_name.to_string(),
+            payload: raw.to_string(),
+        });
+        let excess = g.len().saturating_sub(PENDING_CAP);
+        g.drain(..excess);
+    }
+    summary
+}
+
+/// Drop a prompt the gateway reports as resolved. Returns its id, if any.
+pub(crate) fn resolved(payload: &serde_json::Value) -> Option<String> {
+    let id = request_id(payload)?;
+    if let Ok(mut g) = PENDING.write() {
+        g.retain(|p| p.id != id);
+    }
+    Some(id)
+}
+
+/// Reply frame for a prompt; exec approvals use the gateway's `exec.approval.resolve` method.
+fn build_response(req: &PermissionRequest, approve: bool) -> serde_json::Value {
+    let frame_id = format!("perm-{}", now_ts());
+    if req.source == "exec.approval.requested" {
+        let decision = if approve { "allow-once" } else { "deny" };
+        serde_json::json!({
+            "type": "req",
+            "id": frame_id,
+            "method": "exec.approval.resolve",
+            "params": { "id": req.id, "decision": decision }
+        })
+    } else {
+        serde_json::json!({
+            "type": "req",
+            "id": frame_id,
+            "method": "permission.respond",
+            "params": { "id": req.id, "approve": approve }
+        })
+    }
+}
+
+#[tauri::command]
+pub fn get_pending_permissions() -> Result<Vec<PermissionRequest>, String> {
+    PENDING.read().map(|g| g.clone()).map_err(|_| "lock".into())
+}
+
+/// Approve or deny a pending prompt over the live gateway socket.
+#[tauri::command]
+pub fn gateway_respond_permission(id: String, approve: bool) -> Result<String, String> {
+    let req = PENDING
+        .read()
+        .map_err(|_| "lock")?
+        .iter()
+        .find(|p| p.id == id)
+        .cloned()
+        .ok_or_else(|| format!("No pending permission request '{}'", id))?;
+    send_frame(build_response(&req, approve))?;
+    if let Ok(mut g) = PENDING.write() {
+        g.retain(|p| p.id != id);
+    }
+    let verdict = if approve { "Approved" } else { "Denied" };
+    info!("{} gateway permission {} ({})", verdict, id, req.action);
+    evidence::push("info", &format!("{} agent action: {} (session {})", verdict, req.action, req.session_id));
+    Ok(format!("{} {}", verdict, req.action))
+}
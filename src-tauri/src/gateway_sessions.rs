@@ -0,0 +1,73 @@
+//! Live agent sessions derived from gateway events: one row per session with
+//! its latest activity, so the UI can show "3 active agents" with drill-down.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::gateway_ws::GatewayEvent;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GatewaySession {
+    pub session_id: String,
+    pub platform: String,
+    /// `ts` of the most recent event, same format as `GatewayEvent::ts`.
+    pub last_activity: String,
+    pub event_count: u64,
+    pub last_kind: String,
+    pub last_summary: String,
+}
+
+static SESSIONS: Lazy<RwLock<HashMap<String, GatewaySession>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+fn ts_secs(ts: &str) -> u64 {
+    ts.split('.').next().and_then(|s| s.parse().ok()).unwrap_or(0)
+}
+
+/// Fold one stored event into the session map. Events without a session are ignored.
+pub(crate) fn touch(evt: &GatewayEvent) {
+    if evt.session_id.is_empty() {
+        return;
+    }
+    if let Ok(mut g) = SESSIONS.write() {
+        let s = g.entry(evt.session_id.clone()).or_insert_with(|| GatewaySession {
+            session_id: evt.session_id.clone(),
+            platform: String::new(),
+            last_activity: String::new(),
+            event_count: 0,
+            last_kind: String::new(),
+            last_summary: String::new(),
+        });
+        if !evt.platform.is_empty() {
+            s.platform = evt.platform.clone();
+        }
+        s.last_activity = evt.ts.clone();
+        s.event_count += 1;
+        s.last_kind = evt.kind.clone();
+        s.last_summary = evt.summary.clone();
+    }
+}
+
+pub(crate) fn clear() {
+    if let Ok(mut g) = SESSIONS.write() {
+        g.clear();
+    }
+}
+
+/// Active sessions, most recent first. Sessions idle longer than the
+/// `gateway_session_idle_secs` setting are pruned.
+#[tauri::command]
+pub fn gateway_sessions() -> Result<Vec<GatewaySession>, String> {
+    let idle = crate::settings::get().gateway_session_idle_secs;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut g = SESSIONS.write().map_err(|_| "lock")?;
+    g.retain(|_, s| now.saturating_sub(ts_secs(&s.last_activity)) <= idle);
+    let mut rows: Vec<GatewaySession> = g.values().cloned().collect();
+    rows.sort_by_key(|s| std::cmp::Reverse(ts_secs(&s.last_activity)));
+    Ok(rows)
+}
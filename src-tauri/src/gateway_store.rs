@@ -0,0 +1,161 @@
+//! On-disk history of gateway events so the monitor timeline survives restarts.
+//! File: <config dir>/vault0/gateway_events.jsonl, compacted to the newest events once it grows past `STORE_BYTES`.
+//! Only the event's metadata and summary are kept; raw frames can carry prompts,
+//! tool output and tokens, so `payload` is dropped before it reaches the disk.
+//! Writes go through a background thread so the websocket loop never waits on
+//! the disk; if the queue backs up, events are dropped from the file (never from
+//! the in-memory ring).
+
+use once_cell::sync::Lazy;
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::Mutex;
+use tracing::warn;
+
+use crate::gateway_ws::GatewayEvent;
+
+const STORE_DIR: &str = "vault0";
+const STORE_FILE: &str = "gateway_events.jsonl";
+/// Compact once the file passes this size, keeping roughly the newest half.
+const STORE_BYTES: u64 = 5 * 1024 * 1024;
+/// Events waiting for the writer before new ones are dropped from the file.
+const QUEUE_LEN: usize = 4096;
+
+enum Op {
+    Append(GatewayEvent),
+    Clear,
+}
+
+static DISK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+static WRITER: Lazy<SyncSender<Op>> = Lazy::new(spawn_writer);
+
+fn store_path() -> Option<PathBuf> {
+    // Tests never read or write the user's event history.
+    if cfg!(test) {
+        return None;
+    }
+    dirs::config_dir().map(|p| p.join(STORE_DIR).join(STORE_FILE))
+}
+
+fn spawn_writer() -> SyncSender<Op> {
+    let (tx, rx) = mpsc::sync_channel::<Op>(QUEUE_LEN);
+    std::thread::spawn(move || {
+        for op in rx {
+            let Some(path) = store_path() else { continue };
+            let Ok(_guard) = DISK.lock() else { continue };
+            match op {
+                Op::Append(evt) => write_event(&path, &evt),
+                Op::Clear => {
+                    let _ = fs::remove_file(&path);
+                }
+            }
+        }
+    });
+    tx
+}
+
+/// The form of `evt` that is written to disk: everything but the raw frame.
+fn persisted(evt: &GatewayEvent) -> GatewayEvent {
+    GatewayEvent {
+        payload: String::new(),
+        ..evt.clone()
+    }
+}
+
+/// Queue `evt` for the on-disk history. Never blocks.
+pub(crate) fn append(evt: &GatewayEvent) {
+    match WRITER.try_send(Op::Append(persisted(evt))) {
+        Ok(()) | Err(TrySendError::Full(_)) => {}
+        Err(TrySendError::Disconnected(_)) => warn!("Gateway event store writer stopped"),
+    }
+}
+
+fn write_event(path: &Path, evt: &GatewayEvent) {
+    if let Some(parent) = path.parent() {
+        if fs::create_dir_all(parent).is_err() {
+            return;
+        }
+    }
+    let Ok(line) = serde_json::to_string(evt) else { return };
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut f| writeln!(f, "{}", line));
+    if let Err(e) = result {
+        warn!("Gateway event store write failed: {}", e);
+        return;
+    }
+    if fs::metadata(path).map(|m| m.len() > STORE_BYTES).unwrap_or(false) {
+        compact(path);
+    }
+}
+
+/// Rewrite the file with the newest lines that fit in half of `STORE_BYTES`.
+fn compact(path: &Path) {
+    let Ok(content) = fs::read_to_string(path) else { return };
+    let mut kept: VecDeque<&str> = VecDeque::new();
+    let mut size = 0u64;
+    for line in content.lines().rev() {
+        size += line.len() as u64 + 1;
+        if size > STORE_BYTES / 2 {
+            break;
+        }
+        kept.push_front(line);
+    }
+    let mut out = kept.into_iter().collect::<Vec<_>>().join("\n");
+    out.push('\n');
+    if let Err(e) = fs::write(path, out) {
+        warn!("Gateway event store compaction failed: {}", e);
+    }
+}
+
+/// The newest `limit` persisted events, oldest first. Unparseable lines are skipped.
+pub(crate) fn load_tail(limit: usize) -> Vec<GatewayEvent> {
+    let Some(path) = store_path() else { return Vec::new() };
+    let Ok(_guard) = DISK.lock() else { return Vec::new() };
+    let Ok(file) = fs::File::open(&path) else { return Vec::new() };
+    let mut tail: VecDeque<GatewayEvent> = VecDeque::with_capacity(limit);
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        if let Ok(evt) = serde_json::from_str::<GatewayEvent>(&line) {
+            tail.push_back(evt);
+            if tail.len() > limit {
+                tail.pop_front();
+            }
+        }
+    }
+    tail.into()
+}
+
+/// Delete the history. Queued behind pending appends so none of them land
+/// after the file is gone.
+pub(crate) fn clear() {
+    if WRITER.send(Op::Clear).is_err() {
+        warn!("Gateway event store writer stopped");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn persisted_events_keep_the_summary_but_not_the_raw_frame() {
+        let evt = GatewayEvent {
+            ts: "1".into(),
+            kind: "tool_call".into(),
+            session_id: "s1".into(),
+            platform: "slack".into(),
+            summary: "exec: ls".into(),
+            payload: r#"{"token":"sk-live-secret"}"#.into(),
+        };
+        let line = serde_json::to_string(&persisted(&evt)).unwrap();
+        assert!(!line.contains("sk-live-secret"));
+        let back: GatewayEvent = serde_json::from_str(&line).unwrap();
+        assert_eq!((back.kind.as_str(), back.summary.as_str()), ("tool_call", "exec: ls"));
+        assert!(back.payload.is_empty());
+    }
+}
@@ -1,23 +1,34 @@
 //! WebSocket client for the OpenClaw gateway. Streams real-time agent events
-//! (messages, tool calls, thinking states) into a ring buffer that the frontend polls.
+//! (messages, tool calls, thinking states) into a ring buffer and emits each one to the frontend.
 
 use futures_util::{SinkExt, StreamExt};
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, RwLock};
+use tokio::sync::mpsc::UnboundedSender;
 use tokio_tungstenite::tungstenite::Message;
+use tauri::{AppHandle, Emitter};
 use tracing::{error, info, warn};
 
-const EVENT_CAP: usize = 500;
-const DEFAULT_PORT: u16 = 18789;
+use crate::gateway_config::{read_gateway_config, resolve_gateway_token, resolve_gateway_url, tls_connector};
+use crate::gateway_parse::{parse_and_store, parse_and_store_v2};
+use crate::{gateway_sessions, gateway_store};
+
+/// Events kept in the ring; set from `gateway_event_cap` in settings.
+static EVENT_CAP: AtomicUsize = AtomicUsize::new(500);
+/// Gateway protocol versions `gateway_parse` understands the frames of.
+const CLIENT_MIN_PROTOCOL: u64 = 3;
+const CLIENT_MAX_PROTOCOL: u64 = 3;
+/// Tauri event carrying each stored `GatewayEvent`; the ring buffer remains for backfill.
+const GATEWAY_EVENT: &str = "gateway-event";
 
 // ---------------------------------------------------------------------------
 // Public types
 // ---------------------------------------------------------------------------
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GatewayEvent {
     pub ts: String,
     pub kind: String,
@@ -32,6 +43,11 @@ pub struct GatewayStatus {
     pub connected: bool,
     pub event_count: usize,
     pub gateway_url: String,
+    pub protocol: u64,
+    /// Consecutive failed reconnects since the last successful session.
+    pub reconnect_attempts: u32,
+    /// The reconnect loop hit `gateway_max_reconnects` and stopped; a manual connect is needed.
+    pub gave_up: bool,
 }
 
 // ---------------------------------------------------------------------------
@@ -39,82 +55,98 @@ pub struct GatewayStatus {
 // ---------------------------------------------------------------------------
 
 static CONNECTED: AtomicBool = AtomicBool::new(false);
+/// Negotiated protocol version of the current connection (0 until negotiated).
+static PROTOCOL: AtomicU64 = AtomicU64::new(0);
 static SHOULD_RUN: AtomicBool = AtomicBool::new(false);
+static RECONNECT_ATTEMPTS: AtomicU32 = AtomicU32::new(0);
+static GAVE_UP: AtomicBool = AtomicBool::new(false);
+/// Unix seconds of the last frame received on the socket (0 = none yet).
+static LAST_FRAME: AtomicU64 = AtomicU64::new(0);
 static EVENTS: Lazy<RwLock<VecDeque<GatewayEvent>>> =
     Lazy::new(|| RwLock::new(VecDeque::new()));
 static GATEWAY_URL: Lazy<RwLock<String>> =
     Lazy::new(|| RwLock::new(String::new()));
+static APP: OnceCell<AppHandle> = OnceCell::new();
+/// Sender half for frames written by commands (e.g. permission replies) while connected.
+static OUTBOX: Lazy<Mutex<Option<UnboundedSender<String>>>> = Lazy::new(|| Mutex::new(None));
+static KIND_FILTER: Lazy<RwLock<Vec<String>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+/// True when `kind` passes the UI's kind filter (empty filter or "all" = everything).
+fn kind_wanted(kind: &str) -> bool {
+    KIND_FILTER
+        .read()
+        .map(|f| f.is_empty() || f.iter().any(|k| k == "all" || k == kind))
+        .unwrap_or(true)
+}
 
-fn push_event(evt: GatewayEvent) {
+pub(crate) fn push_event(evt: GatewayEvent) {
+    // Session activity counts every event, even kinds hidden by the filter.
+    gateway_sessions::touch(&evt);
+    if !kind_wanted(&evt.kind) {
+        return;
+    }
+    let emitted = APP.get().map(|app| (app, evt.clone()));
+    gateway_store::append(&evt);
     if let Ok(mut g) = EVENTS.write() {
         g.push_back(evt);
-        while g.len() > EVENT_CAP {
+        let cap = EVENT_CAP.load(Ordering::Relaxed);
+        while g.len() > cap {
             g.pop_front();
         }
     }
+    // Emit after the write lock is released so UI listeners can read EVENTS.
+    if let Some((app, evt)) = emitted {
+        let _ = app.emit(GATEWAY_EVENT, evt);
+    }
 }
 
-fn now_ts() -> String {
-    std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .map(|d| format!("{}.{:03}", d.as_secs(), d.subsec_millis()))
-        .unwrap_or_else(|_| "0.000".into())
+/// Called once from the Tauri setup hook so gateway events are pushed to the UI.
+pub fn set_app_handle(app: AppHandle) {
+    let _ = APP.set(app);
 }
 
-// ---------------------------------------------------------------------------
-// Config helpers (reads ~/.openclaw/openclaw.json)
-// ---------------------------------------------------------------------------
-
-#[derive(Debug, Deserialize)]
-struct OcConfig {
-    #[serde(default)]
-    gateway: Option<OcGateway>,
+fn set_outbox(tx: Option<UnboundedSender<String>>) {
+    if let Ok(mut g) = OUTBOX.lock() {
+        *g = tx;
+    }
 }
 
-#[derive(Debug, Deserialize)]
-struct OcGateway {
-    #[serde(default = "default_port")]
-    port: u16,
-    #[serde(default)]
-    auth: Option<OcAuth>,
+/// Queue a frame for the live gateway socket. Fails when not connected.
+pub(crate) fn send_frame(frame: serde_json::Value) -> Result<(), String> {
+    if !CONNECTED.load(Ordering::Relaxed) {
+        return Err("Gateway not connected".into());
+    }
+    let g = OUTBOX.lock().map_err(|_| "lock")?;
+    let tx = g.as_ref().ok_or("Gateway not connected")?;
+    tx.send(frame.to_string()).map_err(|_| "Gateway connection closed".into())
 }
 
-#[derive(Debug, Deserialize)]
-struct OcAuth {
-    #[serde(default)]
-    token: Option<String>,
+fn unix_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
 }
 
-fn default_port() -> u16 {
-    DEFAULT_PORT
+/// Seconds since the last frame on an authenticated socket, or `None` when not
+/// connected. Idle sockets are pinged every 30s, so a healthy gateway answers
+/// (pong or event) well within `LIVE_WINDOW_SECS`.
+pub(crate) fn last_frame_age() -> Option<u64> {
+    if !CONNECTED.load(Ordering::Relaxed) {
+        return None;
+    }
+    let last = LAST_FRAME.load(Ordering::Relaxed);
+    (last > 0).then(|| unix_secs().saturating_sub(last))
 }
 
-fn read_gateway_config() -> (u16, Option<String>) {
-    let home = match dirs::home_dir() {
-        Some(h) => h,
-        None => return (DEFAULT_PORT, None),
-    };
-    let path = home.join(".openclaw").join("openclaw.json");
-    let content = match std::fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => return (DEFAULT_PORT, None),
-    };
-    // Strip // comments for JSON5 compat
-    let stripped: String = content
-        .lines()
-        .map(|l| if l.trim().starts_with("//") { "" } else { l })
-        .collect::<Vec<&str>>()
-        .join("\n");
-    let config: OcConfig = serde_json::from_str(&stripped)
-        .or_else(|_| serde_json::from_str(&content))
-        .unwrap_or(OcConfig { gateway: None });
-    let port = config.gateway.as_ref().map(|g| g.port).unwrap_or(DEFAULT_PORT);
-    let token = config
-        .gateway
-        .as_ref()
-        .and_then(|g| g.auth.as_ref())
-        .and_then(|a| a.token.clone());
-    (port, token)
+/// An authenticated socket counts as live while frames arrive this often.
+pub(crate) const LIVE_WINDOW_SECS: u64 = 75;
+
+pub(crate) fn now_ts() -> String {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| format!("{}.{:03}", d.as_secs(), d.subsec_millis()))
+        .unwrap_or_else(|_| "0.000".into())
 }
 
 // ---------------------------------------------------------------------------
@@ -131,9 +163,49 @@ fn is_skip_event(event_type: &str) -> bool {
     SKIP_EVENTS.iter().any(|p| event_type == *p)
 }
 
+/// Server's advertised protocol range from a `connect.challenge` frame, if present.
+/// Accepts `minProtocol`/`maxProtocol`, a single `protocol`, or a `protocols` list.
+fn challenge_protocol_range(json: &serde_json::Value) -> Option<(u64, u64)> {
+    let payload = json.get("payload").unwrap_or(json);
+    let num = |k: &str| payload.get(k).and_then(|v| v.as_u64());
+    if let (Some(min), Some(max)) = (num("minProtocol"), num("maxProtocol")) {
+        return Some((min, max));
+    }
+    if let Some(p) = num("protocol") {
+        return Some((p, p));
+    }
+    let list: Vec<u64> = payload
+        .get("protocols")
+        .and_then(|v| v.as_array())
+        .map(|a| a.iter().filter_map(|v| v.as_u64()).collect())
+        .unwrap_or_default();
+    Some((*list.iter().min()?, *list.iter().max()?))
+}
+
+/// Protocol range to offer in `connect`: the overlap with the server's
+/// advertised range, or our whole range when the challenge names none, in
+/// which case the server picks and `hello-ok` says which.
+fn negotiate_protocol(server: Option<(u64, u64)>) -> Result<(u64, u64), String> {
+    let Some((smin, smax)) = server else { return Ok((CLIENT_MIN_PROTOCOL, CLIENT_MAX_PROTOCOL)) };
+    let lo = smin.max(CLIENT_MIN_PROTOCOL);
+    let hi = smax.min(CLIENT_MAX_PROTOCOL);
+    if lo > hi {
+        return Err(format!(
+            "unsupported protocol range: server {}-{}, client {}-{}",
+            smin, smax, CLIENT_MIN_PROTOCOL, CLIENT_MAX_PROTOCOL
+        ));
+    }
+    Ok((lo, hi))
+}
+
+/// Version the server chose, from a `hello-ok` frame or the `connect` response carrying one.
+fn hello_protocol(json: &serde_json::Value) -> Option<u64> {
+    json.get("protocol").or_else(|| json.pointer("/payload/protocol")).and_then(|v| v.as_u64())
+}
+
 /// Build the `connect` request frame matching the OpenClaw gateway protocol.
 /// Crabwalk reference: src/integrations/openclaw/protocol.ts → createConnectParams
-fn build_connect_request(token: &Option<String>) -> serde_json::Value {
+fn build_connect_request(token: &Option<String>, (min_protocol, max_protocol): (u64, u64)) -> serde_json::Value {
     let auth = token.as_ref().map(|t| serde_json::json!({"token": t}));
     serde_json::json!({
         "type": "req",
@@ -143,8 +215,8 @@ fn build_connect_request(token: &Option<String>) -> serde_json::Value {
             .unwrap_or(0)),
         "method": "connect",
         "params": {
-            "minProtocol": 3,
-            "maxProtocol": 3,
+            "minProtocol": min_protocol,
+            "maxProtocol": max_protocol,
             "client": {
                 "id": "cli",
                 "version": "0.1.0",
@@ -152,7 +224,7 @@ fn build_connect_request(token: &Option<String>) -> serde_json::Value {
                 "mode": "cli"
             },
             "role": "operator",
-            "scopes": ["operator.read"],
+            "scopes": ["operator.read", "operator.approvals"],
             "caps": [],
             "commands": [],
             "permissions": {},
@@ -163,21 +235,55 @@ fn build_connect_request(token: &Option<String>) -> serde_json::Value {
     })
 }
 
-async fn ws_loop() {
-    let (port, token) = read_gateway_config();
-    let url = format!("ws://127.0.0.1:{}", port);
+/// One connection attempt. Returns true if the session authenticated, so the
+/// caller can reset its reconnect budget.
+async fn ws_loop() -> bool {
+    let (port, token, config_url) = read_gateway_config();
+    let token = match resolve_gateway_token(token) {
+        Ok(t) => t,
+        Err(e) => {
+            error!("Gateway WS {}", e);
+            push_event(GatewayEvent {
+                ts: now_ts(),
+                kind: "error".into(),
+                session_id: String::new(),
+                platform: String::new(),
+                summary: format!("Cannot resolve gateway token: {}", e),
+                payload: String::new(),
+            });
+            // Retrying won't help until the vault is unlocked
+            SHOULD_RUN.store(false, Ordering::Relaxed);
+            CONNECTED.store(false, Ordering::Relaxed);
+            return false;
+        }
+    };
+    let url = resolve_gateway_url(port, config_url);
     if let Ok(mut g) = GATEWAY_URL.write() {
         *g = url.clone();
     }
 
+    if !url.starts_with("ws://") && !url.starts_with("wss://") {
+        error!("Gateway URL must use ws:// or wss://: {}", url);
+        CONNECTED.store(false, Ordering::Relaxed);
+        return false;
+    }
+    let connector = match tls_connector(&url) {
+        Ok(c) => c,
+        Err(e) => {
+            error!("Gateway WS {}", e);
+            CONNECTED.store(false, Ordering::Relaxed);
+            return false;
+        }
+    };
+
     info!("Gateway WS connecting to {}", url);
 
-    let ws_stream = match tokio_tungstenite::connect_async(&url).await {
+    let ws_stream = match tokio_tungstenite::connect_async_tls_with_config(url.as_str(), None, false, connector).await {
         Ok((stream, _)) => stream,
         Err(e) => {
             error!("Gateway WS connect failed: {}", e);
             CONNECTED.store(false, Ordering::Relaxed);
-            return;
+            return false;
         }
     };
 
@@ -185,9 +291,24 @@ async fn ws_loop() {
 
     let (mut write, mut read) = ws_stream.split();
     let mut authenticated = false;
+    let (tx, mut outbox) = tokio::sync::mpsc::unbounded_channel::<String>();
+    set_outbox(Some(tx));
 
     while SHOULD_RUN.load(Ordering::Relaxed) {
-        match tokio::time::timeout(std::time::Duration::from_secs(30), read.next()).await {
+        let next = tokio::select! {
+            Some(frame) = outbox.recv() => {
+                if let Err(e) = write.send(Message::Text(frame)).await {
+                    error!("Gateway WS send failed: {}", e);
+                    break;
+                }
+                continue;
+            }
+            next = tokio::time::timeout(std::time::Duration::from_secs(30), read.next()) => next,
+        };
+        if let Ok(Some(Ok(_))) = &next {
+            LAST_FRAME.store(unix_secs(), Ordering::Relaxed);
+        }
+        match next {
             Ok(Some(Ok(Message::Text(text)))) => {
                 let json: serde_json::Value = match serde_json::from_str(&text) {
                     Ok(v) => v,
@@ -204,8 +325,26 @@ async fn ws_loop() {
 
                 // Step 1: Gateway sends connect.challenge → we respond with connect request
                 if frame_type == "event" && event_name == "connect.challenge" {
-                    info!("Gateway challenge received, sending connect request");
-                    let connect_req = build_connect_request(&token);
+                    let range = match negotiate_protocol(challenge_protocol_range(&json)) {
+                        Ok(p) => p,
+                        Err(e) => {
+                            error!("Gateway {}", e);
+                            push_event(GatewayEvent {
+                                ts: now_ts(),
+                                kind: "error".into(),
+                                session_id: String::new(),
+                                platform: String::new(),
+                                summary: format!("Gateway {}", e),
+                                payload: text.clone(),
+                            });
+                            SHOULD_RUN.store(false, Ordering::Relaxed);
+                            break;
+                        }
+                    };
+                    // A single version is settled now; otherwise hello-ok names it.
+                    PROTOCOL.store(if range.0 == range.1 { range.0 } else { 0 }, Ordering::Relaxed);
+                    info!("Gateway challenge received, sending connect request (protocols {}-{})", range.0, range.1);
+                    let connect_req = build_connect_request(&token, range);
                     let _ = write.send(Message::Text(connect_req.to_string())).await;
                     continue;
                 }
@@ -214,8 +353,10 @@ async fn ws_loop() {
                 if frame_type == "hello-ok" {
                     authenticated = true;
                     CONNECTED.store(true, Ordering::Relaxed);
-                    let protocol = json.get("protocol").and_then(|v| v.as_u64()).unwrap_or(0);
-                    info!("Gateway WS authenticated (protocol {})", protocol);
+                    if let Some(protocol) = hello_protocol(&json) {
+                        PROTOCOL.store(protocol, Ordering::Relaxed);
+                    }
+                    info!("Gateway WS authenticated (negotiated protocol {})", PROTOCOL.load(Ordering::Relaxed));
                     continue;
                 }
 
@@ -225,6 +366,9 @@ async fn ws_loop() {
                     if ok {
                         authenticated = true;
                         CONNECTED.store(true, Ordering::Relaxed);
+                        if let Some(protocol) = hello_protocol(&json) {
+                            PROTOCOL.store(protocol, Ordering::Relaxed);
+                        }
                         info!("Gateway WS connect response OK");
                         continue;
                     } else {
@@ -304,205 +448,67 @@ async fn ws_loop() {
         }
     }
 
+    set_outbox(None);
     CONNECTED.store(false, Ordering::Relaxed);
+    PROTOCOL.store(0, Ordering::Relaxed);
     info!("Gateway WS disconnected");
+    authenticated
 }
 
-/// Parse OpenClaw gateway events using the real protocol shapes.
-/// Reference: crabwalk/src/integrations/openclaw/parser.ts
-fn parse_and_store_v2(
-    frame_type: &str,
-    event_name: &str,
-    json: &serde_json::Value,
-    raw: &str,
-) {
-    let payload = json.get("payload").unwrap_or(json);
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
 
-    let session_id = payload
-        .get("sessionKey")
-        .or_else(|| payload.get("sessionId"))
-        .or_else(|| payload.get("session_id"))
-        .and_then(|v| v.as_str())
-        .unwrap_or("")
-        .to_string();
-
-    let platform = payload
-        .get("platform")
-        .or_else(|| payload.get("channel"))
-        .and_then(|v| v.as_str())
-        .unwrap_or("")
-        .to_string();
-
-    match event_name {
-        // Chat events: delta (streaming), final, aborted, error
-        "chat" => {
-            let state = payload.get("state").and_then(|v| v.as_str()).unwrap_or("");
-            let kind = match state {
-                "delta" => "thinking",
-                "final" => "message_out",
-                "aborted" => "error",
-                "error" => "error",
-                _ => "message_out",
-            };
-            let summary = extract_chat_content(payload, state);
-            push_event(GatewayEvent {
-                ts: now_ts(), kind: kind.into(), session_id, platform, summary, payload: raw.into(),
-            });
-        }
-        // Agent events: lifecycle, assistant stream, tool_use, tool_result
-        "agent" => {
-            let stream = payload.get("stream").and_then(|v| v.as_str()).unwrap_or("");
-            let data = payload.get("data").unwrap_or(payload);
-            let data_type = data.get("type").and_then(|v| v.as_str()).unwrap_or("");
-
-            let (kind, summary) = match (stream, data_type) {
-                ("lifecycle", _) => {
-                    let phase = data.get("phase").and_then(|v| v.as_str()).unwrap_or("");
-                    match phase {
-                        "start" => ("thinking", "Run started".to_string()),
-                        "end" => ("message_out", "Run completed".to_string()),
-                        _ => ("thinking", format!("Lifecycle: {}", phase)),
-                    }
-                }
-                (_, "tool_use") => {
-                    let name = data.get("name").and_then(|v| v.as_str()).unwrap_or("unknown");
-                    ("tool_call", format!("Tool: {}", name))
-                }
-                (_, "tool_result") => {
-                    let content = data.get("content").and_then(|v| v.as_str()).unwrap_or("");
-                    let preview = truncate(content, 100);
-                    ("tool_result", format!("Result: {}", preview))
-                }
-                ("assistant", _) => {
-                    let text = data.get("text").and_then(|v| v.as_str()).unwrap_or("");
-                    ("thinking", truncate(text, 100))
-                }
-                _ => {
-                    ("thinking", truncate(&data.to_string(), 100))
-                }
-            };
-            push_event(GatewayEvent {
-                ts: now_ts(), kind: kind.into(), session_id, platform, summary, payload: raw.into(),
-            });
-        }
-        // Exec events
-        "exec.started" => {
-            let cmd = payload.get("command").and_then(|v| v.as_str()).unwrap_or("");
-            push_event(GatewayEvent {
-                ts: now_ts(), kind: "tool_call".into(), session_id, platform,
-                summary: format!("Exec: {}", truncate(cmd, 80)),
-                payload: raw.into(),
-            });
-        }
-        "exec.output" => {
-            let output = payload.get("output").and_then(|v| v.as_str()).unwrap_or("");
-            let stream = payload.get("stream").and_then(|v| v.as_str()).unwrap_or("stdout");
-            push_event(GatewayEvent {
-                ts: now_ts(), kind: "tool_result".into(), session_id, platform,
-                summary: format!("[{}] {}", stream, truncate(output, 80)),
-                payload: raw.into(),
-            });
-        }
-        "exec.completed" => {
-            let exit_code = payload.get("exitCode").and_then(|v| v.as_i64()).unwrap_or(-1);
-            let duration = payload.get("durationMs").and_then(|v| v.as_u64()).unwrap_or(0);
-            push_event(GatewayEvent {
-                ts: now_ts(), kind: "tool_result".into(), session_id, platform,
-                summary: format!("Exec done (exit {}, {}ms)", exit_code, duration),
-                payload: raw.into(),
-            });
-        }
-        // Fallback for any other event
-        _ => {
-            push_event(GatewayEvent {
-                ts: now_ts(),
-                kind: frame_type.to_string(),
-                session_id, platform,
-                summary: truncate(&json.to_string(), 120),
-                payload: raw.into(),
-            });
+/// Resize the event ring, dropping the oldest events if it shrinks. The caller clamps `cap`.
+pub(crate) fn set_event_cap(cap: usize) {
+    EVENT_CAP.store(cap, Ordering::Relaxed);
+    if let Ok(mut g) = EVENTS.write() {
+        while g.len() > cap {
+            g.pop_front();
         }
     }
 }
 
-fn extract_chat_content(payload: &serde_json::Value, state: &str) -> String {
-    // Try message.content[].text first (standard shape)
-    if let Some(msg) = payload.get("message") {
-        if let Some(content) = msg.get("content") {
-            if let Some(arr) = content.as_array() {
-                let texts: Vec<&str> = arr.iter()
-                    .filter_map(|b| {
-                        let btype = b.get("type").and_then(|v| v.as_str()).unwrap_or("");
-                        match btype {
-                            "text" => b.get("text").and_then(|v| v.as_str()),
-                            "tool_use" => b.get("name").and_then(|v| v.as_str()),
-                            _ => None,
-                        }
-                    })
-                    .collect();
-                if !texts.is_empty() {
-                    return truncate(&texts.join(""), 120);
-                }
-            }
-            if let Some(s) = content.as_str() {
-                return truncate(s, 120);
-            }
-        }
-        if let Some(s) = msg.get("text").and_then(|v| v.as_str()) {
-            return truncate(s, 120);
-        }
-        if let Some(s) = msg.as_str() {
-            return truncate(s, 120);
-        }
-    }
-    if let Some(err) = payload.get("errorMessage").and_then(|v| v.as_str()) {
-        return truncate(err, 120);
+/// Seed an empty ring with the newest persisted events so the timeline has
+/// context from before the last restart.
+fn replay_persisted() {
+    let Ok(mut g) = EVENTS.write() else { return };
+    if !g.is_empty() {
+        return;
     }
-    match state {
-        "delta" => "Thinking...".into(),
-        "final" => "Response complete".into(),
-        "aborted" => "Aborted".into(),
-        "error" => "Error".into(),
-        _ => state.to_string(),
-    }
-}
-
-fn truncate(s: &str, max: usize) -> String {
-    if s.len() > max {
-        format!("{}...", &s[..max])
-    } else {
-        s.to_string()
+    let events = gateway_store::load_tail(EVENT_CAP.load(Ordering::Relaxed));
+    for evt in &events {
+        gateway_sessions::touch(evt);
     }
+    info!("Replayed {} persisted gateway events", events.len());
+    g.extend(events);
 }
 
-/// Legacy parser kept for non-gateway events (e.g. from evidence log)
-fn parse_and_store(raw: &str) {
-    if let Ok(json) = serde_json::from_str::<serde_json::Value>(raw) {
-        let frame_type = json.get("type").and_then(|v| v.as_str()).unwrap_or("");
-        let event_name = json.get("event").and_then(|v| v.as_str()).unwrap_or("");
-        parse_and_store_v2(frame_type, event_name, &json, raw);
-    } else {
-        push_event(GatewayEvent {
-            ts: now_ts(),
-            kind: "unknown".into(),
-            session_id: String::new(),
-            platform: String::new(),
-            summary: truncate(raw, 120),
-            payload: raw.to_string(),
-        });
-    }
+/// Stop the reconnect loop after `max` failed attempts and tell the UI why.
+fn give_up(max: u32) {
+    error!("Gateway WS giving up after {} reconnect attempts", max);
+    SHOULD_RUN.store(false, Ordering::Relaxed);
+    CONNECTED.store(false, Ordering::Relaxed);
+    GAVE_UP.store(true, Ordering::Relaxed);
+    push_event(GatewayEvent {
+        ts: now_ts(),
+        kind: "error".into(),
+        session_id: String::new(),
+        platform: String::new(),
+        summary: format!("Gave up reconnecting to the gateway after {} attempts", max),
+        payload: String::new(),
+    });
 }
 
-// ---------------------------------------------------------------------------
-// Tauri commands
-// ---------------------------------------------------------------------------
-
 #[tauri::command]
 pub fn gateway_connect() -> Result<String, String> {
     if CONNECTED.load(Ordering::Relaxed) {
         return Ok("Already connected".into());
     }
     SHOULD_RUN.store(true, Ordering::Relaxed);
+    GAVE_UP.store(false, Ordering::Relaxed);
+    RECONNECT_ATTEMPTS.store(0, Ordering::Relaxed);
+    replay_persisted();
     std::thread::spawn(|| {
         let rt = tokio::runtime::Builder::new_current_thread()
             .enable_all()
@@ -510,10 +516,19 @@ pub fn gateway_connect() -> Result<String, String> {
             .expect("gateway ws runtime");
         rt.block_on(async {
             loop {
-                ws_loop().await;
+                if ws_loop().await {
+                    RECONNECT_ATTEMPTS.store(0, Ordering::Relaxed);
+                }
                 if !SHOULD_RUN.load(Ordering::Relaxed) {
                     break;
                 }
+                let attempts = RECONNECT_ATTEMPTS.fetch_add(1, Ordering::Relaxed) + 1;
+                if let Some(max) = crate::settings::get().gateway_max_reconnects {
+                    if attempts > max {
+                        give_up(max);
+                        break;
+                    }
+                }
                 // Reconnect after 3 seconds if still supposed to run
                 info!("Gateway WS reconnecting in 3s...");
                 tokio::time::sleep(std::time::Duration::from_secs(3)).await;
@@ -541,13 +556,31 @@ pub fn gateway_status() -> Result<GatewayStatus, String> {
         connected: CONNECTED.load(Ordering::Relaxed),
         event_count,
         gateway_url,
+        protocol: PROTOCOL.load(Ordering::Relaxed),
+        reconnect_attempts: RECONNECT_ATTEMPTS.load(Ordering::Relaxed),
+        gave_up: GAVE_UP.load(Ordering::Relaxed),
     })
 }
 
 #[tauri::command]
 pub fn get_gateway_events() -> Result<Vec<GatewayEvent>, String> {
     let g = EVENTS.read().map_err(|_| "lock")?;
-    Ok(g.iter().cloned().collect())
+    Ok(g.iter().filter(|e| kind_wanted(&e.kind)).cloned().collect())
+}
+
+/// Only store and emit events of the given kinds (e.g. `tool_call`, `error`).
+/// An empty list or `"all"` restores the default of keeping everything.
+#[tauri::command]
+pub fn gateway_set_filter(kinds: Vec<String>) -> Result<Vec<String>, String> {
+    let kinds: Vec<String> = kinds
+        .into_iter()
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty())
+        .collect();
+    let mut f = KIND_FILTER.write().map_err(|_| "lock")?;
+    *f = kinds.clone();
+    info!("Gateway event filter: {:?}", kinds);
+    Ok(kinds)
 }
 
 #[tauri::command]
@@ -555,5 +588,40 @@ pub fn gateway_clear_events() -> Result<String, String> {
     if let Ok(mut g) = EVENTS.write() {
         g.clear();
     }
+    gateway_sessions::clear();
+    gateway_store::clear();
     Ok("Cleared".into())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn offered(challenge: serde_json::Value) -> Result<(u64, u64), String> {
+        negotiate_protocol(challenge_protocol_range(&challenge))
+    }
+
+    #[test]
+    fn challenge_without_a_range_gets_the_baseline_protocol() {
+        let range = offered(json!({"type": "event", "event": "connect.challenge", "payload": {"nonce": "n"}})).unwrap();
+        let req = build_connect_request(&None, range);
+        assert_eq!(req["params"]["minProtocol"], 3);
+        assert_eq!(req["params"]["maxProtocol"], 3);
+    }
+
+    #[test]
+    fn advertised_range_is_narrowed_to_what_the_parser_handles() {
+        assert_eq!(offered(json!({"payload": {"minProtocol": 2, "maxProtocol": 4}})), Ok((3, 3)));
+        assert_eq!(offered(json!({"payload": {"protocols": [1, 3]}})), Ok((3, 3)));
+        assert!(offered(json!({"payload": {"minProtocol": 4, "maxProtocol": 5}})).is_err());
+        assert!(offered(json!({"payload": {"protocol": 2}})).is_err());
+    }
+
+    #[test]
+    fn chosen_protocol_is_read_from_hello_ok() {
+        assert_eq!(hello_protocol(&json!({"type": "hello-ok", "protocol": 3})), Some(3));
+        assert_eq!(hello_protocol(&json!({"type": "res", "ok": true, "payload": {"type": "hello-ok", "protocol": 3}})), Some(3));
+        assert_eq!(hello_protocol(&json!({"type": "res", "ok": true})), None);
+    }
+}
@@ -0,0 +1,86 @@
+//! `--headless` mode: run the proxy without the Tauri window, for servers and CI.
+//!
+//!     vault0-desktop --headless [--policy <path>]
+//!
+//! The vault is unlocked with the passphrase in `VAULT0_PASSPHRASE`, its entries
+//! are loaded into the proxy, and the proxy runs until Ctrl-C / SIGINT.
+
+use crate::{policy, proxy, settings, vault_store};
+use tracing::{info, warn};
+
+const PASSPHRASE_ENV: &str = "VAULT0_PASSPHRASE";
+
+pub struct HeadlessArgs {
+    pub policy_path: Option<String>,
+}
+
+/// `Some` when the command line asks for headless mode.
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Option<Result<HeadlessArgs, String>> {
+    let mut headless = false;
+    let mut policy_path = None;
+    let mut args = args.into_iter().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--headless" => headless = true,
+            "--policy" => match args.next() {
+                Some(p) => policy_path = Some(p),
+                None => return Some(Err("--policy needs a path".into())),
+            },
+            _ => {
+                if let Some(p) = arg.strip_prefix("--policy=") {
+                    policy_path = Some(p.to_string());
+                }
+            }
+        }
+    }
+    headless.then_some(Ok(HeadlessArgs { policy_path }))
+}
+
+/// Unlock the vault, load the policy and start the proxy, the same steps the
+/// app takes through its commands.
+fn start(args: &HeadlessArgs) -> Result<(), String> {
+    settings::apply(&settings::get());
+
+    match std::env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) if !passphrase.is_empty() => {
+            let loaded = vault_store::unlock(&passphrase)?;
+            info!("Loaded {} vault secret(s) into the proxy", loaded);
+        }
+        _ if vault_store::vault_exists() => {
+            warn!("{} not set; the vault stays locked and no secrets will be injected", PASSPHRASE_ENV);
+        }
+        _ => {}
+    }
+
+    if let Some(path) = &args.policy_path {
+        if !std::path::Path::new(path).exists() {
+            return Err(format!("Policy file not found: {}", path));
+        }
+    }
+    let policy = policy::load_policy(args.policy_path.clone())?;
+    info!("Policy loaded ({} allowed domain(s))", policy.allow_domains.len());
+
+    proxy::start().map_err(|e| e.to_string())
+}
+
+/// Run until SIGINT. Returns the process exit code.
+pub fn run(args: HeadlessArgs) -> i32 {
+    if let Err(e) = start(&args) {
+        eprintln!("vault0: {}", e);
+        return 1;
+    }
+    info!("Vault-0 running headless; press Ctrl-C to stop");
+    let rt = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(rt) => rt,
+        Err(e) => {
+            eprintln!("vault0: runtime: {}", e);
+            return 1;
+        }
+    };
+    if let Err(e) = rt.block_on(tokio::signal::ctrl_c()) {
+        eprintln!("vault0: waiting for Ctrl-C: {}", e);
+    }
+    let _ = proxy::stop();
+    info!("Vault-0 stopped");
+    0
+}
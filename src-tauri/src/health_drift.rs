@@ -0,0 +1,71 @@
+//! Config drift: keys that `harden_openclaw` moved into the vault but that are
+//! back in plaintext (typically an agent rewriting its own config).
+//! Baseline file: <config dir>/vault0/hardening_baseline.json.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const BASELINE_FILE: &str = "hardening_baseline.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Baseline {
+    install_path: String,
+    created_at: u64,
+    /// Key names secured by that hardening run.
+    secured_keys: Vec<String>,
+}
+
+fn baseline_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|p| p.join("vault0").join(BASELINE_FILE))
+}
+
+fn load_baseline() -> Option<Baseline> {
+    let content = fs::read_to_string(baseline_path()?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Record the keys secured by a hardening run, replacing the previous baseline.
+/// Does nothing when no keys were secured so an idle re-run keeps the old baseline.
+pub(crate) fn record_baseline(install_path: &str, mut secured_keys: Vec<String>) {
+    if secured_keys.is_empty() {
+        return;
+    }
+    let Some(path) = baseline_path() else { return };
+    secured_keys.sort();
+    secured_keys.dedup();
+    let baseline = Baseline {
+        install_path: install_path.to_string(),
+        created_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        secured_keys,
+    };
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    match serde_json::to_string_pretty(&baseline) {
+        Ok(json) => {
+            if let Err(e) = fs::write(&path, json) {
+                tracing::warn!("Could not write hardening baseline: {}", e);
+            }
+        }
+        Err(e) => tracing::warn!("Could not serialize hardening baseline: {}", e),
+    }
+}
+
+/// Baseline keys found in plaintext again, in the hardened install or the home
+/// config files. Empty when no hardening has been recorded.
+pub(crate) fn drifted_keys() -> Vec<String> {
+    let Some(baseline) = load_baseline() else { return Vec::new() };
+    let mut findings = crate::detect::scan_install_dir(Path::new(&baseline.install_path));
+    if let Some(home) = crate::detect::home_dir() {
+        findings.extend(crate::detect::scan_for_keys(&home));
+    }
+    baseline
+        .secured_keys
+        .into_iter()
+        .filter(|k| findings.iter().any(|f| &f.key_name == k))
+        .collect()
+}
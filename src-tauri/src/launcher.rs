@@ -1,17 +1,190 @@
-use crate::evidence;
+use crate::{evidence, launcher_output, launcher_registry, launcher_sandbox};
+use serde::Deserialize;
 use std::collections::HashMap;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
 
-const PROXY_ADDR: &str = "http://127.0.0.1:3840";
+/// Resolve `aliases` from the unlocked vault into `(ENV_NAME, secret)` pairs,
+/// refusing any alias the policy's `agent_env_rules` don't grant to this script.
+fn env_secrets(script_path: &str, aliases: &[String]) -> Result<Vec<(String, String)>, String> {
+    let allowed = {
+        let state = crate::proxy::state().read().map_err(|_| "state lock")?;
+        crate::policy::allowed_env_aliases(&state.policy, script_path)
+    };
+    let mut secrets = Vec::new();
+    for alias in aliases {
+        if let Some(allowed) = &allowed {
+            if !allowed.contains(alias) {
+                evidence::push("blocked", &format!("Policy does not allow {} to receive '{}'", script_path, alias));
+                return Err(format!("Policy does not allow this script to receive '{}'", alias));
+            }
+        }
+        let value = crate::vault_store::vault_get_secret(alias.clone())?;
+        secrets.push((crate::secure_launch::env_var_name(alias), value));
+    }
+    Ok(secrets)
+}
+
+/// Built-in interpreter per script extension; `agent_interpreters` in settings overrides these.
+fn default_interpreter(ext: &str) -> Option<&'static str> {
+    match ext {
+        "py" if cfg!(windows) => Some("python"),
+        "py" => Some("python3"),
+        "js" | "mjs" => Some("node"),
+        "ts" => Some("npx tsx"),
+        "sh" => Some("sh"),
+        _ => None,
+    }
+}
+
+/// Program and leading args for a script extension, e.g. `bun run` -> (`bun`, [`run`]).
+fn interpreter_for(ext: &str) -> Result<(String, Vec<String>), String> {
+    let interpreters = crate::settings::get().agent_interpreters;
+    let configured = interpreters.get(ext).or_else(|| interpreters.get(&format!(".{}", ext))).cloned();
+    let command = configured
+        .or_else(|| default_interpreter(ext).map(String::from))
+        .ok_or_else(|| format!("Unsupported file type: .{} (add it to agent_interpreters in settings)", ext))?;
+    let mut parts = command.split_whitespace().map(String::from);
+    let program = parts.next().ok_or_else(|| format!("Empty interpreter configured for .{}", ext))?;
+    Ok((program, parts.collect()))
+}
+
+/// Requested working dir if it exists, else the script's own directory.
+fn working_dir_for(script: &Path, requested: Option<String>) -> PathBuf {
+    let script_dir = script
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."));
+    match requested.map(|d| d.trim().to_string()).filter(|d| !d.is_empty()) {
+        Some(dir) if Path::new(&dir).is_dir() => PathBuf::from(dir),
+        Some(dir) => {
+            evidence::push(
+                "warning",
+                &format!("Working dir {} not found; using {}", dir, script_dir.display()),
+            );
+            script_dir
+        }
+        None => script_dir,
+    }
+}
+
+/// Safety valves for a launched agent. All optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct LaunchLimits {
+    /// Stop the agent (SIGTERM, then SIGKILL) once it has run this long.
+    pub max_runtime_secs: Option<u64>,
+    /// Unix only: niceness added to the child, 0–19.
+    pub nice: Option<i32>,
+    /// Unix only: address-space cap (RLIMIT_AS) in MiB.
+    pub max_memory_mb: Option<u64>,
+}
+
+/// Apply `nice` and the memory cap in the child between fork and exec.
+#[cfg(unix)]
+fn apply_os_limits(command: &mut Command, limits: &LaunchLimits) {
+    use std::os::unix::process::CommandExt;
+    let (nice, memory_mb) = (limits.nice, limits.max_memory_mb);
+    if nice.is_none() && memory_mb.is_none() {
+        return;
+    }
+    // SAFETY: the closure runs in the forked child and only makes the
+    // async-signal-safe nice/setrlimit calls; it does not allocate or lock.
+    unsafe {
+        command.pre_exec(move || {
+            if let Some(n) = nice {
+                libc::nice(n.clamp(0, 19));
+            }
+            if let Some(mb) = memory_mb {
+                let bytes = mb.saturating_mul(1024 * 1024) as libc::rlim_t;
+                let limit = libc::rlimit { rlim_cur: bytes, rlim_max: bytes };
+                if libc::setrlimit(libc::RLIMIT_AS, &limit) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+fn apply_os_limits(_command: &mut Command, limits: &LaunchLimits) {
+    if limits.nice.is_some() || limits.max_memory_mb.is_some() {
+        evidence::push("warning", "nice and memory limits are not supported on this platform; ignoring them");
+    }
+}
+
+/// Relaunch an agent that exits non-zero, up to `max_restarts` times. The wait
+/// before restart `n` is `backoff_secs * 2^n`, capped at a minute.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RestartPolicy {
+    pub restart_on_crash: bool,
+    pub max_restarts: u32,
+    pub backoff_secs: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy { restart_on_crash: false, max_restarts: 3, backoff_secs: 2 }
+    }
+}
+
+/// Everything needed to start, and restart, an agent process. `env` includes
+/// injected secrets, so a spec only ever lives in memory.
+pub(crate) struct LaunchSpec {
+    pub script: String,
+    pub program: String,
+    args: Vec<String>,
+    env: HashMap<String, String>,
+    cwd: PathBuf,
+    pub limits: LaunchLimits,
+    pub restart: RestartPolicy,
+}
+
+/// Start the process described by `spec` and stream its output into evidence.
+pub(crate) fn spawn(spec: &LaunchSpec) -> Result<Child, String> {
+    let mut command = Command::new(&spec.program);
+    command
+        .args(&spec.args)
+        .envs(&spec.env)
+        .current_dir(&spec.cwd)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    apply_os_limits(&mut command, &spec.limits);
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to spawn {}: {}", spec.program, e))?;
+    let pid = child.id();
+    if let Some(out) = child.stdout.take() {
+        launcher_output::forward(pid, "stdout", out);
+    }
+    if let Some(err) = child.stderr.take() {
+        launcher_output::forward(pid, "stderr", err);
+    }
+    Ok(child)
+}
 
 /// Launch an agent script with HTTP_PROXY / HTTPS_PROXY set to the Vault-0 proxy.
+/// `env_aliases` are vault aliases exposed to the child only, as uppercased env vars.
+/// The script runs in `working_dir`, or its own directory when unset.
+/// With `sandbox`, the agent runs under OS confinement (see `launcher_sandbox`).
 #[tauri::command]
-pub fn launch_agent(script_path: String) -> Result<String, String> {
+pub fn launch_agent(
+    script_path: String,
+    env_aliases: Option<Vec<String>>,
+    working_dir: Option<String>,
+    limits: Option<LaunchLimits>,
+    restart: Option<RestartPolicy>,
+    sandbox: Option<bool>,
+) -> Result<String, String> {
     if !crate::proxy::is_running() {
         return Err("Proxy must be running before launching an agent.".to_string());
     }
 
-    let path = std::path::Path::new(&script_path);
+    let path = Path::new(&script_path);
     if !path.exists() {
         return Err(format!("Script not found: {}", script_path));
     }
@@ -22,31 +195,56 @@ pub fn launch_agent(script_path: String) -> Result<String, String> {
         .unwrap_or("")
         .to_lowercase();
 
-    let (program, args): (&str, Vec<&str>) = match ext.as_str() {
-        "py" => ("python3", vec![&script_path]),
-        "js" | "mjs" => ("node", vec![&script_path]),
-        "ts" => ("npx", vec!["tsx", &script_path]),
-        "sh" => ("sh", vec![&script_path]),
-        _ => return Err(format!("Unsupported file type: .{}", ext)),
-    };
+    let (mut program, mut args) = interpreter_for(&ext)?;
+    args.push(script_path.clone());
+    let script_dir = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let cwd = working_dir_for(path, working_dir);
 
+    let proxy_url = format!("http://{}", crate::settings::proxy_addr());
     let mut env: HashMap<String, String> = std::env::vars().collect();
-    env.insert("HTTP_PROXY".to_string(), PROXY_ADDR.to_string());
-    env.insert("HTTPS_PROXY".to_string(), PROXY_ADDR.to_string());
-    env.insert("http_proxy".to_string(), PROXY_ADDR.to_string());
-    env.insert("https_proxy".to_string(), PROXY_ADDR.to_string());
-
-    let child = Command::new(program)
-        .args(&args)
-        .envs(&env)
-        .spawn()
-        .map_err(|e| format!("Failed to spawn {}: {}", program, e))?;
+    env.insert("HTTP_PROXY".to_string(), proxy_url.clone());
+    env.insert("HTTPS_PROXY".to_string(), proxy_url.clone());
+    env.insert("http_proxy".to_string(), proxy_url.clone());
+    env.insert("https_proxy".to_string(), proxy_url);
+    let secrets = env_secrets(&script_path, &env_aliases.unwrap_or_default())?;
+    let injected: Vec<String> = secrets.iter().map(|(k, _)| k.clone()).collect();
+    env.extend(secrets);
+
+    let mut confinement = None;
+    if sandbox.unwrap_or(false) {
+        let wrapped = launcher_sandbox::wrap(&program, &args, &cwd, script_dir, &mut env)?;
+        program = wrapped.program;
+        args = wrapped.args;
+        confinement = Some(wrapped.confinement);
+    }
 
+    let spec = LaunchSpec {
+        script: script_path.clone(),
+        program,
+        args,
+        env,
+        cwd,
+        limits: limits.unwrap_or_default(),
+        restart: restart.unwrap_or_default(),
+    };
+    launcher_registry::deadline_for(&spec.limits)?;
+    let child = spawn(&spec)?;
     let pid = child.id();
     evidence::push(
         "info",
-        &format!("Launched agent {} (pid {}) via {}", script_path, pid, program),
+        &format!("Launched agent {} (pid {}) via {} in {}", script_path, pid, spec.program, spec.cwd.display()),
     );
+    match &confinement {
+        Some(c) => evidence::push("info", &format!("Agent pid {} confined with {}", pid, c)),
+        None => tracing::info!("Agent pid {} launched without OS sandbox", pid),
+    }
+    if !injected.is_empty() {
+        evidence::push("info", &format!("Injected {} into agent pid {} environment", injected.join(", "), pid));
+    }
+    if let Some(secs) = spec.limits.max_runtime_secs {
+        evidence::push("info", &format!("Agent pid {} will be stopped after {}s", pid, secs));
+    }
+    launcher_registry::register(child, Arc::new(spec), 0);
 
     Ok(format!("Agent launched (pid {})", pid))
 }
@@ -0,0 +1,67 @@
+//! Streams a launched agent's stdout/stderr into the evidence log, one entry per
+//! line (kind `agent`, tagged with the PID), rate-limited so a chatty agent
+//! can't push everything else out of the in-memory ring.
+
+use std::io::{BufRead, BufReader, Read};
+use std::time::{Duration, Instant};
+
+use crate::evidence::{self, EvidenceFields};
+
+const MAX_LINE_CHARS: usize = 500;
+/// Per stream: at most `BURST_LINES` entries per `BURST_WINDOW`; the rest are
+/// counted and reported as a single "suppressed" entry.
+const BURST_LINES: usize = 40;
+const BURST_WINDOW: Duration = Duration::from_secs(10);
+
+fn truncate(line: &str) -> String {
+    match line.char_indices().nth(MAX_LINE_CHARS) {
+        Some((idx, _)) => format!("{}… [truncated]", &line[..idx]),
+        None => line.to_string(),
+    }
+}
+
+fn push_line(pid: u32, stream: &str, msg: &str) {
+    evidence::push_structured(
+        "agent",
+        &format!("[pid {} {}] {}", pid, stream, msg),
+        EvidenceFields { pid: Some(pid), ..Default::default() },
+    );
+}
+
+/// Read `source` line by line on a detached thread until the child closes it.
+/// The thread holds no app state, so it never delays shutdown.
+pub(crate) fn forward<R: Read + Send + 'static>(pid: u32, stream: &'static str, source: R) {
+    let spawned = std::thread::Builder::new()
+        .name(format!("agent-{}-{}", pid, stream))
+        .spawn(move || {
+            let mut window_start = Instant::now();
+            let mut in_window = 0usize;
+            let mut suppressed = 0usize;
+            for line in BufReader::new(source).lines() {
+                let Ok(line) = line else { break };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                if window_start.elapsed() >= BURST_WINDOW {
+                    if suppressed > 0 {
+                        push_line(pid, stream, &format!("({} lines suppressed)", suppressed));
+                    }
+                    window_start = Instant::now();
+                    in_window = 0;
+                    suppressed = 0;
+                }
+                if in_window < BURST_LINES {
+                    in_window += 1;
+                    push_line(pid, stream, &truncate(&line));
+                } else {
+                    suppressed += 1;
+                }
+            }
+            if suppressed > 0 {
+                push_line(pid, stream, &format!("({} lines suppressed)", suppressed));
+            }
+        });
+    if let Err(e) = spawned {
+        tracing::warn!("Could not capture agent {} {}: {}", pid, stream, e);
+    }
+}
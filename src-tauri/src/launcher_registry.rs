@@ -0,0 +1,289 @@
+//! Registry of agents started by `launch_agent`, so they can be listed and
+//! stopped from the app instead of from a shell. A watcher thread notices when
+//! an agent exits, logs the exit code and emits `agent-exited`, and relaunches
+//! crashed agents whose restart policy allows it.
+
+use once_cell::sync::{Lazy, OnceCell};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::process::{Child, Command, ExitStatus};
+use std::sync::{Arc, Mutex, Once};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use crate::evidence;
+use crate::launcher::{self, LaunchLimits, LaunchSpec};
+
+/// Time between the polite stop signal and the hard kill.
+const STOP_GRACE: Duration = Duration::from_secs(5);
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+const EXIT_EVENT: &str = "agent-exited";
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentInfo {
+    pub pid: u32,
+    pub script: String,
+    pub program: String,
+    /// Unix seconds.
+    pub started_at: u64,
+    /// Crash restarts so far for this launch.
+    pub restarts: u32,
+    /// `running`, or `gave_up` once the restart budget is spent.
+    pub state: String,
+}
+
+struct RunningAgent {
+    info: AgentInfo,
+    child: Child,
+    spec: Arc<LaunchSpec>,
+    /// When `max_runtime_secs` runs out.
+    deadline: Option<Instant>,
+}
+
+/// Payload of the `agent-exited` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentExit {
+    pub pid: u32,
+    pub script: String,
+    /// `None` when the process was ended by a signal.
+    pub code: Option<i32>,
+    pub success: bool,
+    /// `exited`, `stopped` (via `stop_agent`), `timed out` or `killed` (ignored the stop signal).
+    pub reason: String,
+}
+
+static AGENTS: Lazy<Mutex<HashMap<u32, RunningAgent>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static APP: OnceCell<AppHandle> = OnceCell::new();
+static WATCHER: Once = Once::new();
+/// Crashed agents that exhausted their restart budget, kept for `list_agents`
+/// until dismissed with `stop_agent`.
+static GAVE_UP: Lazy<Mutex<Vec<AgentInfo>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Called once from the Tauri setup hook so exits reach the UI.
+pub fn set_app_handle(app: AppHandle) {
+    let _ = APP.set(app);
+}
+
+fn report_exit(info: &AgentInfo, status: Option<ExitStatus>, reason: &str) {
+    let code = status.and_then(|s| s.code());
+    let success = status.map(|s| s.success()).unwrap_or(false);
+    let code_text = code.map(|c| c.to_string()).unwrap_or_else(|| "signal".into());
+    let kind = if success || reason != "exited" { "info" } else { "error" };
+    evidence::push_structured(
+        kind,
+        &format!("Agent {} (pid {}) {} with code {}", info.script, info.pid, reason, code_text),
+        evidence::EvidenceFields { pid: Some(info.pid), ..Default::default() },
+    );
+    if let Some(app) = APP.get() {
+        let _ = app.emit(
+            EXIT_EVENT,
+            AgentExit { pid: info.pid, script: info.script.clone(), code, success, reason: reason.to_string() },
+        );
+    }
+}
+
+/// Poll registered agents and report the ones that have exited. Polling
+/// `try_wait` keeps each `Child` in the registry so `stop_agent` can still use it.
+fn start_watcher() {
+    WATCHER.call_once(|| {
+        let spawned = std::thread::Builder::new().name("agent-watcher".into()).spawn(|| loop {
+            std::thread::sleep(WATCH_INTERVAL);
+            let now = Instant::now();
+            let (exited, overdue) = match AGENTS.lock() {
+                Ok(mut agents) => {
+                    let mut done: Vec<(u32, Option<ExitStatus>)> = Vec::new();
+                    for (pid, a) in agents.iter_mut() {
+                        match a.child.try_wait() {
+                            Ok(Some(status)) => done.push((*pid, Some(status))),
+                            _ if a.deadline.is_some_and(|d| now >= d) => done.push((*pid, None)),
+                            _ => {}
+                        }
+                    }
+                    let mut exited = Vec::new();
+                    let mut overdue = Vec::new();
+                    for (pid, status) in done {
+                        let Some(agent) = agents.remove(&pid) else { continue };
+                        match status {
+                            Some(status) => exited.push((agent, status)),
+                            None => overdue.push(agent),
+                        }
+                    }
+                    (exited, overdue)
+                }
+                Err(_) => continue,
+            };
+            for (agent, status) in exited {
+                report_exit(&agent.info, Some(status), "exited");
+                if !status.success() && agent.spec.restart.restart_on_crash {
+                    schedule_restart(agent);
+                }
+            }
+            for agent in overdue {
+                let runtime = now_secs().saturating_sub(agent.info.started_at);
+                evidence::push_structured(
+                    "warning",
+                    &format!("Agent {} (pid {}) timed out after {}s", agent.info.script, agent.info.pid, runtime),
+                    evidence::EvidenceFields { pid: Some(agent.info.pid), ..Default::default() },
+                );
+                terminate(agent, "timed out");
+            }
+        });
+        if let Err(e) = spawned {
+            tracing::warn!("Could not start agent watcher: {}", e);
+        }
+    });
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// When an agent started now must be stopped, or an error if `max_runtime_secs`
+/// is too large to represent as a deadline.
+pub(crate) fn deadline_for(limits: &LaunchLimits) -> Result<Option<Instant>, String> {
+    match limits.max_runtime_secs {
+        Some(secs) => Instant::now()
+            .checked_add(Duration::from_secs(secs))
+            .map(Some)
+            .ok_or_else(|| format!("max_runtime_secs {} is too large", secs)),
+        None => Ok(None),
+    }
+}
+
+pub(crate) fn register(child: Child, spec: Arc<LaunchSpec>, restarts: u32) {
+    let pid = child.id();
+    let info = AgentInfo {
+        pid,
+        script: spec.script.clone(),
+        program: spec.program.clone(),
+        started_at: now_secs(),
+        restarts,
+        state: "running".into(),
+    };
+    // Validated by `launch_agent`; computed before taking the lock either way.
+    let deadline = deadline_for(&spec.limits).ok().flatten();
+    if let Ok(mut agents) = AGENTS.lock() {
+        agents.insert(pid, RunningAgent { info, child, spec, deadline });
+    }
+    start_watcher();
+}
+
+/// Relaunch a crashed agent after its backoff, or record that it gave up.
+fn schedule_restart(agent: RunningAgent) {
+    let RunningAgent { mut info, spec, .. } = agent;
+    let policy = &spec.restart;
+    if info.restarts >= policy.max_restarts {
+        evidence::push(
+            "warning",
+            &format!("Agent {} crashed {} times; not restarting again", info.script, info.restarts + 1),
+        );
+        info.state = "gave_up".into();
+        if let Ok(mut gave_up) = GAVE_UP.lock() {
+            gave_up.push(info);
+        }
+        return;
+    }
+    let attempt = info.restarts + 1;
+    let backoff = Duration::from_secs(policy.backoff_secs.saturating_mul(1 << info.restarts.min(16))).min(MAX_BACKOFF);
+    std::thread::spawn(move || {
+        std::thread::sleep(backoff);
+        match launcher::spawn(&spec) {
+            Ok(child) => {
+                evidence::push_structured(
+                    "info",
+                    &format!(
+                        "Restarted agent {} (attempt {}/{}), pid {} -> {}",
+                        spec.script,
+                        attempt,
+                        spec.restart.max_restarts,
+                        info.pid,
+                        child.id()
+                    ),
+                    evidence::EvidenceFields { pid: Some(child.id()), ..Default::default() },
+                );
+                register(child, spec, attempt);
+            }
+            Err(e) => {
+                evidence::push("error", &format!("Restart of agent {} failed: {}", spec.script, e));
+                info.restarts = attempt;
+                info.state = "gave_up".into();
+                if let Ok(mut gave_up) = GAVE_UP.lock() {
+                    gave_up.push(info);
+                }
+            }
+        }
+    });
+}
+
+/// Ask the process to exit: SIGTERM on Unix, `taskkill` without `/F` on Windows.
+fn request_stop(pid: u32) -> bool {
+    let status = if cfg!(windows) {
+        Command::new("taskkill").args(["/PID", &pid.to_string(), "/T"]).status()
+    } else {
+        Command::new("kill").args(["-TERM", &pid.to_string()]).status()
+    };
+    status.map(|s| s.success()).unwrap_or(false)
+}
+
+#[tauri::command]
+pub fn list_agents() -> Result<Vec<AgentInfo>, String> {
+    let agents = AGENTS.lock().map_err(|_| "agent registry lock")?;
+    let mut list: Vec<AgentInfo> = agents.values().map(|a| a.info.clone()).collect();
+    if let Ok(gave_up) = GAVE_UP.lock() {
+        list.extend(gave_up.iter().cloned());
+    }
+    list.sort_by_key(|a| a.started_at);
+    Ok(list)
+}
+
+/// Stop a launched agent: a polite signal first, then a hard kill if it is
+/// still running after a short grace period. Returns once the signal is sent.
+/// For an agent that gave up restarting, this dismisses it from the list.
+#[tauri::command]
+pub fn stop_agent(pid: u32) -> Result<String, String> {
+    if let Ok(mut gave_up) = GAVE_UP.lock() {
+        if let Some(idx) = gave_up.iter().position(|a| a.pid == pid) {
+            gave_up.remove(idx);
+            return Ok(format!("Agent {} dismissed", pid));
+        }
+    }
+    let agent = {
+        let mut agents = AGENTS.lock().map_err(|_| "agent registry lock")?;
+        agents.remove(&pid).ok_or_else(|| format!("No running agent with pid {}", pid))?
+    };
+    terminate(agent, "stopped");
+    Ok(format!("Stopping agent {}", pid))
+}
+
+/// Send the stop signal and, on a background thread, hard-kill the agent if it
+/// outlives the grace period. `reason` labels a clean stop in the exit report.
+fn terminate(mut agent: RunningAgent, reason: &'static str) {
+    let pid = agent.info.pid;
+    if !request_stop(pid) {
+        let _ = agent.child.kill();
+        let status = agent.child.wait().ok();
+        report_exit(&agent.info, status, "killed");
+        return;
+    }
+    std::thread::spawn(move || {
+        let deadline = Instant::now() + STOP_GRACE;
+        while Instant::now() < deadline {
+            if let Ok(Some(status)) = agent.child.try_wait() {
+                report_exit(&agent.info, Some(status), reason);
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(200));
+        }
+        let _ = agent.child.kill();
+        let status = agent.child.wait().ok();
+        evidence::push(
+            "warning",
+            &format!("Agent {} (pid {}) ignored the stop signal", agent.info.script, pid),
+        );
+        report_exit(&agent.info, status, "killed");
+    });
+}
@@ -0,0 +1,152 @@
+//! OS-level confinement for launched agents. The agent's command is wrapped in
+//! `sandbox-exec` on macOS or `bwrap` (bubblewrap) on Linux so it can only write
+//! to its working directory and a private temp dir, and can't read the rest of
+//! the user's home. Launching fails rather than running unconfined when the
+//! sandbox tool is missing.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::detect_platform::find_on_path;
+
+/// A wrapped command and a label for the confinement that was applied.
+pub(crate) struct Sandboxed {
+    pub program: String,
+    pub args: Vec<String>,
+    pub confinement: String,
+}
+
+/// Private temp dir for one launch, exported to the agent as TMPDIR.
+fn agent_tmp_dir() -> Result<PathBuf, String> {
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let dir = std::env::temp_dir().join(format!("vault0-agent-{}", stamp));
+    std::fs::create_dir_all(&dir).map_err(|e| format!("sandbox temp dir {}: {}", dir.display(), e))?;
+    Ok(dir)
+}
+
+/// Install root of the interpreter (`~/.nvm/versions/node/v20` for
+/// `.../bin/node`), which must stay readable when it lives under home.
+fn interpreter_root(program: &str) -> Option<PathBuf> {
+    let path = if Path::new(program).is_absolute() { PathBuf::from(program) } else { find_on_path(program)? };
+    let path = path.canonicalize().unwrap_or(path);
+    path.parent()?.parent().map(Path::to_path_buf)
+}
+
+/// SBPL profile: no network except the proxy port on localhost, writes only to
+/// the working and temp dirs, and no reads under home outside the paths the
+/// agent needs. Later rules take precedence over earlier ones.
+#[cfg(target_os = "macos")]
+fn macos_profile(readable: &[&Path], writable: &[&Path], home: Option<&Path>, proxy_port: u16) -> String {
+    let quote = |p: &Path| format!("\"{}\"", p.to_string_lossy().replace('\\', "\\\\").replace('"', "\\\""));
+    let subpaths = |paths: &[&Path]| paths.iter().map(|p| format!(" (subpath {})", quote(p))).collect::<String>();
+    let mut profile = String::from("(version 1)\n(allow default)\n");
+    profile.push_str("(deny network*)\n");
+    profile.push_str(&format!("(allow network-outbound (remote ip \"localhost:{}\"))\n", proxy_port));
+    profile.push_str("(deny file-write*)\n");
+    profile.push_str(&format!("(allow file-write*{} (literal \"/dev/null\"))\n", subpaths(writable)));
+    if let Some(home) = home {
+        profile.push_str(&format!("(deny file-read* (subpath {}))\n", quote(home)));
+        profile.push_str(&format!("(allow file-read*{}{})\n", subpaths(readable), subpaths(writable)));
+    }
+    profile
+}
+
+/// Wrap `program args` for confinement. `cwd` is the agent's working dir,
+/// `script_dir` the directory holding the script. TMPDIR in `env` is pointed at
+/// a fresh private temp dir.
+pub(crate) fn wrap(
+    program: &str,
+    args: &[String],
+    cwd: &Path,
+    script_dir: &Path,
+    env: &mut HashMap<String, String>,
+) -> Result<Sandboxed, String> {
+    let tmp = agent_tmp_dir()?;
+    env.insert("TMPDIR".to_string(), tmp.to_string_lossy().to_string());
+    let home = crate::detect::home_dir();
+    let interpreter = interpreter_root(program);
+    let mut readable: Vec<&Path> = vec![script_dir];
+    if let Some(root) = &interpreter {
+        readable.push(root);
+    }
+    platform_wrap(program, args, cwd, &tmp, &readable, home.as_deref())
+}
+
+#[cfg(target_os = "macos")]
+fn platform_wrap(
+    program: &str,
+    args: &[String],
+    cwd: &Path,
+    tmp: &Path,
+    readable: &[&Path],
+    home: Option<&Path>,
+) -> Result<Sandboxed, String> {
+    let sandbox_exec = find_on_path("sandbox-exec").ok_or("sandbox-exec not found; cannot confine the agent")?;
+    let proxy_port = crate::settings::get().proxy_port;
+    let profile = macos_profile(readable, &[cwd, tmp], home, proxy_port);
+    let mut wrapped = vec!["-p".to_string(), profile, program.to_string()];
+    wrapped.extend(args.iter().cloned());
+    Ok(Sandboxed {
+        program: sandbox_exec.to_string_lossy().to_string(),
+        args: wrapped,
+        confinement: format!("sandbox-exec (network: proxy port {} only; writes: working and temp dirs)", proxy_port),
+    })
+}
+
+/// bubblewrap: read-only root, home hidden behind a tmpfs, the working and
+/// temp dirs bound writable. A network namespace would also cut the agent off
+/// from the proxy on loopback, so the network is shared and only the proxy
+/// env vars steer it.
+#[cfg(target_os = "linux")]
+fn platform_wrap(
+    program: &str,
+    args: &[String],
+    cwd: &Path,
+    tmp: &Path,
+    readable: &[&Path],
+    home: Option<&Path>,
+) -> Result<Sandboxed, String> {
+    let bwrap = find_on_path("bwrap").ok_or("bubblewrap (bwrap) not found; install it to launch sandboxed agents")?;
+    let path = |p: &Path| p.to_string_lossy().to_string();
+    let mut wrapped: Vec<String> = ["--ro-bind", "/", "/", "--dev", "/dev", "--proc", "/proc", "--tmpfs", "/tmp"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    if let Some(home) = home {
+        wrapped.extend(["--tmpfs".to_string(), path(home)]);
+    }
+    for dir in readable {
+        wrapped.extend(["--ro-bind".to_string(), path(dir), path(dir)]);
+    }
+    for dir in [cwd, tmp] {
+        wrapped.extend(["--bind".to_string(), path(dir), path(dir)]);
+    }
+    wrapped.extend(
+        ["--unshare-all", "--share-net", "--die-with-parent", "--new-session", "--chdir"]
+            .iter()
+            .map(|s| s.to_string()),
+    );
+    wrapped.push(path(cwd));
+    wrapped.push(program.to_string());
+    wrapped.extend(args.iter().cloned());
+    Ok(Sandboxed {
+        program: bwrap.to_string_lossy().to_string(),
+        args: wrapped,
+        confinement: "bubblewrap (filesystem: working and temp dirs writable, home hidden; network not confined)".to_string(),
+    })
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn platform_wrap(
+    _program: &str,
+    _args: &[String],
+    _cwd: &Path,
+    _tmp: &Path,
+    _readable: &[&Path],
+    _home: Option<&Path>,
+) -> Result<Sandboxed, String> {
+    Err("Sandboxed launch is only supported on macOS and Linux".to_string())
+}
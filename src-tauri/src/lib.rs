@@ -1,13 +1,53 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod detect;
+mod detect_backup;
+mod detect_compose;
+mod detect_entropy;
+mod detect_git;
+mod detect_json;
+mod detect_new;
+mod detect_patterns;
+mod detect_platform;
+mod detect_providers;
+mod detect_rewrite;
+mod detect_structured;
+mod detect_walk;
 mod evidence;
+mod evidence_disk;
+mod gateway_config;
+mod gateway_parse;
+mod gateway_permissions;
+mod gateway_sessions;
+mod gateway_store;
 mod gateway_ws;
+mod headless;
+mod health_drift;
 mod launcher;
+mod launcher_output;
+mod launcher_registry;
+mod launcher_sandbox;
+mod logging;
 mod mcp_guard;
+mod metrics;
+mod networks;
+mod notify;
 mod openclaw_health;
 mod policy;
+mod policy_watch;
 mod proxy;
+mod proxy_coalesce;
+mod proxy_connect;
+mod proxy_cost;
+mod proxy_inject;
+mod proxy_redact;
+mod proxy_selftest;
+mod proxy_stream;
+mod proxy_tls;
+mod secret_usage;
+mod secure_launch;
+mod settings;
+mod vault_backup;
 mod vault_store;
 mod wallet;
 mod x402;
@@ -24,78 +64,157 @@ fn get_proxy_status() -> Result<bool, String> {
     Ok(proxy::is_running())
 }
 
+#[tauri::command]
+fn proxy_health() -> proxy::ProxyHealth {
+    proxy::health()
+}
+
 #[tauri::command]
 fn start_proxy() -> Result<(), String> {
     proxy::start().map_err(|e| e.to_string())
 }
 
+/// Async so the wait for in-flight requests to drain doesn't block the UI thread.
 #[tauri::command]
-fn stop_proxy() -> Result<(), String> {
-    proxy::stop().map_err(|e| e.to_string())
+async fn stop_proxy() -> Result<(), String> {
+    tokio::task::spawn_blocking(proxy::stop)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 fn set_secret(alias: String, value: String) -> Result<(), String> {
     let mut state = proxy::state().write().map_err(|_| "state lock")?;
-    state.vault.insert(alias, value);
+    state.vault.insert(alias, zeroize::Zeroizing::new(value));
     Ok(())
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env().add_directive("vault0_desktop=info".parse().unwrap()),
-        )
-        .init();
+    let headless_args = headless::parse_args(std::env::args());
+    logging::init(cfg!(debug_assertions) || headless_args.is_some());
+
+    if let Some(args) = headless_args {
+        let code = match args {
+            Ok(args) => headless::run(args),
+            Err(e) => {
+                eprintln!("vault0: {}", e);
+                2
+            }
+        };
+        std::process::exit(code);
+    }
 
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_pty::init())
+        .plugin(tauri_plugin_notification::init())
         .invoke_handler(tauri::generate_handler![
             greet,
             get_proxy_status,
+            proxy_health,
+            proxy_selftest::proxy_self_test,
+            proxy_cost::get_proxy_spend,
             start_proxy,
             stop_proxy,
             evidence::get_evidence_log,
+            evidence::get_evidence_log_filtered,
             evidence::get_evidence_stats,
+            evidence::get_evidence_by_domain,
             evidence::export_receipt,
+            evidence::export_signed_receipt,
+            evidence::evidence_export,
+            evidence::evidence_export_signed,
+            evidence::verify_evidence_chain,
+            secret_usage::secret_usage_report,
             policy::load_policy,
             policy::save_policy,
+            policy::policy_check,
+            policy::policy_grant_temp,
+            policy::policy_list_grants,
+            policy_watch::policy_watch,
             set_secret,
             x402::get_wallet_balance,
             x402::get_payment_history,
             x402::get_pending_402,
+            x402::estimate_settlement,
             launcher::launch_agent,
+            launcher_registry::list_agents,
+            launcher_registry::stop_agent,
             wallet::create_wallet,
             wallet::import_wallet,
             wallet::get_wallet_info,
             wallet::export_seed,
+            wallet::sign_x402_batch,
             detect::detect_openclaw,
+            detect::detect_all_openclaw,
+            detect_backup::list_backups,
+            detect_backup::restore_backup,
+            detect_backup::delete_backup,
+            detect_platform::detect_add_search_dir,
+            detect_platform::detect_remove_search_dir,
+            detect_platform::detect_list_search_dirs,
+            detect_patterns::detect_add_pattern,
+            detect_patterns::detect_remove_pattern,
+            detect_patterns::detect_list_patterns,
             detect::secure_config_keys,
+            detect_rewrite::unsecure_config_keys,
             detect::harden_openclaw,
-            detect::launch_secure_agent,
-            detect::scan_for_new_secrets,
+            secure_launch::launch_secure_agent,
+            detect_new::scan_for_new_secrets,
+            detect_providers::secure_provider_key,
+            detect_new::secret_watch,
+            mcp_guard::mcp_allow_add,
+            mcp_guard::mcp_allow_remove,
+            mcp_guard::mcp_allow_list,
+            mcp_guard::mcp_list_rules,
+            mcp_guard::mcp_set_token_scope,
+            mcp_guard::mcp_set_methods,
             openclaw_health::check_openclaw_readiness,
             openclaw_health::check_gateway_health,
             vault_store::vault_exists,
             vault_store::vault_create,
             vault_store::vault_unlock,
             vault_store::vault_lock,
+            vault_store::vault_set_auto_lock,
+            vault_store::vault_change_passphrase,
             vault_store::vault_is_unlocked,
             vault_store::vault_add_entry,
+            vault_store::vault_update_entry,
             vault_store::vault_list_entries,
+            vault_store::vault_list_entries_by_tag,
+            vault_store::vault_expiring_soon,
             vault_store::vault_get_secret,
             vault_store::vault_delete_entry,
             vault_store::vault_delete_file,
+            vault_backup::vault_export,
+            vault_backup::vault_import,
             gateway_ws::gateway_connect,
             gateway_ws::gateway_disconnect,
             gateway_ws::gateway_status,
             gateway_ws::get_gateway_events,
             gateway_ws::gateway_clear_events,
+            gateway_ws::gateway_set_filter,
+            gateway_permissions::get_pending_permissions,
+            gateway_permissions::gateway_respond_permission,
+            gateway_sessions::gateway_sessions,
+            settings::get_settings,
+            settings::update_settings,
+            settings::set_evidence_cap,
+            settings::set_gateway_event_cap,
+            logging::get_log_file_path,
         ])
-        .setup(|_app| {
+        .setup(|app| {
             info!("Vault-0 starting");
+            evidence::set_app_handle(app.handle().clone());
+            gateway_ws::set_app_handle(app.handle().clone());
+            detect_new::set_app_handle(app.handle().clone());
+            launcher_registry::set_app_handle(app.handle().clone());
+            notify::set_app_handle(app.handle().clone());
+            settings::apply(&settings::get());
+            evidence::load_tail();
+            vault_store::start_auto_lock();
             Ok(())
         })
         .run(tauri::generate_context!())
@@ -0,0 +1,74 @@
+//! Tracing setup: a daily-rotated log file under <config dir>/vault0/logs, plus
+//! stdout in debug builds and headless mode. The packaged app has no terminal,
+//! so the file is what users attach to bug reports.
+
+use once_cell::sync::OnceCell;
+use std::path::PathBuf;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{Builder, Rotation};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::EnvFilter;
+
+const LOG_PREFIX: &str = "vault0";
+const LOG_SUFFIX: &str = "log";
+/// Daily files kept before the oldest is deleted.
+const KEEP_FILES: usize = 14;
+
+/// Keeps the background writer alive; dropping it would lose buffered lines.
+static GUARD: OnceCell<WorkerGuard> = OnceCell::new();
+
+fn log_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|p| p.join("vault0").join("logs"))
+}
+
+fn filter() -> EnvFilter {
+    EnvFilter::from_default_env().add_directive("vault0_desktop=info".parse().unwrap())
+}
+
+/// Install the global subscriber. Falls back to stdout only if the log
+/// directory can't be created.
+pub fn init(stdout: bool) {
+    let appender = log_dir().ok_or_else(|| "config dir not found".to_string()).and_then(|dir| {
+        Builder::new()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(LOG_PREFIX)
+            .filename_suffix(LOG_SUFFIX)
+            .max_log_files(KEEP_FILES)
+            .build(&dir)
+            .map_err(|e| e.to_string())
+    });
+    let file_layer = match appender {
+        Ok(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let _ = GUARD.set(guard);
+            Some(tracing_subscriber::fmt::layer().with_writer(writer).with_ansi(false))
+        }
+        Err(e) => {
+            eprintln!("vault0: file logging disabled: {}", e);
+            None
+        }
+    };
+    let stdout_layer = (stdout || file_layer.is_none()).then(tracing_subscriber::fmt::layer);
+    tracing_subscriber::registry()
+        .with(filter())
+        .with(file_layer)
+        .with(stdout_layer)
+        .init();
+}
+
+/// Path of the log file currently being written (the newest in the log directory).
+#[tauri::command]
+pub fn get_log_file_path() -> Result<String, String> {
+    let dir = log_dir().ok_or("Config dir not found")?;
+    let newest = std::fs::read_dir(&dir)
+        .map_err(|e| format!("read {}: {}", dir.display(), e))?
+        .flatten()
+        .filter(|e| {
+            let name = e.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with(LOG_PREFIX) && name.ends_with(LOG_SUFFIX)
+        })
+        .max_by_key(|e| e.metadata().and_then(|m| m.modified()).ok())
+        .ok_or("No log file written yet")?;
+    Ok(newest.path().to_string_lossy().to_string())
+}
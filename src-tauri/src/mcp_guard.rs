@@ -1,14 +1,84 @@
 use once_cell::sync::Lazy;
-use std::collections::HashSet;
-use std::net::IpAddr;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::RwLock;
+use tracing::warn;
 
-static ALLOWED_ORIGINS: Lazy<HashSet<String>> = Lazy::new(|| {
-    let mut s = HashSet::new();
-    s.insert("localhost".to_string());
-    s.insert("127.0.0.1".to_string());
-    s
-});
+const ALLOWLIST_FILE: &str = "mcp_allowlist.json";
+const DEFAULT_ORIGINS: [&str; 2] = ["localhost", "127.0.0.1"];
+/// Most a request body is buffered to read its JSON-RPC method.
+pub const METHOD_PEEK_LIMIT: usize = 1024 * 1024;
+
+/// Per-origin settings for an allowlisted MCP server.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct McpOriginRule {
+    /// Vault alias whose secret is sent as this server's bearer token. The
+    /// client's own Authorization header is never passed through.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_alias: Option<String>,
+    /// JSON-RPC methods this origin may be called with (`tools/list`, or a
+    /// prefix such as `resources/*`). `None` allows every method.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub methods: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct McpOrigin {
+    pub origin: String,
+    #[serde(flatten)]
+    pub rule: McpOriginRule,
+}
+
+/// On-disk format: a plain origin list (older files) or origin -> rule.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredAllowlist {
+    List(Vec<String>),
+    Rules(BTreeMap<String, McpOriginRule>),
+}
+
+/// Allowlisted MCP origins (`host` or `host:port`) and their rules, persisted to
+/// <config dir>/vault0/mcp_allowlist.json. Starts as loopback only.
+static ALLOWED_ORIGINS: Lazy<RwLock<BTreeMap<String, McpOriginRule>>> = Lazy::new(|| RwLock::new(load_allowlist()));
+
+fn allowlist_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|p| p.join("vault0").join(ALLOWLIST_FILE))
+}
+
+fn load_allowlist() -> BTreeMap<String, McpOriginRule> {
+    let defaults = || DEFAULT_ORIGINS.iter().map(|s| (s.to_string(), McpOriginRule::default())).collect();
+    let Some(path) = allowlist_path() else { return defaults() };
+    let Ok(content) = fs::read_to_string(&path) else { return defaults() };
+    match serde_json::from_str::<StoredAllowlist>(&content) {
+        Ok(StoredAllowlist::List(list)) => list.into_iter().map(|o| (o, McpOriginRule::default())).collect(),
+        Ok(StoredAllowlist::Rules(rules)) => rules,
+        Err(e) => {
+            warn!("Ignoring invalid MCP allowlist {}: {}", path.display(), e);
+            defaults()
+        }
+    }
+}
+
+fn save_allowlist(origins: &BTreeMap<String, McpOriginRule>) -> Result<(), String> {
+    let path = allowlist_path().ok_or("Config dir not found")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("mkdir: {e}"))?;
+    }
+    let json = serde_json::to_string_pretty(origins).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("write MCP allowlist: {e}"))
+}
+
+/// Reduce `https://Host:8080/path` to `host:8080`.
+fn normalize_origin(origin: &str) -> String {
+    let lower = origin.trim().to_lowercase();
+    let rest = lower.split("://").nth(1).unwrap_or(&lower);
+    rest.split('/').next().unwrap_or(rest).to_string()
+}
 
 /// Heuristic: MCP servers often use paths like /mcp or run on known ports.
 pub fn is_mcp_request(host: &str, path: &str) -> bool {
@@ -17,28 +87,218 @@ pub fn is_mcp_request(host: &str, path: &str) -> bool {
 
 /// Allowlisted MCP server origins.
 pub fn allowed_origins() -> HashSet<String> {
-    ALLOWED_ORIGINS.clone()
+    ALLOWED_ORIGINS.read().map(|s| s.keys().cloned().collect()).unwrap_or_default()
+}
+
+/// Rule for `host`, matching `host:port` first and then the bare host.
+fn rule_for(host: &str) -> Option<McpOriginRule> {
+    let origins = ALLOWED_ORIGINS.read().ok()?;
+    let host_lower = host.to_lowercase();
+    let host_no_port = host_lower.split(':').next().unwrap_or(&host_lower);
+    origins.get(&host_lower).or_else(|| origins.get(host_no_port)).cloned()
 }
 
 /// Check if the given host is in the allowlist.
 pub fn origin_allowed(host: &str) -> bool {
-    let host_lower = host.to_lowercase();
-    if ALLOWED_ORIGINS.contains(&host_lower) {
-        return true;
+    rule_for(host).is_some()
+}
+
+/// Vault alias scoped to this MCP origin, if any. Origins without one get no
+/// token at all (secure default).
+pub fn scoped_token_alias(host: &str) -> Option<String> {
+    rule_for(host).and_then(|r| r.token_alias)
+}
+
+/// Method allowlist for this MCP origin, if one is set.
+pub fn method_allowlist(host: &str) -> Option<Vec<String>> {
+    rule_for(host).and_then(|r| r.methods)
+}
+
+fn method_allowed(allowed: &[String], method: &str) -> bool {
+    allowed.iter().any(|a| match a.strip_suffix('*') {
+        Some(prefix) => method.starts_with(prefix),
+        None => a == method,
+    })
+}
+
+/// Check every JSON-RPC `method` in a request body (single call or batch)
+/// against the allowlist. An empty body carries no call and passes; a body
+/// that isn't JSON-RPC is refused since its method can't be checked.
+pub fn check_methods(allowed: &[String], body: &[u8]) -> Result<(), String> {
+    if body.iter().all(|b| b.is_ascii_whitespace()) {
+        return Ok(());
     }
-    let host_no_port = host_lower.split(':').next().unwrap_or(&host_lower);
-    ALLOWED_ORIGINS.contains(host_no_port)
+    let value: serde_json::Value =
+        serde_json::from_slice(body).map_err(|_| "MCP request body is not JSON-RPC".to_string())?;
+    let calls = match &value {
+        serde_json::Value::Array(items) => items.iter().collect(),
+        _ => vec![&value],
+    };
+    for call in calls {
+        let method = call
+            .get("method")
+            .and_then(|m| m.as_str())
+            .ok_or("MCP request has no JSON-RPC method")?;
+        if !method_allowed(allowed, method) {
+            return Err(format!("MCP method not allowed: {}", method));
+        }
+    }
+    Ok(())
+}
+
+fn sorted(origins: &BTreeMap<String, McpOriginRule>) -> Vec<String> {
+    origins.keys().cloned().collect()
 }
 
-/// Returns true if token passthrough is disabled (secure default).
-pub fn token_passthrough_disabled() -> bool {
-    true
+#[tauri::command]
+pub fn mcp_allow_add(origin: String) -> Result<Vec<String>, String> {
+    let origin = normalize_origin(&origin);
+    if origin.is_empty() {
+        return Err("Origin must not be empty".into());
+    }
+    let mut origins = ALLOWED_ORIGINS.write().map_err(|_| "MCP allowlist lock")?;
+    if !origins.contains_key(&origin) {
+        origins.insert(origin.clone(), McpOriginRule::default());
+        save_allowlist(&origins)?;
+        crate::evidence::push("info", &format!("MCP origin allowlisted: {}", origin));
+    }
+    Ok(sorted(&origins))
 }
 
-/// Block private/internal IP ranges (SSRF mitigation).
+#[tauri::command]
+pub fn mcp_allow_remove(origin: String) -> Result<Vec<String>, String> {
+    let origin = normalize_origin(&origin);
+    let mut origins = ALLOWED_ORIGINS.write().map_err(|_| "MCP allowlist lock")?;
+    if origins.remove(&origin).is_some() {
+        save_allowlist(&origins)?;
+        crate::evidence::push("info", &format!("MCP origin removed from allowlist: {}", origin));
+    }
+    Ok(sorted(&origins))
+}
+
+#[tauri::command]
+pub fn mcp_allow_list() -> Result<Vec<String>, String> {
+    let origins = ALLOWED_ORIGINS.read().map_err(|_| "MCP allowlist lock")?;
+    Ok(sorted(&origins))
+}
+
+/// Allowlisted origins with their rules.
+#[tauri::command]
+pub fn mcp_list_rules() -> Result<Vec<McpOrigin>, String> {
+    let origins = ALLOWED_ORIGINS.read().map_err(|_| "MCP allowlist lock")?;
+    Ok(origins
+        .iter()
+        .map(|(origin, rule)| McpOrigin { origin: origin.clone(), rule: rule.clone() })
+        .collect())
+}
+
+/// Scope a vault alias to an allowlisted MCP origin (`None` clears it). The
+/// proxy then injects that secret, and only that secret, for the origin.
+#[tauri::command]
+pub fn mcp_set_token_scope(origin: String, alias: Option<String>) -> Result<Vec<McpOrigin>, String> {
+    let origin = normalize_origin(&origin);
+    let alias = alias.map(|a| a.trim().to_string()).filter(|a| !a.is_empty());
+    {
+        let mut origins = ALLOWED_ORIGINS.write().map_err(|_| "MCP allowlist lock")?;
+        let rule = origins
+            .get_mut(&origin)
+            .ok_or_else(|| format!("MCP origin not in allowlist: {}", origin))?;
+        rule.token_alias = alias.clone();
+        save_allowlist(&origins)?;
+    }
+    let msg = match &alias {
+        Some(a) => format!("MCP origin {} may receive vault token '{}'", origin, a),
+        None => format!("MCP origin {} token scope cleared", origin),
+    };
+    crate::evidence::push("info", &msg);
+    mcp_list_rules()
+}
+
+/// Restrict an allowlisted MCP origin to the given JSON-RPC methods (`None`
+/// lifts the restriction). Entries ending in `*` match by prefix.
+#[tauri::command]
+pub fn mcp_set_methods(origin: String, methods: Option<Vec<String>>) -> Result<Vec<McpOrigin>, String> {
+    let origin = normalize_origin(&origin);
+    let methods = methods.map(|m| {
+        m.into_iter()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+    });
+    {
+        let mut origins = ALLOWED_ORIGINS.write().map_err(|_| "MCP allowlist lock")?;
+        let rule = origins
+            .get_mut(&origin)
+            .ok_or_else(|| format!("MCP origin not in allowlist: {}", origin))?;
+        rule.methods = methods.clone();
+        save_allowlist(&origins)?;
+    }
+    let msg = match &methods {
+        Some(m) => format!("MCP origin {} limited to methods: {}", origin, m.join(", ")),
+        None => format!("MCP origin {} method restriction cleared", origin),
+    };
+    crate::evidence::push("info", &msg);
+    mcp_list_rules()
+}
+
+/// Host part of an authority: drops userinfo and port, unwraps `[v6]`.
+fn authority_host(authority: &str) -> &str {
+    let authority = authority.rsplit('@').next().unwrap_or(authority);
+    if let Some(rest) = authority.strip_prefix('[') {
+        return rest.split(']').next().unwrap_or(rest);
+    }
+    // A bare IPv6 literal has colons but no port.
+    if IpAddr::from_str(authority).is_ok() {
+        return authority;
+    }
+    authority.split(':').next().unwrap_or(authority)
+}
+
+/// One part of a loose IPv4 literal: `0x` hex, leading-zero octal, or decimal.
+fn parse_ipv4_part(part: &str) -> Option<u32> {
+    let (digits, radix) = match part.strip_prefix("0x").or_else(|| part.strip_prefix("0X")) {
+        Some(hex) => (hex, 16),
+        None if part.len() > 1 && part.starts_with('0') => (&part[1..], 8),
+        None => (part, 10),
+    };
+    // `from_str_radix` takes a leading `+`; inet_aton does not.
+    if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    if digits.is_empty() {
+        return (radix == 16).then_some(0);
+    }
+    u32::from_str_radix(digits, radix).ok()
+}
+
+/// IPv4 in the forms `inet_aton` (and so most HTTP clients) accepts but
+/// `Ipv4Addr::from_str` rejects: `2130706433`, `0177.0.0.1`, `0x7f.1`, `127.1`.
+/// The last part fills the remaining bytes.
+fn parse_ipv4_loose(host: &str) -> Option<std::net::Ipv4Addr> {
+    let parts: Vec<u32> = host.split('.').map(parse_ipv4_part).collect::<Option<_>>()?;
+    let (last, leading) = parts.split_last()?;
+    if leading.len() > 3 || leading.iter().any(|p| *p > 0xff) {
+        return None;
+    }
+    let remaining_bits = 8 * (4 - leading.len() as u32);
+    if remaining_bits < 32 && *last >> remaining_bits != 0 {
+        return None;
+    }
+    let value = leading.iter().fold(0u32, |acc, p| (acc << 8) | p);
+    let value = if leading.is_empty() { *last } else { (value << remaining_bits) | last };
+    Some(std::net::Ipv4Addr::from(value))
+}
+
+/// The host as an IP address, canonicalizing alternate IPv4 encodings.
+fn literal_ip(host: &str) -> Option<IpAddr> {
+    IpAddr::from_str(host).ok().or_else(|| parse_ipv4_loose(host).map(IpAddr::V4))
+}
+
+/// Block private/internal IP literals (SSRF mitigation). Hostnames pass here;
+/// `resolve_checked` covers them.
 pub fn would_be_ssrf(authority: &str) -> bool {
-    let host = authority.split(':').next().unwrap_or(authority);
-    if let Ok(ip) = IpAddr::from_str(host) {
+    let host = authority_host(authority);
+    if let Some(ip) = literal_ip(host) {
         return is_private_or_internal(ip);
     }
     if host.eq_ignore_ascii_case("localhost") || host == "127.0.0.1" {
@@ -47,6 +307,51 @@ pub fn would_be_ssrf(authority: &str) -> bool {
     false
 }
 
+/// Why `resolve_checked` refused a target, and the offending address if known.
+#[derive(Debug)]
+pub struct SsrfBlocked {
+    pub reason: String,
+    pub ip: Option<IpAddr>,
+}
+
+/// Resolve the target and run the private-range check over every address, so a
+/// public name pointing at an internal IP is caught. Returns the checked address
+/// for the caller to dial directly (no second lookup that a rebinding DNS server
+/// could answer differently), or `None` for IP literals and `localhost`, which
+/// need no pinning.
+pub async fn resolve_checked(authority: &str, default_port: u16) -> Result<Option<SocketAddr>, SsrfBlocked> {
+    let host = authority_host(authority);
+    if host.is_empty() || host.eq_ignore_ascii_case("localhost") {
+        return Ok(None);
+    }
+    if let Some(ip) = literal_ip(host) {
+        return if would_be_ssrf(authority) {
+            Err(SsrfBlocked { reason: format!("{} ({}) is a private/internal address", host, ip), ip: Some(ip) })
+        } else {
+            Ok(None)
+        };
+    }
+    let port = authority
+        .rsplit_once(':')
+        .and_then(|(_, p)| p.parse::<u16>().ok())
+        .unwrap_or(default_port);
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| SsrfBlocked { reason: format!("could not resolve {}: {}", host, e), ip: None })?
+        .collect();
+    if let Some(bad) = addrs.iter().find(|a| is_private_or_internal(a.ip())) {
+        return Err(SsrfBlocked {
+            reason: format!("{} resolves to private/internal address {}", host, bad.ip()),
+            ip: Some(bad.ip()),
+        });
+    }
+    addrs
+        .first()
+        .copied()
+        .map(Some)
+        .ok_or_else(|| SsrfBlocked { reason: format!("{} has no addresses", host), ip: None })
+}
+
 fn is_private_or_internal(ip: IpAddr) -> bool {
     match ip {
         IpAddr::V4(a) => {
@@ -54,8 +359,62 @@ fn is_private_or_internal(ip: IpAddr) -> bool {
                 || a.is_loopback()
                 || a.is_link_local()
                 || a.is_broadcast()
+                || a.is_unspecified()
                 || a.octets()[0] == 169
         }
-        IpAddr::V6(a) => a.is_loopback() || a.is_multicast(),
+        IpAddr::V6(a) => {
+            // ::ffff:a.b.c.d reaches the embedded IPv4 host, so judge it as v4.
+            if let Some(v4) = a.to_ipv4_mapped() {
+                return is_private_or_internal(IpAddr::V4(v4));
+            }
+            let first = a.segments()[0];
+            a.is_loopback()
+                || a.is_multicast()
+                || a.is_unspecified()
+                || (first & 0xfe00) == 0xfc00 // unique local fc00::/7
+                || (first & 0xffc0) == 0xfe80 // link local fe80::/10
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        IpAddr::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn unique_local_v6_is_internal() {
+        assert!(is_private_or_internal(ip("fd00::1")));
+    }
+
+    #[test]
+    fn v4_mapped_metadata_address_is_internal() {
+        assert!(is_private_or_internal(ip("::ffff:169.254.169.254")));
+        assert!(would_be_ssrf("[::ffff:169.254.169.254]:80"));
+    }
+
+    #[test]
+    fn loose_ipv4_forms_are_canonicalized() {
+        assert_eq!(parse_ipv4_loose("2130706433"), Some(std::net::Ipv4Addr::new(127, 0, 0, 1)));
+        assert_eq!(parse_ipv4_loose("012.0.0.1"), Some(std::net::Ipv4Addr::new(10, 0, 0, 1)));
+        assert!(would_be_ssrf("2130706433"));
+        assert!(would_be_ssrf("0300.0250.0.1:8080"));
+    }
+
+    #[test]
+    fn signed_ipv4_parts_are_rejected() {
+        assert_eq!(parse_ipv4_loose("0x+7f.0.0.1"), None);
+        assert_eq!(parse_ipv4_loose("0+12.0.0.1"), None);
+        assert_eq!(parse_ipv4_loose("+2130706433"), None);
+        assert_eq!(parse_ipv4_loose("127.0.0.-1"), None);
+    }
+
+    #[test]
+    fn public_addresses_pass() {
+        assert!(!is_private_or_internal(ip("2606:4700::1111")));
+        assert!(!is_private_or_internal(ip("::ffff:1.1.1.1")));
     }
 }
@@ -0,0 +1,161 @@
+//! Prometheus metrics for the proxy, fed from evidence entries as they are pushed.
+//! The HTTP endpoint is off unless `metrics_port` is set, and binds to loopback
+//! unless `metrics_bind` says otherwise.
+
+use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Mutex;
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+use crate::evidence::LogEntry;
+use crate::settings::Settings;
+
+/// Latency histogram upper bounds, in seconds.
+const LATENCY_BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+/// Distinct hosts tracked before the rest are folded into `other`.
+const MAX_HOSTS: usize = 200;
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum_secs: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, secs: f64) {
+        for (bound, slot) in LATENCY_BUCKETS.iter().zip(self.buckets.iter_mut()) {
+            if secs <= *bound {
+                *slot += 1;
+            }
+        }
+        self.count += 1;
+        self.sum_secs += secs;
+    }
+}
+
+#[derive(Default)]
+struct Metrics {
+    allowed: u64,
+    blocked: u64,
+    bytes: u64,
+    settlements: u64,
+    upstream_errors: u64,
+    latency: BTreeMap<String, Histogram>,
+}
+
+static METRICS: Lazy<Mutex<Metrics>> = Lazy::new(|| Mutex::new(Metrics::default()));
+/// Address the endpoint is serving on and the handle that shuts it down.
+static SERVER: Lazy<Mutex<Option<(SocketAddr, oneshot::Sender<()>)>>> = Lazy::new(|| Mutex::new(None));
+
+/// Update counters from an evidence entry. Called by `evidence::push_structured`.
+pub fn record(entry: &LogEntry) {
+    let Ok(mut m) = METRICS.lock() else { return };
+    let f = &entry.fields;
+    match entry.kind.as_str() {
+        "allowed" => m.allowed += 1,
+        "blocked" => m.blocked += 1,
+        // Pending 402s carry status 402; a settled retry carries the upstream's status.
+        "payment" if f.status.is_some_and(|s| s != 402) => {
+            m.allowed += 1;
+            m.settlements += 1;
+        }
+        "error" if f.check.as_deref() == Some("upstream") => m.upstream_errors += 1,
+        _ => return,
+    }
+    m.bytes += f.bytes.unwrap_or(0);
+    if let (Some(ms), Some(host)) = (f.latency_ms, &f.domain) {
+        let key = if m.latency.contains_key(host) || m.latency.len() < MAX_HOSTS {
+            host.clone()
+        } else {
+            "other".to_string()
+        };
+        m.latency.entry(key).or_default().observe(ms as f64 / 1000.0);
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn counter(out: &mut String, name: &str, help: &str, value: u64) {
+    let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} counter\n{name} {value}");
+}
+
+/// Current metrics in the Prometheus text exposition format.
+pub fn render() -> String {
+    let mut out = String::new();
+    let Ok(m) = METRICS.lock() else { return out };
+    counter(&mut out, "vault0_requests_allowed_total", "Requests forwarded upstream.", m.allowed);
+    counter(&mut out, "vault0_requests_blocked_total", "Requests refused by policy or the MCP guard.", m.blocked);
+    counter(&mut out, "vault0_proxied_bytes_total", "Response bytes returned through the proxy.", m.bytes);
+    counter(&mut out, "vault0_x402_settlements_total", "x402 payments settled automatically.", m.settlements);
+    counter(&mut out, "vault0_upstream_errors_total", "Requests that failed to reach the upstream.", m.upstream_errors);
+    let name = "vault0_upstream_latency_seconds";
+    let _ = writeln!(out, "# HELP {name} Upstream round-trip time per host.\n# TYPE {name} histogram");
+    for (host, h) in &m.latency {
+        let host = escape_label(host);
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(h.buckets.iter()) {
+            let _ = writeln!(out, "{name}_bucket{{host=\"{host}\",le=\"{bound}\"}} {count}");
+        }
+        let _ = writeln!(out, "{name}_bucket{{host=\"{host}\",le=\"+Inf\"}} {}", h.count);
+        let _ = writeln!(out, "{name}_sum{{host=\"{host}\"}} {}", h.sum_secs);
+        let _ = writeln!(out, "{name}_count{{host=\"{host}\"}} {}", h.count);
+    }
+    out
+}
+
+async fn metrics_handler() -> impl axum::response::IntoResponse {
+    ([(axum::http::header::CONTENT_TYPE, "text/plain; version=0.0.4")], render())
+}
+
+/// Start, move or stop the endpoint to match `settings`. Called from `settings::apply`.
+pub fn apply(settings: &Settings) {
+    let wanted = match settings.metrics_port {
+        Some(port) => match settings.metrics_bind.parse::<IpAddr>() {
+            Ok(ip) => Some(SocketAddr::new(ip, port)),
+            Err(_) => {
+                warn!("Invalid metrics_bind '{}'; metrics endpoint disabled", settings.metrics_bind);
+                None
+            }
+        },
+        None => None,
+    };
+    let Ok(mut server) = SERVER.lock() else { return };
+    if server.as_ref().map(|(addr, _)| *addr) == wanted {
+        return;
+    }
+    if let Some((addr, stop)) = server.take() {
+        let _ = stop.send(());
+        info!("Metrics endpoint on {} stopped", addr);
+    }
+    let Some(addr) = wanted else { return };
+    if !addr.ip().is_loopback() {
+        crate::evidence::push("warning", &format!("Metrics endpoint bound to non-loopback address {}", addr));
+    }
+    let (tx, rx) = oneshot::channel::<()>();
+    std::thread::spawn(move || {
+        let rt = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(rt) => rt,
+            Err(e) => return warn!("Metrics runtime: {}", e),
+        };
+        rt.block_on(async {
+            let app = axum::Router::new().route("/metrics", axum::routing::get(metrics_handler));
+            let listener = match tokio::net::TcpListener::bind(addr).await {
+                Ok(l) => l,
+                Err(e) => return warn!("Metrics endpoint could not bind {}: {}", addr, e),
+            };
+            info!("Metrics endpoint listening on http://{}/metrics", addr);
+            let shutdown = async {
+                let _ = rx.await;
+            };
+            if let Err(e) = axum::serve(listener, app).with_graceful_shutdown(shutdown).await {
+                warn!("Metrics endpoint failed: {}", e);
+            }
+        });
+    });
+    *server = Some((addr, tx));
+}
@@ -0,0 +1,44 @@
+//! Chains the wallet can pay on, and the USDC contract EIP-3009 signatures are
+//! bound to on each. Entries under `networks` in settings override or extend
+//! the built-in table.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    pub chain_id: u64,
+    /// USDC token contract, the EIP-712 `verifyingContract`.
+    pub usdc_address: String,
+    /// The token's EIP-712 domain name, which differs between deployments.
+    pub usdc_name: String,
+    #[serde(default = "default_usdc_version")]
+    pub usdc_version: String,
+}
+
+fn default_usdc_version() -> String {
+    "2".to_string()
+}
+
+fn builtin(name: &str) -> Option<NetworkConfig> {
+    let (chain_id, usdc_address, usdc_name) = match name {
+        "base" => (8453, "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913", "USD Coin"),
+        "base-sepolia" => (84532, "0x036CbD53842c5426634e7929541eC2318f3dCF7e", "USDC"),
+        _ => return None,
+    };
+    Some(NetworkConfig {
+        chain_id,
+        usdc_address: usdc_address.to_string(),
+        usdc_name: usdc_name.to_string(),
+        usdc_version: default_usdc_version(),
+    })
+}
+
+/// Configuration for `name`, preferring the settings table over the built-ins.
+pub fn lookup(name: &str) -> Result<NetworkConfig, String> {
+    crate::settings::get()
+        .networks
+        .get(name)
+        .cloned()
+        .or_else(|| builtin(name))
+        .ok_or_else(|| format!("Unknown network '{}' (add it to networks in settings)", name))
+}
@@ -0,0 +1,77 @@
+//! OS notifications for proxy blocks and new 402 payment requests.
+//! Each category can be switched off in settings, and repeats for the same
+//! domain or recipient are held back so a looping agent can't flood the desktop.
+
+use once_cell::sync::{Lazy, OnceCell};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+/// Minimum time between two notifications with the same category and key.
+const DEBOUNCE: Duration = Duration::from_secs(30);
+
+static APP: OnceCell<AppHandle> = OnceCell::new();
+/// (category, key) -> when it last notified and how many were held back since.
+static RECENT: Lazy<Mutex<HashMap<(&'static str, String), (Instant, u32)>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Called once from the Tauri setup hook; without it (headless) nothing is shown.
+pub fn set_app_handle(app: AppHandle) {
+    let _ = APP.set(app);
+}
+
+/// Whether to show now. Returns the number of notifications suppressed since
+/// the last one shown for this key.
+fn debounce(category: &'static str, key: &str) -> Option<u32> {
+    let mut recent = RECENT.lock().ok()?;
+    let now = Instant::now();
+    recent.retain(|_, (at, _)| now.duration_since(*at) < DEBOUNCE * 10);
+    match recent.get_mut(&(category, key.to_string())) {
+        Some((at, held)) if now.duration_since(*at) < DEBOUNCE => {
+            *held += 1;
+            None
+        }
+        Some((at, held)) => {
+            let suppressed = *held;
+            *at = now;
+            *held = 0;
+            Some(suppressed)
+        }
+        None => {
+            recent.insert((category, key.to_string()), (now, 0));
+            Some(0)
+        }
+    }
+}
+
+fn show(category: &'static str, key: &str, title: &str, body: String) {
+    let Some(app) = APP.get() else { return };
+    let Some(suppressed) = debounce(category, key) else { return };
+    let body = if suppressed > 0 { format!("{} (+{} more)", body, suppressed) } else { body };
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        tracing::warn!("Notification failed: {}", e);
+    }
+}
+
+/// A request was blocked. Called for every `blocked` evidence entry.
+pub fn blocked(domain: Option<&str>, msg: &str) {
+    if !crate::settings::get().notify_blocked {
+        return;
+    }
+    let key = domain.unwrap_or("");
+    let body = match domain {
+        Some(d) if !d.is_empty() => format!("{}: {}", d, msg),
+        _ => msg.to_string(),
+    };
+    show("blocked", key, "Vault-0 blocked a request", body);
+}
+
+/// A 402 payment request is waiting for approval. Called from `record_pending`.
+pub fn payment_pending(amount_cents: u64, recipient: &str) {
+    if !crate::settings::get().notify_payments {
+        return;
+    }
+    let body = format!("${:.2} requested by {}", amount_cents as f64 / 100.0, recipient);
+    show("payment", recipient, "Payment needs attention", body);
+}
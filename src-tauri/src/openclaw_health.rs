@@ -1,9 +1,12 @@
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use once_cell::sync::Lazy;
+use regex::Regex;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::Command;
-use std::time::Duration;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use tracing::info;
 
 #[derive(Debug, Serialize)]
@@ -37,15 +40,52 @@ fn resolve_install_path(path: Option<String>) -> Result<String, String> {
     Err("OpenClaw install path not found (tried ~/openclaw and ~/clawbot)".to_string())
 }
 
-fn run_status_command(install_path: &str) -> (bool, String, Vec<String>) {
+const STATUS_COMMAND: &str = "npx -y pnpm@10.23.0 run openclaw status";
+const STATUS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Login shell for the status command: `cmd` on Windows, otherwise `$SHELL`,
+/// then zsh, then `sh` (zsh isn't installed on most Linux systems).
+fn status_shell() -> (String, &'static str) {
+    if cfg!(windows) {
+        return ("cmd".to_string(), "/C");
+    }
+    let shell = std::env::var("SHELL")
+        .ok()
+        .filter(|s| Path::new(s).exists())
+        .or_else(|| Path::new("/bin/zsh").exists().then(|| "/bin/zsh".to_string()))
+        .unwrap_or_else(|| "/bin/sh".to_string());
+    (shell, "-lc")
+}
+
+async fn run_status_command(install_path: &str) -> (bool, String, Vec<String>) {
     let mut diagnostics = Vec::new();
-    let cmd = format!(
-        "cd \"{}\" && npx -y pnpm@10.23.0 run openclaw status",
-        install_path.replace('"', "\\\"")
-    );
-    diagnostics.push(format!("Running status command: {}", cmd));
+    let (shell, flag) = status_shell();
+    diagnostics.push(format!("Running status command: {} (in {}, via {})", STATUS_COMMAND, install_path, shell));
 
-    let output = Command::new("/bin/zsh").arg("-lc").arg(cmd).output();
+    let child = tokio::process::Command::new(&shell)
+        .arg(flag)
+        .arg(STATUS_COMMAND)
+        .current_dir(install_path)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn();
+    let child = match child {
+        Ok(c) => c,
+        Err(e) => {
+            diagnostics.push(format!("Status command execution error: {}", e));
+            return (false, String::new(), diagnostics);
+        }
+    };
+
+    // On timeout the wait future is dropped, which kills the child.
+    let output = match tokio::time::timeout(STATUS_TIMEOUT, child.wait_with_output()).await {
+        Ok(out) => out,
+        Err(_) => {
+            diagnostics.push(format!("Status command timed out after {}s", STATUS_TIMEOUT.as_secs()));
+            return (false, String::new(), diagnostics);
+        }
+    };
     match output {
         Ok(out) => {
             let stdout = String::from_utf8_lossy(&out.stdout).to_string();
@@ -65,6 +105,12 @@ fn run_status_command(install_path: &str) -> (bool, String, Vec<String>) {
                 "connected",
             ];
             let marker_match = healthy_markers.iter().any(|m| lower.contains(m));
+            if let Some(version) = parse_version(&combined) {
+                diagnostics.push(format!("Detected OpenClaw version {}", version));
+                if let Ok(mut v) = STATUS_VERSION.lock() {
+                    *v = Some(version);
+                }
+            }
             let ok = out.status.success() && marker_match;
             diagnostics.push(format!(
                 "Status command exit: {} marker_match:{}",
@@ -80,19 +126,37 @@ fn run_status_command(install_path: &str) -> (bool, String, Vec<String>) {
     }
 }
 
+/// Ports and paths probed when looking for a running OpenClaw HTTP endpoint.
+const DEFAULT_PROBE_PORTS: [u16; 3] = [3000, 8787, 8080];
+const DEFAULT_PROBE_PATHS: [&str; 2] = ["/health", "/status"];
+const GATEWAY_PROBE_PATHS: [&str; 2] = ["/__openclaw__/canvas/", "/health"];
+const PROBE_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// Probe URLs in order: the gateway port from openclaw.json, the built-in ports,
+/// then user-configured targets from settings. Duplicates are dropped.
+fn probe_targets() -> Vec<String> {
+    let (gateway_port, _, _) = crate::gateway_config::read_gateway_config();
+    let mut ports = vec![gateway_port];
+    ports.extend(DEFAULT_PROBE_PORTS.iter().filter(|p| **p != gateway_port));
+    let mut targets: Vec<String> = ports
+        .iter()
+        .flat_map(|port| DEFAULT_PROBE_PATHS.iter().map(move |path| format!("http://127.0.0.1:{}{}", port, path)))
+        .collect();
+    for extra in crate::settings::get().health_probe_targets {
+        let extra = extra.trim().to_string();
+        if (extra.starts_with("http://") || extra.starts_with("https://")) && !targets.contains(&extra) {
+            targets.push(extra);
+        }
+    }
+    targets
+}
+
 async fn run_http_probe() -> (bool, String, u16, Vec<String>) {
     let mut diagnostics = Vec::new();
-    let candidates = [
-        "http://127.0.0.1:3000/health",
-        "http://127.0.0.1:3000/status",
-        "http://127.0.0.1:8787/health",
-        "http://127.0.0.1:8787/status",
-        "http://127.0.0.1:8080/health",
-        "http://127.0.0.1:8080/status",
-    ];
+    let candidates = probe_targets();
 
     let client = Client::builder()
-        .timeout(Duration::from_secs(2))
+        .timeout(Duration::from_secs(crate::settings::get().health_probe_timeout_secs))
         .build()
         .map_err(|e| e.to_string());
     let client = match client {
@@ -103,32 +167,73 @@ async fn run_http_probe() -> (bool, String, u16, Vec<String>) {
         }
     };
 
-    for url in candidates {
-        diagnostics.push(format!("HTTP probe: {}", url));
-        match client.get(url).send().await {
-            Ok(resp) => {
-                let code = resp.status().as_u16();
-                if resp.status().is_success() {
-                    return (true, url.to_string(), code, diagnostics);
-                }
-                diagnostics.push(format!("HTTP non-success {} at {}", code, url));
+    // All targets are probed concurrently; the first success wins and the rest are dropped.
+    let mut probes: FuturesUnordered<_> = candidates
+        .into_iter()
+        .map(|url| {
+            let client = client.clone();
+            async move {
+                let result = probe_once(&client, &url).await;
+                (url, result)
             }
+        })
+        .collect();
+    while let Some((url, result)) = probes.next().await {
+        diagnostics.push(format!("HTTP probe: {}", url));
+        match result {
+            Ok(code) if (200..300).contains(&code) => return (true, url, code, diagnostics),
+            Ok(code) => diagnostics.push(format!("HTTP non-success {} at {}", code, url)),
             Err(e) => diagnostics.push(format!("HTTP error at {}: {}", url, e)),
         }
     }
     (false, String::new(), 0, diagnostics)
 }
 
-#[derive(Debug, Serialize)]
+/// One GET with a single retry for transient failures (connection reset or
+/// refused mid-restart). Timeouts are not retried.
+async fn probe_once(client: &Client, url: &str) -> Result<u16, String> {
+    match client.get(url).send().await {
+        Ok(resp) => Ok(resp.status().as_u16()),
+        Err(e) if e.is_timeout() => Err(e.to_string()),
+        Err(_) => {
+            tokio::time::sleep(PROBE_RETRY_DELAY).await;
+            client
+                .get(url)
+                .send()
+                .await
+                .map(|r| r.status().as_u16())
+                .map_err(|e| format!("{} (after retry)", e))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct GatewayHealth {
+    /// The HTTP probe answered or the WebSocket session is live.
     pub running: bool,
+    /// Which signal confirmed (or failed to confirm) the gateway is up.
+    pub liveness_note: String,
     pub port: u16,
     pub model: String,
     pub auth_mode: String,
     pub bind: String,
     pub config_secured: bool,
     pub unsecured_keys: Vec<String>,
+    /// True when keys secured by the last hardening are back in plaintext.
+    pub config_drift: bool,
+    pub drifted_keys: Vec<String>,
     pub config_path: String,
+    /// From the gateway's `/version` endpoint, else the last status command output.
+    pub version: Option<String>,
+    pub agents: Vec<AgentInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentInfo {
+    pub id: String,
+    pub name: Option<String>,
+    pub model: Option<String>,
+    pub default: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -161,6 +266,21 @@ struct AuthSection {
 struct AgentsSection {
     #[serde(default)]
     defaults: Option<AgentDefaults>,
+    #[serde(default)]
+    list: Vec<AgentEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AgentEntry {
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    /// Either a model id or `{ "primary": ... }`.
+    #[serde(default)]
+    model: Option<serde_json::Value>,
+    #[serde(default)]
+    default: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -175,7 +295,57 @@ struct ModelSection {
     primary: Option<String>,
 }
 
-fn default_port() -> u16 { 18789 }
+fn default_port() -> u16 { crate::gateway_config::default_port() }
+
+fn agent_list(config: &OpenClawConfig) -> Vec<AgentInfo> {
+    let Some(agents) = config.agents.as_ref() else { return Vec::new() };
+    agents
+        .list
+        .iter()
+        .enumerate()
+        .map(|(i, a)| AgentInfo {
+            id: a.id.clone().or_else(|| a.name.clone()).unwrap_or_else(|| format!("agent-{}", i + 1)),
+            name: a.name.clone(),
+            model: match &a.model {
+                Some(serde_json::Value::String(m)) => Some(m.clone()),
+                Some(v) => v.get("primary").and_then(|p| p.as_str()).map(String::from),
+                None => None,
+            },
+            default: a.default,
+        })
+        .collect()
+}
+
+static VERSION_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\bv?(\d+\.\d+\.\d+(?:-[0-9A-Za-z.]+)?)\b").unwrap());
+
+/// Version reported by the last successful status command run.
+static STATUS_VERSION: Mutex<Option<String>> = Mutex::new(None);
+
+/// First version-looking token, preferring lines that mention openclaw or a version.
+fn parse_version(text: &str) -> Option<String> {
+    let first = |line: &str| VERSION_RE.captures(line).map(|c| c[1].to_string());
+    text.lines()
+        .filter(|l| {
+            let lower = l.to_lowercase();
+            lower.contains("openclaw") || lower.contains("version")
+        })
+        .find_map(first)
+        .or_else(|| first(text))
+}
+
+/// `GET /version` on the gateway; accepts `{"version": ...}` or a plain-text body.
+async fn fetch_gateway_version(client: &Client, port: u16) -> Option<String> {
+    let resp = client.get(format!("http://127.0.0.1:{}/version", port)).send().await.ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    let body = resp.text().await.ok()?;
+    match serde_json::from_str::<serde_json::Value>(&body) {
+        Ok(v) => v.get("version").and_then(|v| v.as_str()).map(String::from),
+        Err(_) => parse_version(&body),
+    }
+}
 
 fn openclaw_config_path() -> Option<PathBuf> {
     let home = dirs::home_dir()?;
@@ -186,19 +356,8 @@ fn openclaw_config_path() -> Option<PathBuf> {
 fn parse_openclaw_config(path: &Path) -> Result<OpenClawConfig, String> {
     let content = fs::read_to_string(path).map_err(|e| format!("read config: {e}"))?;
     // openclaw.json uses JSON5 (comments, trailing commas) so we parse leniently
-    serde_json::from_str::<OpenClawConfig>(&content)
-        .or_else(|_| {
-            // Strip comments for basic JSON5 compat
-            let stripped: String = content.lines()
-                .map(|l| {
-                    let t = l.trim();
-                    if t.starts_with("//") { "" } else { l }
-                })
-                .collect::<Vec<&str>>()
-                .join("\n");
-            serde_json::from_str::<OpenClawConfig>(&stripped)
-        })
-        .map_err(|e| format!("parse config: {e}"))
+    let value = crate::detect_json::parse_lenient(&content).ok_or("parse config: not valid JSON5")?;
+    serde_json::from_value::<OpenClawConfig>(value).map_err(|e| format!("parse config: {e}"))
 }
 
 fn check_config_for_plaintext(path: &Path) -> (bool, Vec<String>) {
@@ -224,17 +383,37 @@ fn check_config_for_plaintext(path: &Path) -> (bool, Vec<String>) {
     (secured, unsecured)
 }
 
+/// How long a health result is reused; the dashboard polls every few seconds.
+const HEALTH_TTL: Duration = Duration::from_secs(5);
+
+static HEALTH_CACHE: Mutex<Option<(PathBuf, Instant, GatewayHealth)>> = Mutex::new(None);
+
+fn cached_health(config_path: &Path) -> Option<GatewayHealth> {
+    let cache = HEALTH_CACHE.lock().ok()?;
+    match cache.as_ref() {
+        Some((path, at, health)) if path == config_path && at.elapsed() < HEALTH_TTL => Some(health.clone()),
+        _ => None,
+    }
+}
+
+/// Gateway health, cached for a few seconds per config path. Pass `force: true`
+/// to bypass the cache (manual refresh).
 #[tauri::command]
-pub async fn check_gateway_health() -> Result<GatewayHealth, String> {
+pub async fn check_gateway_health(force: Option<bool>) -> Result<GatewayHealth, String> {
     let config_path = openclaw_config_path()
         .ok_or("OpenClaw config not found at ~/.openclaw/openclaw.json")?;
+    if !force.unwrap_or(false) {
+        if let Some(health) = cached_health(&config_path) {
+            return Ok(health);
+        }
+    }
 
     let config = parse_openclaw_config(&config_path).unwrap_or(OpenClawConfig {
         gateway: None,
         agents: None,
     });
 
-    let port = config.gateway.as_ref().map(|g| g.port).unwrap_or(18789);
+    let port = config.gateway.as_ref().map(|g| g.port).unwrap_or_else(default_port);
     let bind = config.gateway.as_ref().and_then(|g| g.bind.clone()).unwrap_or("loopback".into());
     let auth_mode = config.gateway.as_ref().and_then(|g| g.auth.as_ref()).and_then(|a| a.mode.clone()).unwrap_or("none".into());
     let model = config.agents.as_ref()
@@ -244,30 +423,62 @@ pub async fn check_gateway_health() -> Result<GatewayHealth, String> {
         .unwrap_or("unknown".into());
 
     let (config_secured, unsecured_keys) = check_config_for_plaintext(&config_path);
+    let drifted_keys = crate::health_drift::drifted_keys();
+    if !drifted_keys.is_empty() {
+        tracing::warn!("Config drift: previously secured keys back in plaintext: {}", drifted_keys.join(", "));
+    }
 
-    // Probe gateway
-    let running = {
-        let url = format!("http://127.0.0.1:{}/__openclaw__/canvas/", port);
-        let client = Client::builder().timeout(Duration::from_secs(2)).build().ok();
-        if let Some(c) = client {
-            c.get(&url).send().await.map(|r| r.status().is_success() || r.status().as_u16() == 426).unwrap_or(false)
-        } else {
-            false
+    // Probe gateway on its configured port; 426 means the WS endpoint answered.
+    let probe_timeout = Duration::from_secs(crate::settings::get().health_probe_timeout_secs);
+    let client = Client::builder().timeout(probe_timeout).build().ok();
+    let mut http_ok = false;
+    if let Some(c) = &client {
+        for path in GATEWAY_PROBE_PATHS {
+            let url = format!("http://127.0.0.1:{}{}", port, path);
+            if c.get(&url).send().await.map(|r| r.status().is_success() || r.status().as_u16() == 426).unwrap_or(false) {
+                http_ok = true;
+                break;
+            }
         }
+    }
+    // The WebSocket is the gateway's real interface: a live session means we
+    // authenticated and are still receiving frames.
+    let ws_age = crate::gateway_ws::last_frame_age().filter(|age| *age <= crate::gateway_ws::LIVE_WINDOW_SECS);
+    let running = http_ok || ws_age.is_some();
+    let liveness_note = match (ws_age, http_ok) {
+        (Some(age), true) => format!("WebSocket session live (last frame {}s ago); HTTP probe answered", age),
+        (Some(age), false) => format!("WebSocket session live (last frame {}s ago); HTTP probe got no answer", age),
+        (None, true) => "HTTP probe answered; no live WebSocket session".to_string(),
+        (None, false) => "No HTTP answer and no live WebSocket session".to_string(),
     };
+    let version = match (&client, http_ok) {
+        (Some(c), true) => fetch_gateway_version(c, port).await,
+        _ => None,
+    }
+    .or_else(|| STATUS_VERSION.lock().ok().and_then(|v| v.clone()));
+    let agents = agent_list(&config);
 
     info!("Gateway health: running={}, port={}, model={}, secured={}", running, port, model, config_secured);
 
-    Ok(GatewayHealth {
+    let health = GatewayHealth {
         running,
+        liveness_note,
         port,
         model,
         auth_mode,
         bind,
         config_secured,
         unsecured_keys,
+        config_drift: !drifted_keys.is_empty(),
+        drifted_keys,
         config_path: config_path.to_string_lossy().to_string(),
-    })
+        version,
+        agents,
+    };
+    if let Ok(mut cache) = HEALTH_CACHE.lock() {
+        *cache = Some((config_path, Instant::now(), health.clone()));
+    }
+    Ok(health)
 }
 
 #[tauri::command]
@@ -275,7 +486,7 @@ pub async fn check_openclaw_readiness(path: Option<String>) -> Result<ReadinessP
     let install_path = resolve_install_path(path)?;
     info!("Readiness check for OpenClaw at {}", install_path);
 
-    let (status_ok, status_output, mut diagnostics) = run_status_command(&install_path);
+    let (status_ok, status_output, mut diagnostics) = run_status_command(&install_path).await;
     if status_ok {
         diagnostics.push("Readiness source: status command".to_string());
         return Ok(ReadinessProbeResult {
@@ -1,8 +1,10 @@
+use chrono::{DateTime, Datelike, Local, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
 
-use crate::proxy;
+use crate::{evidence, proxy};
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Policy {
@@ -10,8 +12,113 @@ pub struct Policy {
     pub block_domains: Vec<String>,
     pub spend_cap_cents: Option<u64>,
     pub output_redact_patterns: Vec<String>,
+    /// Scrubbed from outgoing request bodies before they leave the machine.
+    #[serde(default)]
+    pub input_redact_patterns: Vec<String>,
     #[serde(default)]
     pub auto_settle_402: bool,
+    #[serde(default)]
+    pub block_methods: Vec<String>,
+    #[serde(default)]
+    pub method_rules: Vec<MethodRule>,
+    #[serde(default)]
+    pub path_rules: Vec<PathRule>,
+    #[serde(default)]
+    pub allow_windows: Vec<AccessWindow>,
+    #[serde(default)]
+    pub agent_env_rules: Vec<AgentEnvRule>,
+    #[serde(default)]
+    pub client_certs: Vec<ClientCertRule>,
+    /// Let identical concurrent GET/HEAD requests share one upstream response.
+    #[serde(default)]
+    pub coalesce_get: bool,
+    /// Which vault alias the proxy injects for which host, ahead of the built-in
+    /// OpenAI and Anthropic mappings.
+    #[serde(default)]
+    pub inject_rules: Vec<InjectRule>,
+    /// Honour an agent's `X-Vault0-Alias` header. Even then, only an alias an
+    /// inject rule or built-in mapping assigns to the request's host is used.
+    #[serde(default)]
+    pub allow_alias_header: bool,
+    /// Ports `CONNECT` tunnels may reach besides 443.
+    #[serde(default)]
+    pub connect_ports: Vec<u16>,
+}
+
+/// Inject vault `alias` into requests to hosts matching `host` (see
+/// [`host_matches`]). The key goes in `header` (default `Authorization`),
+/// prefixed by `scheme` and a space; the scheme defaults to `Bearer` for
+/// `Authorization` and to none for any other header. The first matching rule wins.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct InjectRule {
+    pub host: String,
+    pub alias: String,
+    #[serde(default)]
+    pub header: Option<String>,
+    #[serde(default)]
+    pub scheme: Option<String>,
+}
+
+/// Mutual TLS for `domain`: the proxy presents the identity stored under vault
+/// `alias` (PEM certificate chain followed by a PKCS#8 private key).
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ClientCertRule {
+    pub domain: String,
+    pub alias: String,
+}
+
+/// Vault aliases a launched script may receive as environment variables.
+/// `script` matches whole trailing components of the script path, so a bare
+/// file name works; an absolute `script` must match the full path.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct AgentEnvRule {
+    pub script: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+/// Aliases `script` may receive, or `None` when the policy sets no agent env
+/// rules at all. A script no rule matches gets an empty list.
+pub fn allowed_env_aliases(policy: &Policy, script: &str) -> Option<Vec<String>> {
+    if policy.agent_env_rules.is_empty() {
+        return None;
+    }
+    let script = script.replace('\\', "/");
+    Some(
+        policy
+            .agent_env_rules
+            .iter()
+            .filter(|r| script_matches(&script, &r.script))
+            .flat_map(|r| r.aliases.iter().cloned())
+            .collect(),
+    )
+}
+
+/// `rule` names `script` on a `/` component boundary, so `run.py` does not
+/// match `/tmp/evil_run.py`.
+fn script_matches(script: &str, rule: &str) -> bool {
+    let rule = rule.trim().replace('\\', "/");
+    if rule.is_empty() {
+        return false;
+    }
+    if script == rule {
+        return true;
+    }
+    if rule.starts_with('/') || Path::new(&rule).is_absolute() {
+        return false;
+    }
+    script.ends_with(&format!("/{}", rule))
+}
+
+/// Per-domain HTTP method rule. An empty `allow_methods` permits every method
+/// not listed in `block_methods`.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct MethodRule {
+    pub domain: String,
+    #[serde(default)]
+    pub allow_methods: Vec<String>,
+    #[serde(default)]
+    pub block_methods: Vec<String>,
 }
 
 #[tauri::command]
@@ -21,8 +128,7 @@ pub fn load_policy(path: Option<String>) -> Result<Policy, String> {
     if path.is_empty() || !Path::new(path).exists() {
         return Ok(Policy::default());
     }
-    let s = fs::read_to_string(path).map_err(|e| e.to_string())?;
-    let policy: Policy = serde_yaml::from_str(&s).map_err(|e| e.to_string())?;
+    let policy = read_policy_file(Path::new(path))?;
     {
         let mut state = proxy::state().write().map_err(|_| "state lock")?;
         state.policy = policy.clone();
@@ -39,7 +145,11 @@ pub fn save_policy(path: Option<String>, policy: Policy) -> Result<(), String> {
         state.policy = policy;
         return Ok(());
     }
-    let s = serde_yaml::to_string(&policy).map_err(|e| e.to_string())?;
+    let s = if is_json_path(Path::new(path)) {
+        serde_json::to_string_pretty(&policy).map_err(|e| e.to_string())?
+    } else {
+        serde_yaml::to_string(&policy).map_err(|e| e.to_string())?
+    };
     fs::write(path, s).map_err(|e| e.to_string())?;
     let mut state = proxy::state().write().map_err(|_| "state lock")?;
     state.policy = policy;
@@ -63,10 +173,363 @@ pub fn default_hardened_policy() -> Policy {
             "Bearer [a-zA-Z0-9._-]+".into(),
         ],
         auto_settle_402: false,
+        ..Policy::default()
+    }
+}
+
+/// Whether `host` falls under a domain `pattern`: the domain itself or any
+/// subdomain of it, so `openai.com` covers `api.openai.com` but not
+/// `notopenai.com`. `*.openai.com` covers subdomains only, and `*` every host.
+/// Case-insensitive; a trailing dot on either side is ignored.
+pub fn host_matches(host: &str, pattern: &str) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    let pattern = pattern.trim().trim_end_matches('.').to_ascii_lowercase();
+    if pattern == "*" {
+        return true;
     }
+    let is_subdomain = |suffix: &str| !suffix.is_empty() && host.ends_with(&format!(".{suffix}"));
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => is_subdomain(suffix),
+        None => !pattern.is_empty() && (host == pattern || is_subdomain(&pattern)),
+    }
+}
+
+/// Method rule denying `method` for `host`, described for evidence, if any.
+/// Methods are compared case-insensitively.
+fn method_rule_denying(policy: &Policy, host: &str, method: &str) -> Option<String> {
+    let listed = |list: &[String]| list.iter().any(|m| m.eq_ignore_ascii_case(method));
+    if listed(&policy.block_methods) {
+        return Some(format!("block_methods: {}", method.to_uppercase()));
+    }
+    policy
+        .method_rules
+        .iter()
+        .filter(|r| host_matches(host, &r.domain))
+        .find(|r| listed(&r.block_methods) || (!r.allow_methods.is_empty() && !listed(&r.allow_methods)))
+        .map(|r| format!("method_rules: {}", r.domain))
+}
+
+/// Path-prefix rule for a domain. When several rules match a request,
+/// the one with the longest prefix wins.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathRule {
+    pub domain: String,
+    pub prefix: String,
+    pub action: RuleAction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    Allow,
+    Block,
+}
+
+/// Most specific path rule matching `host` and `path`, if any. The path is
+/// normalized first, so `/v1/./x`, `/v1//x` and `/v1/%78` all match `/v1/x`.
+pub fn match_path_rule<'a>(policy: &'a Policy, host: &str, path: &str) -> Option<&'a PathRule> {
+    let path = normalize_path(path);
+    policy
+        .path_rules
+        .iter()
+        .filter(|r| host_matches(host, &r.domain) && path.starts_with(r.prefix.as_str()))
+        .max_by_key(|r| r.prefix.len())
+}
+
+/// `path` as the upstream will read it: percent-encoded unreserved characters
+/// decoded, empty and `.` segments dropped, and `..` resolved. A trailing slash
+/// is kept.
+pub fn normalize_path(path: &str) -> String {
+    let decoded = decode_unreserved(path);
+    let raw: Vec<&str> = decoded.split('/').collect();
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in &raw {
+        match *segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            s => segments.push(s),
+        }
+    }
+    let mut out = format!("/{}", segments.join("/"));
+    if !segments.is_empty() && matches!(raw.last(), Some(&"" | &"." | &"..")) {
+        out.push('/');
+    }
+    out
+}
+
+/// Decode `%XX` escapes of unreserved characters (RFC 3986: letters, digits,
+/// `-`, `.`, `_`, `~`); other escapes, such as `%2F`, are left as they are.
+fn decode_unreserved(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    let mut rest = path;
+    while let Some(ch) = rest.chars().next() {
+        let unescaped = (ch == '%')
+            .then(|| rest.get(1..3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .filter(|b| b.is_ascii_alphanumeric() || b"-._~".contains(b));
+        match unescaped {
+            Some(b) => {
+                out.push(b as char);
+                rest = &rest[3..];
+            }
+            None => {
+                out.push(ch);
+                rest = &rest[ch.len_utf8()..];
+            }
+        }
+    }
+    out
 }
 
-fn default_policy_path() -> String {
+/// Outcome of running a request through the policy. `status` is the HTTP status
+/// the proxy answers with when the request is denied.
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyDecision {
+    pub allowed: bool,
+    pub matched_rule: Option<String>,
+    pub reason: String,
+    pub status: u16,
+}
+
+impl PolicyDecision {
+    pub(crate) fn allow(matched_rule: Option<String>) -> Self {
+        PolicyDecision { allowed: true, matched_rule, reason: "allowed".into(), status: 200 }
+    }
+
+    fn deny(status: u16, matched_rule: Option<String>, reason: String) -> Self {
+        PolicyDecision { allowed: false, matched_rule, reason, status }
+    }
+}
+
+/// Domain, time-window, path and method checks in the order `proxy_handler` enforces them.
+/// A live temporary grant for the host lifts the allow list; `block_domains` still applies.
+pub fn evaluate(
+    policy: &Policy,
+    grants: &[TempGrant],
+    host: &str,
+    path: &str,
+    method: &str,
+    now: DateTime<Utc>,
+) -> PolicyDecision {
+    let now_secs = now.timestamp().max(0) as u64;
+    if let Some(d) = policy.block_domains.iter().find(|d| host_matches(host, d)) {
+        return PolicyDecision::deny(403, Some(format!("block_domains: {d}")), "domain blocked by policy".into());
+    }
+    let grant = grants
+        .iter()
+        .find(|g| g.expires_at > now_secs && host_matches(host, &g.domain))
+        .map(|g| format!("temp_grant: {}", g.domain));
+    let allow_match = policy.allow_domains.iter().find(|d| host_matches(host, d));
+    if grant.is_none() && !policy.allow_domains.is_empty() && allow_match.is_none() {
+        return PolicyDecision::deny(403, None, "domain not in allow list".into());
+    }
+    if !within_allowed_window(policy, now) {
+        return PolicyDecision::deny(403, Some("allow_windows".into()), "outside allowed time window".into());
+    }
+    let path_rule = match_path_rule(policy, host, path);
+    let path_rule_name = path_rule.map(|r| format!("path_rules: {}{}", r.domain, r.prefix));
+    if path_rule.map(|r| r.action) == Some(RuleAction::Block) {
+        return PolicyDecision::deny(403, path_rule_name, format!("path {path} blocked by rule"));
+    }
+    if let Some(rule) = method_rule_denying(policy, host, method) {
+        return PolicyDecision::deny(405, Some(rule), format!("method {} blocked by policy", method.to_uppercase()));
+    }
+    PolicyDecision::allow(
+        path_rule_name
+            .or(grant)
+            .or_else(|| allow_match.map(|d| format!("allow_domains: {d}"))),
+    )
+}
+
+/// `evaluate` against the live policy and grants, dropping lapsed grants first.
+pub fn evaluate_request(host: &str, path: &str, method: &str) -> Result<PolicyDecision, String> {
+    prune_expired_grants();
+    let state = proxy::state().read().map_err(|_| "state lock")?;
+    Ok(evaluate(&state.policy, &state.grants, host, path, method, Utc::now()))
+}
+
+/// Dry-run `method url` against the active policy without making a network call.
+#[tauri::command]
+pub fn policy_check(url: String, method: String) -> Result<PolicyDecision, String> {
+    let parsed = reqwest::Url::parse(&url).map_err(|e| format!("invalid url: {e}"))?;
+    evaluate_request(parsed.host_str().unwrap_or(""), parsed.path(), &method)
+}
+
+/// Time-limited allow override for a single domain, granted from a UI prompt.
+#[derive(Debug, Clone, Serialize)]
+pub struct TempGrant {
+    pub domain: String,
+    pub granted_at: u64,
+    pub expires_at: u64,
+}
+
+#[tauri::command]
+pub fn policy_grant_temp(domain: String, ttl_secs: u64) -> Result<TempGrant, String> {
+    let domain = domain.trim().to_lowercase();
+    if domain.is_empty() {
+        return Err("Domain is required".into());
+    }
+    if ttl_secs == 0 {
+        return Err("ttl_secs must be greater than zero".into());
+    }
+    let now = Utc::now().timestamp().max(0) as u64;
+    let grant = TempGrant {
+        domain: domain.clone(),
+        granted_at: now,
+        expires_at: now.saturating_add(ttl_secs),
+    };
+    {
+        let mut state = proxy::state().write().map_err(|_| "state lock")?;
+        state.grants.retain(|g| g.domain != domain);
+        state.grants.push(grant.clone());
+    }
+    evidence::push("info", &format!("Temporary allow granted for {} ({}s)", domain, ttl_secs));
+    Ok(grant)
+}
+
+#[tauri::command]
+pub fn policy_list_grants() -> Result<Vec<TempGrant>, String> {
+    prune_expired_grants();
+    let state = proxy::state().read().map_err(|_| "state lock")?;
+    Ok(state.grants.clone())
+}
+
+/// Drop lapsed grants and record each expiry in the evidence log. Runs on
+/// every policy evaluation, so it only takes the write lock when one lapsed.
+pub fn prune_expired_grants() {
+    let now = Utc::now().timestamp().max(0) as u64;
+    let any_expired = proxy::state()
+        .read()
+        .map(|s| s.grants.iter().any(|g| g.expires_at <= now))
+        .unwrap_or(false);
+    if !any_expired {
+        return;
+    }
+    let expired: Vec<TempGrant> = match proxy::state().write() {
+        Ok(mut state) => {
+            let (expired, live) = state.grants.drain(..).partition(|g| g.expires_at <= now);
+            state.grants = live;
+            expired
+        }
+        Err(_) => return,
+    };
+    for g in expired {
+        evidence::push("info", &format!("Temporary allow for {} expired", g.domain));
+    }
+}
+
+/// Time window in which the proxy forwards requests. `start`/`end` are `HH:MM`;
+/// a window whose end is before its start runs past midnight into the next day.
+/// `days` are weekday names (`mon`, `tuesday`, ...) for the day the window opens;
+/// empty means every day. `tz` is an IANA zone name, `UTC`, or `local` (default).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessWindow {
+    #[serde(default)]
+    pub days: Vec<String>,
+    pub start: String,
+    pub end: String,
+    #[serde(default)]
+    pub tz: Option<String>,
+}
+
+/// True when no windows are configured or `now` falls inside at least one of them.
+pub fn within_allowed_window(policy: &Policy, now: DateTime<Utc>) -> bool {
+    policy.allow_windows.is_empty() || policy.allow_windows.iter().any(|w| window_contains(w, now))
+}
+
+fn window_contains(window: &AccessWindow, now: DateTime<Utc>) -> bool {
+    let (weekday, minute) = match window.tz.as_deref() {
+        None | Some("") | Some("local") => {
+            let t = now.with_timezone(&Local);
+            (t.weekday(), t.hour() * 60 + t.minute())
+        }
+        Some(name) => match name.parse::<Tz>() {
+            Ok(tz) => {
+                let t = now.with_timezone(&tz);
+                (t.weekday(), t.hour() * 60 + t.minute())
+            }
+            Err(_) => {
+                tracing::warn!("Unknown timezone in policy window: {}", name);
+                return false;
+            }
+        },
+    };
+    let (Some(start), Some(end)) = (parse_hhmm(&window.start), parse_hhmm(&window.end)) else {
+        tracing::warn!("Invalid policy window {}-{}", window.start, window.end);
+        return false;
+    };
+    let on = |day: Weekday| {
+        window.days.is_empty() || window.days.iter().any(|d| d.parse::<Weekday>().ok() == Some(day))
+    };
+    if start <= end {
+        on(weekday) && minute >= start && minute < end
+    } else {
+        (on(weekday) && minute >= start) || (on(weekday.pred()) && minute < end)
+    }
+}
+
+/// Minutes since midnight for `HH:MM`; `24:00` is accepted as end of day.
+fn parse_hhmm(s: &str) -> Option<u32> {
+    let (h, m) = s.trim().split_once(':')?;
+    let (h, m): (u32, u32) = (h.parse().ok()?, m.parse().ok()?);
+    if m >= 60 || h > 24 || (h == 24 && m != 0) {
+        return None;
+    }
+    Some(h * 60 + m)
+}
+
+/// Read a policy file, choosing JSON or YAML by extension (YAML for anything not `.json`)
+/// and falling back to the other format before giving up.
+pub(crate) fn read_policy_file(path: &Path) -> Result<Policy, String> {
+    let s = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let from_json = || serde_json::from_str::<Policy>(&s).map_err(|e| e.to_string());
+    let from_yaml = || serde_yaml::from_str::<Policy>(&s).map_err(|e| e.to_string());
+    let result = if is_json_path(path) {
+        from_json().or_else(|je| from_yaml().map_err(|ye| (je, ye)))
+    } else {
+        from_yaml().or_else(|ye| from_json().map_err(|je| (je, ye)))
+    };
+    result.map_err(|(je, ye)| {
+        format!(
+            "{} is neither a valid JSON nor YAML policy (json: {je}; yaml: {ye})",
+            path.display()
+        )
+    })
+}
+
+fn is_json_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case("json"))
+        .unwrap_or(false)
+}
+
+/// Reject policies that would parse but silently misbehave at enforcement time.
+pub fn validate_policy(policy: &Policy) -> Result<(), String> {
+    for pat in policy.output_redact_patterns.iter().chain(&policy.input_redact_patterns) {
+        regex::Regex::new(pat).map_err(|e| format!("invalid redact pattern {pat}: {e}"))?;
+    }
+    for r in &policy.inject_rules {
+        if let Some(h) = &r.header {
+            reqwest::header::HeaderName::from_bytes(h.as_bytes())
+                .map_err(|_| format!("invalid inject header {h} for {}", r.host))?;
+        }
+    }
+    for w in &policy.allow_windows {
+        if parse_hhmm(&w.start).is_none() || parse_hhmm(&w.end).is_none() {
+            return Err(format!("invalid time window {}-{}", w.start, w.end));
+        }
+        if let Some(tz) = w.tz.as_deref().filter(|t| !t.is_empty() && *t != "local") {
+            tz.parse::<Tz>().map_err(|_| format!("unknown timezone {tz}"))?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn default_policy_path() -> String {
     dirs::config_dir()
         .map(|p| p.join("vault0").join("policy.yaml"))
         .and_then(|p| {
@@ -77,3 +540,218 @@ fn default_policy_path() -> String {
         })
         .unwrap_or_else(|| "policy.yaml".to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(policy: &Policy, host: &str, path: &str, method: &str) -> PolicyDecision {
+        evaluate(policy, &[], host, path, method, Utc::now())
+    }
+
+    #[test]
+    fn global_block_methods_return_405() {
+        let policy = Policy { block_methods: vec!["delete".into()], ..Policy::default() };
+        let d = check(&policy, "api.openai.com", "/v1/files/1", "DELETE");
+        assert!(!d.allowed);
+        assert_eq!(d.status, 405);
+        assert_eq!(d.matched_rule.as_deref(), Some("block_methods: DELETE"));
+        assert!(check(&policy, "api.openai.com", "/v1/files", "GET").allowed);
+    }
+
+    #[test]
+    fn method_rules_apply_per_domain() {
+        let policy = Policy {
+            method_rules: vec![MethodRule {
+                domain: "openai.com".into(),
+                allow_methods: vec!["GET".into(), "POST".into()],
+                block_methods: Vec::new(),
+            }],
+            ..Policy::default()
+        };
+        assert!(check(&policy, "api.openai.com", "/v1/chat", "post").allowed);
+        let d = check(&policy, "api.openai.com", "/v1/files/1", "PUT");
+        assert_eq!((d.allowed, d.status), (false, 405));
+        assert_eq!(d.matched_rule.as_deref(), Some("method_rules: openai.com"));
+        assert!(check(&policy, "api.anthropic.com", "/v1/messages", "PUT").allowed);
+    }
+
+    #[test]
+    fn longest_path_prefix_wins() {
+        let rule = |prefix: &str, action| PathRule { domain: "api.openai.com".into(), prefix: prefix.into(), action };
+        let policy = Policy {
+            path_rules: vec![rule("/v1/", RuleAction::Block), rule("/v1/chat/", RuleAction::Allow)],
+            ..Policy::default()
+        };
+        let d = check(&policy, "api.openai.com", "/v1/chat/completions", "POST");
+        assert!(d.allowed);
+        assert_eq!(d.matched_rule.as_deref(), Some("path_rules: api.openai.com/v1/chat/"));
+        let d = check(&policy, "api.openai.com", "/v1/files", "GET");
+        assert_eq!((d.allowed, d.status), (false, 403));
+        assert!(check(&policy, "api.anthropic.com", "/v1/files", "GET").allowed);
+    }
+
+    #[test]
+    fn path_rules_see_through_dot_segments_slashes_and_escapes() {
+        let policy = Policy {
+            path_rules: vec![PathRule {
+                domain: "api.openai.com".into(),
+                prefix: "/v1/fine_tuning".into(),
+                action: RuleAction::Block,
+            }],
+            ..Policy::default()
+        };
+        for path in [
+            "/v1/fine_tuning/jobs",
+            "/v1/./fine_tuning/jobs",
+            "/v1//fine_tuning",
+            "//v1/fine_tuning",
+            "/v1/chat/../fine_tuning",
+            "/v1/%66ine_tuning",
+            "/v1/fine%5Ftuning",
+            "/v1/%2e/fine_tuning",
+            "/v1/%2E%2E/v1/fine_tuning",
+        ] {
+            assert!(!check(&policy, "api.openai.com", path, "POST").allowed, "{path} got through");
+        }
+        assert!(check(&policy, "api.openai.com", "/v1/chat/completions", "POST").allowed);
+    }
+
+    #[test]
+    fn normalize_path_keeps_reserved_escapes_and_trailing_slashes() {
+        assert_eq!(normalize_path("/a/b/"), "/a/b/");
+        assert_eq!(normalize_path("/a/b/.."), "/a/");
+        assert_eq!(normalize_path("/../.."), "/");
+        assert_eq!(normalize_path("/a%2Fb"), "/a%2Fb");
+        assert_eq!(normalize_path("/caf\u{e9}/%7Euser"), "/caf\u{e9}/~user");
+    }
+
+    #[test]
+    fn path_block_is_checked_before_methods() {
+        let policy = Policy {
+            block_methods: vec!["DELETE".into()],
+            path_rules: vec![PathRule { domain: "openai.com".into(), prefix: "/admin".into(), action: RuleAction::Block }],
+            ..Policy::default()
+        };
+        assert_eq!(check(&policy, "api.openai.com", "/admin/users", "DELETE").status, 403);
+    }
+
+    fn window(days: &[&str], start: &str, end: &str) -> AccessWindow {
+        AccessWindow {
+            days: days.iter().map(|d| d.to_string()).collect(),
+            start: start.into(),
+            end: end.into(),
+            tz: Some("UTC".into()),
+        }
+    }
+
+    /// 2026-10-16 is a Friday.
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        use chrono::TimeZone;
+        Utc.with_ymd_and_hms(2026, 10, day, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn window_within_a_day() {
+        let w = window(&["fri"], "09:00", "17:30");
+        assert!(window_contains(&w, at(16, 9, 0)));
+        assert!(window_contains(&w, at(16, 17, 29)));
+        assert!(!window_contains(&w, at(16, 17, 30)));
+        assert!(!window_contains(&w, at(17, 12, 0)));
+    }
+
+    #[test]
+    fn window_wrapping_midnight_belongs_to_its_opening_day() {
+        let w = window(&["friday"], "22:00", "06:00");
+        assert!(window_contains(&w, at(16, 23, 0)));
+        assert!(window_contains(&w, at(17, 5, 59)));
+        assert!(!window_contains(&w, at(17, 6, 0)));
+        assert!(!window_contains(&w, at(16, 3, 0)));
+        assert!(!window_contains(&w, at(16, 12, 0)));
+        assert!(!window_contains(&w, at(17, 23, 0)));
+    }
+
+    #[test]
+    fn invalid_hhmm_never_matches_and_fails_validation() {
+        for (start, end) in [("25:00", "06:00"), ("09:60", "17:00"), ("noon", "17:00"), ("09:00", "24:01")] {
+            let w = window(&[], start, end);
+            assert!(!window_contains(&w, at(16, 12, 0)), "{start}-{end}");
+            let policy = Policy { allow_windows: vec![w], ..Policy::default() };
+            assert!(validate_policy(&policy).is_err(), "{start}-{end}");
+        }
+        assert_eq!(parse_hhmm("24:00"), Some(24 * 60));
+    }
+
+    #[test]
+    fn outside_every_window_is_denied() {
+        let policy = Policy { allow_windows: vec![window(&[], "09:00", "17:00")], ..Policy::default() };
+        let d = evaluate(&policy, &[], "api.openai.com", "/", "GET", at(16, 20, 0));
+        assert_eq!(d.matched_rule.as_deref(), Some("allow_windows"));
+        assert!(!d.allowed);
+        assert!(evaluate(&policy, &[], "api.openai.com", "/", "GET", at(16, 10, 0)).allowed);
+    }
+
+    #[test]
+    fn env_rules_match_on_path_components() {
+        let policy = Policy {
+            agent_env_rules: vec![
+                AgentEnvRule { script: "agents/run.py".into(), aliases: vec!["openai".into()] },
+                AgentEnvRule { script: "/opt/bot/main.py".into(), aliases: vec!["slack".into()] },
+            ],
+            ..Policy::default()
+        };
+        let aliases = |script: &str| allowed_env_aliases(&policy, script).unwrap();
+        assert_eq!(aliases("/home/me/agents/run.py"), vec!["openai"]);
+        assert_eq!(aliases("C:\\work\\agents\\run.py"), vec!["openai"]);
+        assert!(aliases("/home/me/evil_agents/run.py").is_empty());
+        assert!(aliases("/home/me/myagents/run.py").is_empty());
+        assert_eq!(aliases("/opt/bot/main.py"), vec!["slack"]);
+        assert!(aliases("/tmp/opt/bot/main.py").is_empty());
+        assert_eq!(allowed_env_aliases(&Policy::default(), "/x.py"), None);
+    }
+
+    #[test]
+    fn temp_grant_lifts_allow_list_but_not_block_list() {
+        let policy = Policy {
+            allow_domains: vec!["openai.com".into()],
+            block_domains: vec!["169.254.169.254".into()],
+            ..Policy::default()
+        };
+        let now = Utc::now();
+        let live = now.timestamp() as u64 + 60;
+        let grant = |domain: &str, expires_at| TempGrant { domain: domain.into(), granted_at: 0, expires_at };
+        assert!(!check(&policy, "example.com", "/", "GET").allowed);
+        let d = evaluate(&policy, &[grant("example.com", live)], "example.com", "/", "GET", now);
+        assert!(d.allowed);
+        assert_eq!(d.matched_rule.as_deref(), Some("temp_grant: example.com"));
+        let d = evaluate(&policy, &[grant("example.com", 1)], "example.com", "/", "GET", now);
+        assert!(!d.allowed);
+        let d = evaluate(&policy, &[grant("169.254.169.254", live)], "169.254.169.254", "/", "GET", now);
+        assert_eq!(d.matched_rule.as_deref(), Some("block_domains: 169.254.169.254"));
+        assert!(!d.allowed);
+    }
+
+    #[test]
+    fn host_matches_domain_and_subdomains() {
+        assert!(host_matches("api.openai.com", "openai.com"));
+        assert!(host_matches("openai.com", "openai.com"));
+        assert!(host_matches("API.OpenAI.com.", "openai.com"));
+        assert!(!host_matches("openai.com", "api.openai.com"));
+    }
+
+    #[test]
+    fn host_matches_respects_label_boundaries() {
+        assert!(!host_matches("evilopenai.com", "openai.com"));
+        assert!(!host_matches("openai.com.evil.net", "openai.com"));
+    }
+
+    #[test]
+    fn host_matches_wildcards() {
+        assert!(host_matches("api.anthropic.com", "*.anthropic.com"));
+        assert!(host_matches("a.b.anthropic.com", "*.anthropic.com"));
+        assert!(!host_matches("anthropic.com", "*.anthropic.com"));
+        assert!(!host_matches("evilanthropic.com", "*.anthropic.com"));
+        assert!(host_matches("anything.example", "*"));
+        assert!(!host_matches("openai.com", ""));
+    }
+}
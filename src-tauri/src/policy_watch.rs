@@ -0,0 +1,89 @@
+//! Hot reload of the policy file while the proxy runs.
+//! Watches the file's directory (editors often replace files rather than write in place)
+//! and swaps a new policy into `ProxyState` only after it parses and validates.
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::Lazy;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::{evidence, policy, proxy};
+
+static WATCHER: Lazy<Mutex<Option<RecommendedWatcher>>> = Lazy::new(|| Mutex::new(None));
+/// Raw contents of the last file applied, so duplicate change events don't reload twice.
+static LAST_APPLIED: Lazy<Mutex<String>> = Lazy::new(|| Mutex::new(String::new()));
+
+/// Start or stop watching the policy file. Dropping the watcher stops it.
+#[tauri::command]
+pub fn policy_watch(enabled: bool, path: Option<String>) -> Result<(), String> {
+    let mut guard = WATCHER.lock().map_err(|_| "watcher lock")?;
+    *guard = None;
+    if !enabled {
+        info!("Policy watcher stopped");
+        return Ok(());
+    }
+    let path = PathBuf::from(path.unwrap_or_else(policy::default_policy_path));
+    let dir = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+        .to_path_buf();
+    let file_name = path.file_name().map(|n| n.to_os_string());
+    let watched = path.clone();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        let event = match res {
+            Ok(e) => e,
+            Err(e) => {
+                warn!("Policy watcher error: {}", e);
+                return;
+            }
+        };
+        if !(event.kind.is_modify() || event.kind.is_create()) {
+            return;
+        }
+        if event.paths.iter().any(|p| p.file_name().map(|n| n.to_os_string()) == file_name) {
+            reload(&watched);
+        }
+    })
+    .map_err(|e| format!("policy watcher: {e}"))?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("watch {}: {e}", dir.display()))?;
+    *guard = Some(watcher);
+    info!("Policy watcher started on {}", path.display());
+    Ok(())
+}
+
+fn reload(path: &Path) {
+    let raw = match std::fs::read_to_string(path) {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    if let Ok(last) = LAST_APPLIED.lock() {
+        if *last == raw {
+            return;
+        }
+    }
+    let result = policy::read_policy_file(path).and_then(|p| policy::validate_policy(&p).map(|_| p));
+    match result {
+        Ok(new_policy) => {
+            match proxy::state().write() {
+                Ok(mut state) => state.policy = new_policy,
+                Err(_) => return,
+            }
+            if let Ok(mut last) = LAST_APPLIED.lock() {
+                *last = raw;
+            }
+            info!("Policy reloaded from {}", path.display());
+            evidence::push("info", &format!("Policy reloaded from {}", path.display()));
+        }
+        Err(e) => {
+            warn!("Policy reload rejected: {}", e);
+            evidence::push(
+                "error",
+                &format!("Policy reload failed, keeping previous policy: {}", e),
+            );
+        }
+    }
+}
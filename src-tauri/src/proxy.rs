@@ -1,33 +1,72 @@
-use crate::evidence;
+use crate::evidence::{self, EvidenceFields};
 use crate::mcp_guard;
-use crate::policy::Policy;
+use crate::proxy_coalesce::{self, Fetched};
+use crate::proxy_cost;
+use crate::proxy_inject;
+use crate::proxy_redact::redact_body;
+use crate::proxy_stream;
+use crate::policy::{self, Policy, PolicyDecision};
 use base64::Engine;
+use futures_util::FutureExt;
 use axum::{
     body::Body,
     extract::Request,
-    http::{StatusCode, Uri},
+    http::{Method, StatusCode, Uri},
     response::{IntoResponse, Response},
 };
 use once_cell::sync::Lazy;
-use std::collections::HashMap;
-use std::net::SocketAddr;
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
 use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::RwLock;
+use std::sync::{Mutex, RwLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use serde::Serialize;
 use thiserror::Error;
-use tracing::info;
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+use zeroize::Zeroizing;
+
+/// Request header naming the vault alias to inject instead of the per-host default.
+/// Honoured only when the policy allows it (see `proxy_inject::header_for_alias`).
+/// Consumed by the proxy, never forwarded.
+const ALIAS_HEADER: &str = "x-vault0-alias";
+
+/// How long `stop` lets in-flight requests and streams finish before the
+/// remaining connections are dropped.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
 
 static RUNNING: AtomicBool = AtomicBool::new(false);
+/// Address the proxy was started on, and when.
+static STARTED: Lazy<Mutex<Option<(SocketAddr, Instant)>>> = Lazy::new(|| Mutex::new(None));
+/// Shutdown trigger and server thread of the running proxy.
+static SERVER: Lazy<Mutex<Option<(oneshot::Sender<()>, JoinHandle<()>)>>> = Lazy::new(|| Mutex::new(None));
 
 pub struct ProxyState {
-    pub vault: HashMap<String, String>,
+    /// Loaded vault keys by alias, overwritten in memory when removed.
+    pub vault: HashMap<String, Zeroizing<String>>,
+    /// Expiry (unix seconds) of loaded vault keys that have one.
+    pub expires: HashMap<String, u64>,
+    /// Aliases in `vault` loaded from the vault store, as opposed to
+    /// `set_secret` or a config scan.
+    pub vault_aliases: HashSet<String>,
     pub policy: Policy,
+    pub grants: Vec<policy::TempGrant>,
+    /// Approximate model spend since start, see `proxy_cost`.
+    pub spend_cents: f64,
+    pub priced_requests: u64,
 }
 
 static STATE: Lazy<RwLock<ProxyState>> = Lazy::new(|| {
     RwLock::new(ProxyState {
         vault: HashMap::new(),
+        expires: HashMap::new(),
+        vault_aliases: HashSet::new(),
         policy: Policy::default(),
+        grants: Vec::new(),
+        spend_cents: 0.0,
+        priced_requests: 0,
     })
 });
 
@@ -49,12 +88,54 @@ pub fn state() -> &'static RwLock<ProxyState> {
     &STATE
 }
 
+/// Refuse a non-loopback bind address unless `proxy_allow_remote` opts in, and
+/// warn when it does.
+fn check_bind(addr: SocketAddr) -> Result<(), String> {
+    if addr.ip().is_loopback() {
+        return Ok(());
+    }
+    if !crate::settings::get().proxy_allow_remote {
+        return Err(format!(
+            "{} is not a loopback address; enable proxy_allow_remote to expose the proxy",
+            addr.ip()
+        ));
+    }
+    let msg = format!("Proxy listening on non-loopback address {}; other machines can use the injected vault keys", addr);
+    warn!("{}", msg);
+    evidence::push("warning", &msg);
+    Ok(())
+}
+
 pub fn start() -> Result<(), ProxyError> {
     if RUNNING.swap(true, Ordering::Relaxed) {
         return Err(ProxyError::AlreadyRunning);
     }
-    let addr = SocketAddr::from_str("127.0.0.1:3840").map_err(|e| ProxyError::Bind(e.to_string()))?;
-    std::thread::spawn(move || {
+    // Bind here so a taken port is reported to the caller instead of
+    // panicking the server thread.
+    let bound = SocketAddr::from_str(&crate::settings::proxy_addr())
+        .map_err(|e| e.to_string())
+        .and_then(|addr| {
+            check_bind(addr)?;
+            let listener = std::net::TcpListener::bind(addr).map_err(|e| format!("{}: {}", addr, e))?;
+            listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+            Ok((addr, listener))
+        });
+    let (addr, listener) = bound.map_err(|e| {
+        RUNNING.store(false, Ordering::Relaxed);
+        ProxyError::Bind(e)
+    })?;
+    if let Ok(mut started) = STARTED.lock() {
+        *started = Some((addr, Instant::now()));
+    }
+    let identities = {
+        let guard = STATE.read().expect("state read");
+        crate::proxy_tls::load_identities(&guard.policy)
+    };
+    if identities > 0 {
+        info!("Loaded {} client certificate(s) for mutual TLS", identities);
+    }
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    let thread = std::thread::spawn(move || {
         let rt = tokio::runtime::Builder::new_current_thread()
             .enable_all()
             .build()
@@ -62,23 +143,95 @@ pub fn start() -> Result<(), ProxyError> {
         rt.block_on(async {
             let app = axum::Router::new()
                 .route("/", axum::routing::any(proxy_handler))
-                .route("/*path", axum::routing::any(proxy_handler));
-            let listener = tokio::net::TcpListener::bind(addr).await.expect("proxy bind");
+                .route("/*path", axum::routing::any(proxy_handler))
+                // CONNECT targets are `host:port` with no path, so they match no route.
+                .fallback(proxy_handler);
+            let listener = match tokio::net::TcpListener::from_std(listener) {
+                Ok(l) => l,
+                Err(e) => {
+                    warn!("Vault-0 proxy listener on {}: {}", addr, e);
+                    return;
+                }
+            };
             info!("Vault-0 proxy listening on {}", addr);
-            axum::serve(listener, app).await.expect("proxy serve");
+            let shutdown = shutdown_rx.shared();
+            let server = axum::serve(listener, app).with_graceful_shutdown(shutdown.clone().map(|_| ()));
+            tokio::select! {
+                res = server => {
+                    if let Err(e) = res {
+                        warn!("Vault-0 proxy stopped with error: {}", e);
+                    }
+                }
+                _ = async {
+                    let _ = shutdown.await;
+                    tokio::time::sleep(DRAIN_TIMEOUT).await;
+                } => warn!("Proxy connections still open after {:?}; closing them", DRAIN_TIMEOUT),
+            }
         });
+        // Dropping the runtime cancels any tunnels or streams still running.
+        info!("Vault-0 proxy on {} shut down", addr);
     });
+    if let Ok(mut server) = SERVER.lock() {
+        *server = Some((shutdown_tx, thread));
+    }
     Ok(())
 }
 
+/// Stop accepting connections, let in-flight requests drain (up to
+/// `DRAIN_TIMEOUT`) and wait for the server thread, so the port is free for
+/// the next `start`.
 pub fn stop() -> Result<(), ProxyError> {
-    if !RUNNING.swap(false, Ordering::Relaxed) {
+    if !is_running() {
         return Err(ProxyError::NotRunning);
     }
+    let server = SERVER.lock().ok().and_then(|mut s| s.take());
+    if let Some((shutdown, thread)) = server {
+        let _ = shutdown.send(());
+        let _ = thread.join();
+    }
+    if let Ok(mut started) = STARTED.lock() {
+        *started = None;
+    }
+    RUNNING.store(false, Ordering::Relaxed);
     Ok(())
 }
 
+#[derive(Debug, Serialize)]
+pub struct ProxyHealth {
+    pub running_flag: bool,
+    /// A loopback TCP connect to `bound_addr` succeeded.
+    pub port_reachable: bool,
+    pub bound_addr: Option<String>,
+    pub uptime_secs: u64,
+}
+
+/// Status from an actual connection attempt, not just the `RUNNING` flag, which
+/// stays set if the server thread failed to bind or panicked.
+pub fn health() -> ProxyHealth {
+    let started = STARTED.lock().ok().and_then(|s| *s);
+    let port_reachable = started
+        .map(|(addr, _)| {
+            // A wildcard bind is reachable on loopback.
+            let target = match addr.ip() {
+                IpAddr::V4(ip) if ip.is_unspecified() => SocketAddr::from(([127, 0, 0, 1], addr.port())),
+                IpAddr::V6(ip) if ip.is_unspecified() => SocketAddr::from((std::net::Ipv6Addr::LOCALHOST, addr.port())),
+                _ => addr,
+            };
+            std::net::TcpStream::connect_timeout(&target, Duration::from_millis(500)).is_ok()
+        })
+        .unwrap_or(false);
+    ProxyHealth {
+        running_flag: is_running(),
+        port_reachable,
+        bound_addr: started.map(|(addr, _)| addr.to_string()),
+        uptime_secs: started.map(|(_, at)| at.elapsed().as_secs()).unwrap_or(0),
+    }
+}
+
 async fn proxy_handler(req: Request) -> Response {
+    if req.method() == Method::CONNECT {
+        return crate::proxy_connect::tunnel(req).await;
+    }
     let uri = req.uri().clone();
     let host_header = req
         .headers()
@@ -93,68 +246,200 @@ async fn proxy_handler(req: Request) -> Response {
         .filter(|h| !h.is_empty())
         .unwrap_or_else(|| host_header.split(':').next().unwrap_or("").to_string());
 
-    let (allowed, deny_reason) = {
-        let guard = STATE.read().expect("state read");
-        let policy = &guard.policy;
-        let allow = policy.allow_domains.is_empty()
-            || policy.allow_domains.iter().any(|d| host.ends_with(d.as_str()));
-        let block = policy.block_domains.iter().any(|d| host.ends_with(d.as_str()));
-        if block {
-            (false, Some("domain blocked by policy".to_string()))
-        } else if !policy.allow_domains.is_empty() && !allow {
-            (false, Some("domain not in allow list".to_string()))
-        } else {
-            (true, None)
-        }
+    // The self-test's echo request is let through on its nonce alone.
+    let self_test_alias = crate::proxy_selftest::claim_echo(&host, uri.port_u16(), path, req.headers());
+    let decision = match self_test_alias {
+        Some(_) => PolicyDecision::allow(Some("self_test".to_string())),
+        None => match policy::evaluate_request(&host, path, req.method().as_str()) {
+            Ok(d) => d,
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+        },
     };
-
-    if !allowed {
-        let msg = format!("Vault-0 policy denied: {}", deny_reason.unwrap_or_default());
-        evidence::push("blocked", &msg);
-        return (StatusCode::FORBIDDEN, msg).into_response();
+    if !decision.allowed {
+        return denied(&decision, &host, req.method().as_str());
+    }
+    if let Some(refused) = proxy_cost::over_cap(&host) {
+        return refused;
     }
 
+    // MCP targets are dialled at the address the SSRF check resolved.
+    let mut pinned_addr = None;
+    let mut mcp_token_alias: Option<String> = None;
+    let mut mcp_methods: Option<Vec<String>> = None;
     if mcp_guard::is_mcp_request(&host, path) {
         if !mcp_guard::origin_allowed(&host) {
-            evidence::push("blocked", "MCP server not in allowlist");
-            return (
-                StatusCode::FORBIDDEN,
-                "MCP server not in allowlist".to_string(),
-            )
-                .into_response();
+            return mcp_blocked("mcp_origin", "MCP server not in allowlist", &host, None, StatusCode::FORBIDDEN);
         }
-        if mcp_guard::would_be_ssrf(uri.authority().map(|a| a.as_str()).unwrap_or("")) {
-            evidence::push("blocked", "MCP SSRF: private/internal target blocked");
-            return (
-                StatusCode::FORBIDDEN,
-                "MCP SSRF: private/internal target blocked".to_string(),
-            )
-                .into_response();
+        let authority = uri.authority().map(|a| a.as_str()).unwrap_or(&host_header).to_string();
+        let default_port = if uri.scheme_str() == Some("http") { 80 } else { 443 };
+        match mcp_guard::resolve_checked(&authority, default_port).await {
+            Ok(addr) => pinned_addr = addr,
+            Err(blocked) => {
+                return mcp_blocked(
+                    "mcp_ssrf",
+                    &format!("MCP SSRF: {}", blocked.reason),
+                    &host,
+                    blocked.ip,
+                    StatusCode::FORBIDDEN,
+                );
+            }
         }
-        if mcp_guard::token_passthrough_disabled() && req.headers().contains_key("authorization") {
-            evidence::push("blocked", "Token passthrough disabled for MCP");
-            return (
+        if req.headers().contains_key(ALIAS_HEADER) {
+            return mcp_blocked(
+                "mcp_token_passthrough",
+                "X-Vault0-Alias is not accepted for MCP servers; scope a token to the origin instead",
+                &host,
+                pinned_addr.map(|a| a.ip()),
                 StatusCode::BAD_REQUEST,
-                "Token passthrough disabled for MCP".to_string(),
-            )
-                .into_response();
+            );
         }
+        mcp_token_alias = mcp_guard::scoped_token_alias(&host);
+        if mcp_token_alias.is_none() && req.headers().contains_key("authorization") {
+            return mcp_blocked(
+                "mcp_token_passthrough",
+                "Token passthrough disabled for MCP",
+                &host,
+                pinned_addr.map(|a| a.ip()),
+                StatusCode::BAD_REQUEST,
+            );
+        }
+        mcp_methods = mcp_guard::method_allowlist(&host);
     }
 
     let (method, headers, body) = (req.method().clone(), req.headers().clone(), req.into_body());
+    let body_bytes = match &mcp_methods {
+        // The JSON-RPC method is checked before anything is forwarded. The peek is
+        // capped, so an oversized body is refused rather than buffered.
+        Some(allowed) => {
+            let pinned_ip = pinned_addr.map(|a| a.ip());
+            let Ok(bytes) = axum::body::to_bytes(body, mcp_guard::METHOD_PEEK_LIMIT).await else {
+                return mcp_blocked(
+                    "mcp_method",
+                    "MCP request body too large to check its method",
+                    &host,
+                    pinned_ip,
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                );
+            };
+            if let Err(reason) = mcp_guard::check_methods(allowed, &bytes) {
+                return mcp_blocked("mcp_method", &reason, &host, pinned_ip, StatusCode::FORBIDDEN);
+            }
+            bytes
+        }
+        None => {
+            let limit = crate::settings::get().proxy_body_limit_mb.saturating_mul(1024 * 1024);
+            match axum::body::to_bytes(body, limit).await {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    let too_large = e.into_inner().downcast_ref::<http_body_util::LengthLimitError>().is_some();
+                    let (kind, status, msg) = if too_large {
+                        (
+                            "blocked",
+                            StatusCode::PAYLOAD_TOO_LARGE,
+                            format!("Request body to {} exceeds the {} byte proxy limit", host, limit),
+                        )
+                    } else {
+                        ("error", StatusCode::BAD_REQUEST, format!("Could not read request body for {}", host))
+                    };
+                    evidence::push_structured(
+                        kind,
+                        &msg,
+                        EvidenceFields {
+                            domain: Some(host.clone()),
+                            method: Some(method.to_string()),
+                            status: Some(status.as_u16()),
+                            check: Some("body_limit".to_string()),
+                            ..Default::default()
+                        },
+                    );
+                    return (status, msg).into_response();
+                }
+            }
+        }
+    };
     let target_url = build_full_uri(&uri, &host);
-    let inject_key = alias_for_host(&host);
+    let scoped = mcp_token_alias.is_some();
+    let requested_alias = headers
+        .get(ALIAS_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty());
 
-    let (auth_header, redact_patterns) = {
+    let (inject_key, inject_header, auth_header, redact_patterns, input_patterns) = {
         let state_guard = STATE.read().expect("state read");
+        let (inject_key, inject_header) = match (mcp_token_alias.or(self_test_alias), &requested_alias) {
+            (Some(alias), _) => (Some(alias), proxy_inject::InjectHeader::default()),
+            (None, Some(alias)) => match proxy_inject::header_for_alias(&state_guard.policy, &host, alias) {
+                Ok(header) => (Some(alias.clone()), header),
+                Err(reason) => {
+                    drop(state_guard);
+                    return proxy_inject::alias_refused(alias, &host, method.as_str(), reason);
+                }
+            },
+            (None, None) => match proxy_inject::alias_for_host(&state_guard.policy, &host) {
+                Some((alias, header)) => (Some(alias), header),
+                None => (None, proxy_inject::InjectHeader::default()),
+            },
+        };
         let auth = inject_key.as_ref().and_then(|alias| state_guard.vault.get(alias.as_str()).cloned());
         let redact = state_guard.policy.output_redact_patterns.clone();
-        (auth, redact)
+        (inject_key, inject_header, auth, redact, state_guard.policy.input_redact_patterns.clone())
     };
+    let body_bytes = if input_patterns.is_empty() || body_bytes.is_empty() {
+        body_bytes
+    } else {
+        let (scrubbed, matched) = crate::proxy_redact::redact_request(&body_bytes, &input_patterns);
+        for pat in &matched {
+            evidence::push_structured(
+                "warning",
+                &format!("Redacted a match for input pattern {} from the request body to {}", pat, host),
+                EvidenceFields {
+                    domain: Some(host.clone()),
+                    method: Some(method.to_string()),
+                    check: Some("input_redact".to_string()),
+                    ..Default::default()
+                },
+            );
+        }
+        axum::body::Bytes::from(scrubbed)
+    };
+    if let (Some(alias), None) = (&requested_alias, &auth_header) {
+        let msg = format!("X-Vault0-Alias '{}' is not in the vault", alias);
+        evidence::push_structured(
+            "blocked",
+            &msg,
+            EvidenceFields {
+                domain: Some(host.clone()),
+                method: Some(method.to_string()),
+                status: Some(StatusCode::BAD_REQUEST.as_u16()),
+                ..Default::default()
+            },
+        );
+        return (StatusCode::BAD_REQUEST, msg).into_response();
+    }
+    if scoped && auth_header.is_none() {
+        let msg = format!("MCP token alias '{}' is not loaded in the vault", inject_key.as_deref().unwrap_or_default());
+        evidence::push("blocked", &msg);
+        return (StatusCode::SERVICE_UNAVAILABLE, msg).into_response();
+    }
+    if let (Some(alias), Some(_)) = (&inject_key, &auth_header) {
+        if let Some(refused) = proxy_inject::expired(alias, &host) {
+            return refused;
+        }
+    }
 
     let mut out_headers = reqwest::header::HeaderMap::new();
     for (k, v) in headers.iter() {
-        if k.as_str().eq_ignore_ascii_case("authorization") && auth_header.is_some() {
+        // Scoped MCP origins only ever see the injected token.
+        if k.as_str().eq_ignore_ascii_case("authorization") && (auth_header.is_some() || scoped) {
+            continue;
+        }
+        // reqwest advertises the encodings it can decode (gzip, brotli,
+        // deflate) and hands back plaintext, so redaction sees the real body.
+        if k.as_str().eq_ignore_ascii_case(ALIAS_HEADER)
+            || k.as_str().eq_ignore_ascii_case(crate::proxy_selftest::NONCE_HEADER)
+            || k.as_str().eq_ignore_ascii_case("accept-encoding")
+        {
             continue;
         }
         if let Ok(name) = reqwest::header::HeaderName::from_bytes(k.as_str().as_bytes()) {
@@ -164,45 +449,102 @@ async fn proxy_handler(req: Request) -> Response {
         }
     }
     if let Some(ref key) = auth_header {
-        out_headers.insert(
-            reqwest::header::AUTHORIZATION,
-            reqwest::header::HeaderValue::from_str(&format!("Bearer {}", key))
-                .unwrap_or_else(|_| reqwest::header::HeaderValue::from_static("Bearer")),
-        );
+        proxy_inject::apply(&mut out_headers, &inject_header, key);
+        // An agent using its keys keeps the vault from auto-locking under it.
+        crate::vault_store::touch();
     }
 
-    let client = reqwest::Client::builder().build().unwrap_or_default();
-    const BODY_LIMIT: usize = 10 * 1024 * 1024;
-    let body_bytes = axum::body::to_bytes(body, BODY_LIMIT).await.unwrap_or_default();
-    let req_builder = client.request(method.clone(), &target_url).headers(out_headers.clone());
-    let upstream = if body_bytes.is_empty() {
-        req_builder.send().await
+    let injected_alias = if auth_header.is_some() { inject_key.clone() } else { None };
+
+    let mut client_builder = reqwest::Client::builder();
+    if let Some(secs) = crate::settings::get().upstream_timeout_secs {
+        client_builder = client_builder.timeout(Duration::from_secs(secs));
+    }
+    if let Some(addr) = pinned_addr {
+        client_builder = client_builder.resolve(&host, addr);
+    }
+    if let Some(identity) = crate::proxy_tls::identity_for(&host) {
+        client_builder = client_builder.identity(identity);
+    }
+    let client = client_builder.build().unwrap_or_default();
+    let started = Instant::now();
+    let mut req_builder = client.request(method.clone(), &target_url).headers(out_headers.clone());
+    if !body_bytes.is_empty() {
+        req_builder = req_builder.body(body_bytes.to_vec());
+    }
+    let coalesce = proxy_coalesce::eligible(&method, body_bytes.is_empty())
+        && STATE.read().map(|s| s.policy.coalesce_get).unwrap_or(false);
+    let (upstream, shared) = if coalesce {
+        let key = proxy_coalesce::key(&method, &target_url, &out_headers);
+        proxy_coalesce::shared_fetch(key, req_builder).await
     } else {
-        req_builder.body(body_bytes.to_vec()).send().await
+        match req_builder.send().await {
+            // Streams go straight through; a 402 is buffered so it can be settled.
+            Ok(resp) if resp.status().as_u16() != 402 && proxy_stream::is_streaming(resp.headers()) => {
+                let msg = match &decision.matched_rule {
+                    Some(rule) => format!("{} {} ({}) [streamed]", method, target_url, rule),
+                    None => format!("{} {} [streamed]", method, target_url),
+                };
+                evidence::push_structured(
+                    "allowed",
+                    &msg,
+                    EvidenceFields {
+                        domain: Some(host.clone()),
+                        method: Some(method.to_string()),
+                        status: Some(resp.status().as_u16()),
+                        latency_ms: Some(started.elapsed().as_millis() as u64),
+                        alias: injected_alias.clone(),
+                        ..Default::default()
+                    },
+                );
+                return proxy_stream::respond(resp, &redact_patterns);
+            }
+            Ok(resp) => (Ok(proxy_coalesce::buffer(resp).await), false),
+            Err(e) => (Err(e.to_string()), false),
+        }
     };
 
     match upstream {
-        Ok(resp) => {
-            let status = resp.status();
-            let headers_vec: Vec<(String, String)> = resp
-                .headers()
-                .iter()
-                .map(|(k, v)| (k.as_str().to_string(), v.to_str().unwrap_or("").to_string()))
-                .collect();
-            let bytes = resp.bytes().await.unwrap_or_default();
+        Ok(Fetched { status, headers: headers_vec, bytes }) => {
             if status.as_u16() == 402 {
                 if let Some(intent) = crate::x402::parse_402_required(&headers_vec, &bytes) {
                     let id = crate::x402::record_pending(intent.clone());
-                    evidence::push(
+                    evidence::push_structured(
                         "payment",
                         &format!("402 pending {} cents -> {} [{}]", intent.amount_cents, intent.recipient, id),
+                        EvidenceFields {
+                            domain: Some(host.clone()),
+                            method: Some(method.to_string()),
+                            status: Some(402),
+                            cost_cents: Some(intent.amount_cents),
+                            alias: injected_alias.clone(),
+                            ..Default::default()
+                        },
                     );
 
-                    let should_auto_settle = {
+                    let (auto_settle, spend_cap) = {
                         let guard = STATE.read().expect("state read");
-                        let p = &guard.policy;
-                        p.auto_settle_402
-                            && (p.spend_cap_cents.is_none() || intent.amount_cents <= p.spend_cap_cents.unwrap_or(0))
+                        (guard.policy.auto_settle_402, guard.policy.spend_cap_cents)
+                    };
+                    let should_auto_settle = match (auto_settle, spend_cap) {
+                        (false, _) => false,
+                        (true, None) => true,
+                        (true, Some(cap)) => {
+                            let estimate = crate::x402::estimate_intent(&intent);
+                            let fits = estimate.within_cap(cap);
+                            if !fits {
+                                evidence::push(
+                                    "warning",
+                                    &format!(
+                                        "402 not auto-settled: all-in cost {} cents is not within the {}-cent cap{}",
+                                        estimate.total_cents,
+                                        cap,
+                                        estimate.note.map(|n| format!(" ({})", n)).unwrap_or_default()
+                                    ),
+                                );
+                            }
+                            fits
+                        }
                     };
 
                     if should_auto_settle {
@@ -242,16 +584,26 @@ async fn proxy_handler(req: Request) -> Response {
                                     if let Ok(retry) = retry_resp {
                                         let retry_status = retry.status();
                                         if retry_status.is_success() {
-                                            evidence::push(
-                                                "payment",
-                                                &format!("402 settled {} cents -> {}", intent.amount_cents, intent.recipient),
-                                            );
                                             let retry_headers_vec: Vec<(String, String)> = retry
                                                 .headers()
                                                 .iter()
                                                 .map(|(k, v)| (k.as_str().to_string(), v.to_str().unwrap_or("").to_string()))
                                                 .collect();
                                             let retry_bytes = retry.bytes().await.unwrap_or_default();
+                                            evidence::push_structured(
+                                                "payment",
+                                                &format!("402 settled {} cents -> {}", intent.amount_cents, intent.recipient),
+                                                EvidenceFields {
+                                                    domain: Some(host.clone()),
+                                                    method: Some(method.to_string()),
+                                                    status: Some(retry_status.as_u16()),
+                                                    cost_cents: Some(intent.amount_cents),
+                                                    bytes: Some(retry_bytes.len() as u64),
+                                                    latency_ms: Some(started.elapsed().as_millis() as u64),
+                                                    alias: injected_alias.clone(),
+                                                    ..Default::default()
+                                                },
+                                            );
                                             let retry_filtered = redact_body(&retry_bytes, &redact_patterns);
                                             let mut retry_builder = Response::builder().status(retry_status);
                                             for (k, v) in &retry_headers_vec {
@@ -273,7 +625,30 @@ async fn proxy_handler(req: Request) -> Response {
                     }
                 }
             } else {
-                evidence::push("allowed", &format!("{} {}", method, target_url));
+                let mut msg = match &decision.matched_rule {
+                    Some(rule) => format!("{} {} ({})", method, target_url, rule),
+                    None => format!("{} {}", method, target_url),
+                };
+                if shared {
+                    msg.push_str(" [shared response]");
+                } else if status.is_success() {
+                    if let Some(cents) = proxy_cost::record(&body_bytes, &bytes) {
+                        msg.push_str(&format!(" [~{:.3}¢]", cents));
+                    }
+                }
+                evidence::push_structured(
+                    "allowed",
+                    &msg,
+                    EvidenceFields {
+                        domain: Some(host.clone()),
+                        method: Some(method.to_string()),
+                        status: Some(status.as_u16()),
+                        bytes: Some(bytes.len() as u64),
+                        latency_ms: Some(started.elapsed().as_millis() as u64),
+                        alias: injected_alias.clone(),
+                        ..Default::default()
+                    },
+                );
             }
             let filtered = redact_body(&bytes, &redact_patterns);
             let mut resp_builder = Response::builder().status(status);
@@ -289,14 +664,70 @@ async fn proxy_handler(req: Request) -> Response {
                 .body(Body::from(filtered))
                 .unwrap_or_else(|_| Response::new(Body::from("internal error")))
         }
-        Err(e) => (
-            StatusCode::BAD_GATEWAY,
-            format!("Upstream error: {}", e),
-        )
-            .into_response(),
+        Err(e) => {
+            evidence::push_structured(
+                "error",
+                &format!("Upstream error for {} {}: {}", method, target_url, e),
+                EvidenceFields {
+                    domain: Some(host.clone()),
+                    method: Some(method.to_string()),
+                    status: Some(StatusCode::BAD_GATEWAY.as_u16()),
+                    latency_ms: Some(started.elapsed().as_millis() as u64),
+                    check: Some("upstream".to_string()),
+                    alias: injected_alias,
+                    ..Default::default()
+                },
+            );
+            (
+                StatusCode::BAD_GATEWAY,
+                format!("Upstream error: {}", e),
+            )
+                .into_response()
+        }
     }
 }
 
+/// Refuse a request the policy denied and record it as blocked evidence.
+pub(crate) fn denied(decision: &PolicyDecision, host: &str, method: &str) -> Response {
+    let msg = match &decision.matched_rule {
+        Some(rule) => format!("Vault-0 policy denied: {} ({})", decision.reason, rule),
+        None => format!("Vault-0 policy denied: {}", decision.reason),
+    };
+    evidence::push_structured(
+        "blocked",
+        &msg,
+        EvidenceFields {
+            domain: Some(host.to_string()),
+            method: Some(method.to_string()),
+            status: Some(decision.status),
+            ..Default::default()
+        },
+    );
+    let status = StatusCode::from_u16(decision.status).unwrap_or(StatusCode::FORBIDDEN);
+    (status, msg).into_response()
+}
+
+/// Reject an MCP request, recording which guard fired, the host and the address
+/// it resolved to so the block can be audited from the evidence log.
+fn mcp_blocked(check: &str, msg: &str, host: &str, ip: Option<IpAddr>, status: StatusCode) -> Response {
+    let detail = match ip {
+        Some(ip) => format!("{} [{} -> {}]", msg, host, ip),
+        None => format!("{} [{}]", msg, host),
+    };
+    evidence::push_structured(
+        "blocked",
+        &detail,
+        EvidenceFields {
+            domain: Some(host.to_string()),
+            status: Some(status.as_u16()),
+            check: Some(check.to_string()),
+            resolved_ip: ip.map(|ip| ip.to_string()),
+            ..Default::default()
+        },
+    );
+    (status, msg.to_string()).into_response()
+}
+
 fn build_full_uri(uri: &Uri, host: &str) -> String {
     if let Some(s) = uri.path().strip_prefix("https://").or_else(|| uri.path().strip_prefix("http://")) {
         if s.contains('/') || s.contains('?') {
@@ -318,24 +749,70 @@ fn build_full_uri(uri: &Uri, host: &str) -> String {
     }
 }
 
-fn alias_for_host(host: &str) -> Option<String> {
-    let alias = match host {
-        h if h.contains("openai.com") => "openai",
-        h if h.contains("anthropic.com") => "anthropic",
-        _ => return None,
-    };
-    Some(alias.to_string())
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
-fn redact_body(body: &[u8], patterns: &[String]) -> Vec<u8> {
-    let mut text = match std::str::from_utf8(body) {
-        Ok(t) => t.to_string(),
-        Err(_) => return body.to_vec(),
-    };
-    for pat in patterns {
-        if let Ok(re) = regex::Regex::new(pat) {
-            text = re.replace_all(&text, "[REDACTED]").to_string();
+    /// Serve `body` on a loopback port, encoded with whatever the request's
+    /// Accept-Encoding asks for first: gzip, or zstd (opaque bytes, since
+    /// reqwest here cannot decode it). Returns the URL.
+    async fn serve_encoded(body: &'static [u8]) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut sock, _)) = listener.accept().await {
+                let mut buf = [0u8; 4096];
+                let n = sock.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]).to_ascii_lowercase();
+                let accept = request
+                    .lines()
+                    .find_map(|l| l.strip_prefix("accept-encoding:"))
+                    .unwrap_or("")
+                    .trim()
+                    .to_string();
+                let (encoding, payload) = if accept.starts_with("zstd") {
+                    ("Content-Encoding: zstd\r\n", b"\x28\xb5\x2f\xfd opaque".to_vec())
+                } else if accept.starts_with("gzip") {
+                    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                    encoder.write_all(body).unwrap();
+                    ("Content-Encoding: gzip\r\n", encoder.finish().unwrap())
+                } else {
+                    ("", body.to_vec())
+                };
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n",
+                    encoding,
+                    payload.len()
+                );
+                sock.write_all(head.as_bytes()).await.unwrap();
+                sock.write_all(&payload).await.unwrap();
+            }
+        });
+        format!("http://{}/v1/echo", addr)
+    }
+
+    #[tokio::test]
+    async fn agent_accept_encoding_is_replaced_so_the_body_can_be_redacted() {
+        let url = serve_encoded(br#"{"key":"sk-ABCDEFGHIJKLMNOPQRSTUVWXYZ0123"}"#).await;
+        if let Ok(mut state) = STATE.write() {
+            state.policy.output_redact_patterns = vec!["sk-[a-zA-Z0-9]{20,}".to_string()];
+        }
+        // gzip is decoded either way; zstd only comes back readable because
+        // the agent's header never reaches the upstream.
+        for accept in ["gzip", "zstd"] {
+            let req = Request::builder()
+                .method(Method::GET)
+                .uri(url.as_str())
+                .header("accept-encoding", accept)
+                .body(Body::empty())
+                .unwrap();
+            let resp = proxy_handler(req).await;
+            assert_eq!(resp.status(), StatusCode::OK, "{}", accept);
+            assert!(resp.headers().get("content-encoding").is_none(), "{}", accept);
+            let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+            assert_eq!(String::from_utf8_lossy(&bytes), r#"{"key":"[REDACTED]"}"#, "{}", accept);
         }
     }
-    text.into_bytes()
 }
@@ -0,0 +1,100 @@
+//! Request coalescing: identical GET/HEAD requests in flight at the same time
+//! share one upstream call. Enabled by the policy's `coalesce_get` flag.
+
+use axum::body::Bytes;
+use futures_util::future::{BoxFuture, FutureExt, Shared};
+use once_cell::sync::Lazy;
+use reqwest::header::HeaderMap;
+use reqwest::{Method, RequestBuilder, StatusCode};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+/// Per-request tracing headers that don't change the response and would
+/// otherwise keep duplicates apart.
+const IGNORED_HEADERS: [&str; 4] = ["x-request-id", "traceparent", "tracestate", "x-stainless-retry-count"];
+
+/// Upstream response, buffered so it can be handed to every waiting request.
+#[derive(Clone)]
+pub struct Fetched {
+    pub status: StatusCode,
+    pub headers: Vec<(String, String)>,
+    pub bytes: Bytes,
+}
+
+type SharedFetch = Shared<BoxFuture<'static, Result<Fetched, String>>>;
+
+static IN_FLIGHT: Lazy<Mutex<HashMap<String, SharedFetch>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Send the request and buffer the whole response.
+pub async fn fetch(request: RequestBuilder) -> Result<Fetched, String> {
+    let resp = request.send().await.map_err(|e| e.to_string())?;
+    Ok(buffer(resp).await)
+}
+
+/// Read an upstream response fully into memory.
+pub async fn buffer(resp: reqwest::Response) -> Fetched {
+    let status = resp.status();
+    let headers = resp
+        .headers()
+        .iter()
+        .map(|(k, v)| (k.as_str().to_string(), v.to_str().unwrap_or("").to_string()))
+        .collect();
+    let bytes = resp.bytes().await.unwrap_or_default();
+    Fetched { status, headers, bytes }
+}
+
+/// Whether a request may share a response: safe method, no body.
+pub fn eligible(method: &Method, body_empty: bool) -> bool {
+    body_empty && (method == Method::GET || method == Method::HEAD)
+}
+
+/// Identity of a request for coalescing. Credentials are part of the key, so
+/// requests only share a response when they'd carry the same auth upstream;
+/// the key is hashed so it doesn't hold them in plain text.
+pub fn key(method: &Method, url: &str, headers: &HeaderMap) -> String {
+    let mut pairs: Vec<(String, &[u8])> = headers
+        .iter()
+        .map(|(k, v)| (k.as_str().to_ascii_lowercase(), v.as_bytes()))
+        .filter(|(k, _)| !IGNORED_HEADERS.contains(&k.as_str()))
+        .collect();
+    pairs.sort();
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_str());
+    hasher.update(b" ");
+    hasher.update(url);
+    for (k, v) in pairs {
+        hasher.update(b"\n");
+        hasher.update(k);
+        hasher.update(b":");
+        hasher.update(v);
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// The in-flight call for `key`, and whether it was already running.
+fn join_or_start(key: String, request: RequestBuilder) -> (SharedFetch, bool) {
+    let mut in_flight = IN_FLIGHT.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(call) = in_flight.get(&key) {
+        return (call.clone(), true);
+    }
+    let entry_key = key.clone();
+    // The entry is cleared by the call itself, so it goes away even if the
+    // request that started it is cancelled.
+    let call = async move {
+        let result = fetch(request).await;
+        IN_FLIGHT.lock().unwrap_or_else(PoisonError::into_inner).remove(&entry_key);
+        result
+    }
+    .boxed()
+    .shared();
+    in_flight.insert(key, call.clone());
+    (call, false)
+}
+
+/// Join an identical request already in flight, or start one others can join.
+/// Returns the response and whether it came from another request's call.
+pub async fn shared_fetch(key: String, request: RequestBuilder) -> (Result<Fetched, String>, bool) {
+    let (call, joined) = join_or_start(key, request);
+    (call.await, joined)
+}
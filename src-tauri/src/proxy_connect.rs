@@ -0,0 +1,154 @@
+//! HTTPS `CONNECT` tunnels, which is how agents with `HTTPS_PROXY` set reach
+//! TLS upstreams. The target host goes through the same policy check as plain
+//! requests and allowed tunnels are spliced byte for byte. The traffic inside
+//! is end-to-end TLS, so no key is injected and nothing is redacted; path rules
+//! see `/` and method rules see `CONNECT`, so a host with a method allow list
+//! must include CONNECT to be tunnelled. Tunnels only reach port 443 unless the
+//! policy lists more in `connect_ports`, and never private or internal
+//! addresses: the target is resolved and checked once, then dialled at that
+//! address.
+
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use hyper_util::rt::TokioIo;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+
+use crate::evidence::{self, EvidenceFields};
+use crate::{mcp_guard, policy, proxy};
+
+/// The only port tunnels may reach unless the policy's `connect_ports` adds more.
+const DEFAULT_CONNECT_PORT: u16 = 443;
+
+/// Dial timeout when `upstream_timeout_secs` is not set.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Answer a `CONNECT host:port` request: check policy, dial the target, then
+/// splice the upgraded client connection to it until either side closes.
+pub(crate) async fn tunnel(req: Request) -> Response {
+    let Some(authority) = req.uri().authority().cloned() else {
+        return (StatusCode::BAD_REQUEST, "CONNECT requires host:port").into_response();
+    };
+    let host = authority.host().to_string();
+    let port = authority.port_u16().unwrap_or(443);
+
+    let decision = match policy::evaluate_request(&host, "/", "CONNECT") {
+        Ok(d) => d,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    };
+    if !decision.allowed {
+        return proxy::denied(&decision, &host, "CONNECT");
+    }
+    if let Some(refused) = crate::proxy_cost::over_cap(&host) {
+        return refused;
+    }
+    let port_allowed = port == DEFAULT_CONNECT_PORT
+        || proxy::state().read().map(|s| s.policy.connect_ports.contains(&port)).unwrap_or(false);
+    if !port_allowed {
+        let msg = format!("CONNECT to port {} is not allowed by policy (connect_ports)", port);
+        return refused(&host, "connect_port", &msg, None);
+    }
+    let dial_host = host.trim_start_matches('[').trim_end_matches(']').to_string();
+    if dial_host.eq_ignore_ascii_case("localhost") {
+        return refused(&host, "connect_ssrf", "CONNECT to localhost is not allowed", None);
+    }
+    // Dial the address that passed the check, not a second lookup a rebinding
+    // DNS server could answer differently.
+    let pinned = match mcp_guard::resolve_checked(authority.as_str(), port).await {
+        Ok(addr) => addr,
+        Err(blocked) => return refused(&host, "connect_ssrf", &format!("CONNECT SSRF: {}", blocked.reason), blocked.ip),
+    };
+
+    let started = Instant::now();
+    let timeout = crate::settings::get().upstream_timeout_secs.map(Duration::from_secs).unwrap_or(CONNECT_TIMEOUT);
+    let dial = async {
+        match pinned {
+            Some(addr) => TcpStream::connect(addr).await,
+            None => TcpStream::connect((dial_host.as_str(), port)).await,
+        }
+    };
+    let upstream = match tokio::time::timeout(timeout, dial).await {
+        Ok(Ok(stream)) => stream,
+        Ok(Err(e)) => return upstream_error(&host, port, &e.to_string(), started),
+        Err(_) => return upstream_error(&host, port, "connect timed out", started),
+    };
+    let msg = match &decision.matched_rule {
+        Some(rule) => format!("CONNECT {}:{} ({})", host, port, rule),
+        None => format!("CONNECT {}:{}", host, port),
+    };
+    evidence::push_structured(
+        "allowed",
+        &msg,
+        EvidenceFields {
+            domain: Some(host.clone()),
+            method: Some("CONNECT".to_string()),
+            status: Some(StatusCode::OK.as_u16()),
+            latency_ms: Some(started.elapsed().as_millis() as u64),
+            resolved_ip: upstream.peer_addr().ok().map(|a| a.ip().to_string()),
+            ..Default::default()
+        },
+    );
+
+    tokio::spawn(async move {
+        let mut upstream = upstream;
+        let upgraded = match hyper::upgrade::on(req).await {
+            Ok(upgraded) => upgraded,
+            Err(e) => {
+                tracing::warn!("CONNECT {}:{} upgrade failed: {}", host, port, e);
+                return;
+            }
+        };
+        let mut client = TokioIo::new(upgraded);
+        match tokio::io::copy_bidirectional(&mut client, &mut upstream).await {
+            Ok((sent, received)) => tracing::info!(
+                "Tunnel to {}:{} closed after {}s ({} bytes sent, {} received)",
+                host,
+                port,
+                started.elapsed().as_secs(),
+                sent,
+                received
+            ),
+            Err(e) => tracing::info!("Tunnel to {}:{} ended: {}", host, port, e),
+        }
+    });
+    // An unsized body: axum adds `content-length: 0` to a sized empty one,
+    // which hyper refuses to send on a successful CONNECT.
+    Response::new(Body::from_stream(futures_util::stream::empty::<Result<Vec<u8>, std::io::Error>>()))
+}
+
+/// A 403 for a tunnel the port or SSRF checks refused, recorded as blocked evidence.
+fn refused(host: &str, check: &str, msg: &str, ip: Option<IpAddr>) -> Response {
+    evidence::push_structured(
+        "blocked",
+        msg,
+        EvidenceFields {
+            domain: Some(host.to_string()),
+            method: Some("CONNECT".to_string()),
+            status: Some(StatusCode::FORBIDDEN.as_u16()),
+            check: Some(check.to_string()),
+            resolved_ip: ip.map(|ip| ip.to_string()),
+            ..Default::default()
+        },
+    );
+    (StatusCode::FORBIDDEN, msg.to_string()).into_response()
+}
+
+fn upstream_error(host: &str, port: u16, error: &str, started: Instant) -> Response {
+    let msg = format!("Upstream error for CONNECT {}:{}: {}", host, port, error);
+    evidence::push_structured(
+        "error",
+        &msg,
+        EvidenceFields {
+            domain: Some(host.to_string()),
+            method: Some("CONNECT".to_string()),
+            status: Some(StatusCode::BAD_GATEWAY.as_u16()),
+            latency_ms: Some(started.elapsed().as_millis() as u64),
+            check: Some("upstream".to_string()),
+            ..Default::default()
+        },
+    );
+    (StatusCode::BAD_GATEWAY, msg).into_response()
+}
@@ -0,0 +1,115 @@
+//! Approximate cost of proxied model calls, from the token usage providers
+//! report in the response body, counted against the policy's `spend_cap_cents`.
+//! Streamed responses and tunnelled (CONNECT) traffic can't be read, so they
+//! are not counted; models missing from the price table aren't either.
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+
+use crate::evidence::{self, EvidenceFields};
+use crate::proxy;
+
+/// US cents per million (input, output) tokens, by model name prefix. The
+/// longest matching prefix wins, so `gpt-4o-mini` is not priced as `gpt-4o`.
+const PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-3.5-turbo", 50.0, 150.0),
+    ("gpt-4o-mini", 15.0, 60.0),
+    ("gpt-4o", 250.0, 1000.0),
+    ("gpt-4.1-nano", 10.0, 40.0),
+    ("gpt-4.1-mini", 40.0, 160.0),
+    ("gpt-4.1", 200.0, 800.0),
+    ("o3-mini", 110.0, 440.0),
+    ("o4-mini", 110.0, 440.0),
+    ("o3", 200.0, 800.0),
+    ("claude-3-haiku", 25.0, 125.0),
+    ("claude-3-5-haiku", 80.0, 400.0),
+    ("claude-3-5-sonnet", 300.0, 1500.0),
+    ("claude-3-7-sonnet", 300.0, 1500.0),
+    ("claude-sonnet-4", 300.0, 1500.0),
+    ("claude-3-opus", 1500.0, 7500.0),
+    ("claude-opus-4", 1500.0, 7500.0),
+];
+
+#[derive(Debug, Serialize)]
+pub struct ProxySpend {
+    pub spend_cents: f64,
+    pub spend_cap_cents: Option<u64>,
+    /// Responses whose usage was priced.
+    pub priced_requests: u64,
+}
+
+fn price_for(model: &str) -> Option<(f64, f64)> {
+    PRICES
+        .iter()
+        .filter(|(prefix, _, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _, _)| prefix.len())
+        .map(|(_, input, output)| (*input, *output))
+}
+
+/// `(model, cents)` for a response carrying OpenAI (`prompt_tokens` /
+/// `completion_tokens`) or Anthropic (`input_tokens` / `output_tokens`) usage.
+/// The model comes from the response, else the request body.
+fn cost_of(request_body: &[u8], response_body: &[u8]) -> Option<(String, f64)> {
+    let resp: serde_json::Value = serde_json::from_slice(response_body).ok()?;
+    let usage = resp.get("usage")?;
+    let tokens = |keys: [&str; 2]| keys.iter().find_map(|k| usage.get(k).and_then(|v| v.as_u64())).unwrap_or(0);
+    let input = tokens(["prompt_tokens", "input_tokens"]);
+    let output = tokens(["completion_tokens", "output_tokens"]);
+    let model = resp
+        .get("model")
+        .and_then(|m| m.as_str())
+        .map(String::from)
+        .or_else(|| {
+            let req: serde_json::Value = serde_json::from_slice(request_body).ok()?;
+            req.get("model").and_then(|m| m.as_str()).map(String::from)
+        })?;
+    let (input_price, output_price) = price_for(&model)?;
+    let cents = (input as f64 * input_price + output as f64 * output_price) / 1_000_000.0;
+    Some((model, cents))
+}
+
+/// Price a successful response and add it to the running spend. Returns the
+/// cost in cents when the response could be priced.
+pub(crate) fn record(request_body: &[u8], response_body: &[u8]) -> Option<f64> {
+    let (model, cents) = cost_of(request_body, response_body)?;
+    let mut state = proxy::state().write().ok()?;
+    state.spend_cents += cents;
+    state.priced_requests += 1;
+    tracing::debug!("{} call cost ~{:.4} cents (total {:.2})", model, cents, state.spend_cents);
+    Some(cents)
+}
+
+/// A 402 refusal once the running spend has reached `spend_cap_cents`.
+pub(crate) fn over_cap(host: &str) -> Option<Response> {
+    let (spend, cap) = {
+        let state = proxy::state().read().ok()?;
+        (state.spend_cents, state.policy.spend_cap_cents?)
+    };
+    if spend < cap as f64 {
+        return None;
+    }
+    let msg = format!("Vault-0 spend cap reached: {:.2} of {} cents spent", spend, cap);
+    evidence::push_structured(
+        "blocked",
+        &msg,
+        EvidenceFields {
+            domain: Some(host.to_string()),
+            status: Some(StatusCode::PAYMENT_REQUIRED.as_u16()),
+            check: Some("spend_cap".to_string()),
+            ..Default::default()
+        },
+    );
+    Some((StatusCode::PAYMENT_REQUIRED, msg).into_response())
+}
+
+/// Approximate spend on model calls since the app started.
+#[tauri::command]
+pub fn get_proxy_spend() -> Result<ProxySpend, String> {
+    let state = proxy::state().read().map_err(|_| "state lock")?;
+    Ok(ProxySpend {
+        spend_cents: state.spend_cents,
+        spend_cap_cents: state.policy.spend_cap_cents,
+        priced_requests: state.priced_requests,
+    })
+}
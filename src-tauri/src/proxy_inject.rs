@@ -0,0 +1,217 @@
+//! Which vault key the proxy injects into a request, how it is written, and
+//! whether it has expired. The policy's `inject_rules` are checked first, then
+//! the built-in provider mappings.
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+
+use crate::evidence::{self, EvidenceFields};
+use crate::policy::{host_matches, InjectRule, Policy};
+use crate::proxy;
+
+/// Providers the proxy knows without any `inject_rules`: host pattern, the
+/// vault alias injected for it, and the header it goes in (`None` for
+/// `Authorization: Bearer`). Anthropic takes the raw key in `x-api-key`.
+const BUILT_IN: &[(&str, &str, Option<&str>)] =
+    &[("openai.com", "openai", None), ("anthropic.com", "anthropic", Some("x-api-key"))];
+
+/// API version sent to Anthropic when the agent doesn't name one; requests
+/// without it are rejected.
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Where an injected key goes: header name, optional scheme prefix, and
+/// headers the provider requires alongside it, added only if the agent didn't
+/// send them.
+#[derive(Debug, Clone)]
+pub(crate) struct InjectHeader {
+    pub name: HeaderName,
+    pub scheme: Option<String>,
+    pub companions: Vec<(HeaderName, HeaderValue)>,
+}
+
+impl Default for InjectHeader {
+    fn default() -> Self {
+        InjectHeader { name: AUTHORIZATION, scheme: Some("Bearer".to_string()), companions: Vec::new() }
+    }
+}
+
+impl InjectHeader {
+    fn from_rule(rule: &InjectRule) -> Self {
+        let name = rule
+            .header
+            .as_deref()
+            .and_then(|h| HeaderName::from_bytes(h.trim().as_bytes()).ok())
+            .unwrap_or(AUTHORIZATION);
+        let scheme = match &rule.scheme {
+            Some(s) => Some(s.trim().to_string()).filter(|s| !s.is_empty()),
+            None if name == AUTHORIZATION => Some("Bearer".to_string()),
+            None => None,
+        };
+        InjectHeader { name, scheme, companions: Vec::new() }
+    }
+
+    fn built_in(alias: &str, header: Option<&'static str>) -> Self {
+        let Some(header) = header else { return InjectHeader::default() };
+        let companions = match alias {
+            "anthropic" => vec![(
+                HeaderName::from_static("anthropic-version"),
+                HeaderValue::from_static(ANTHROPIC_VERSION),
+            )],
+            _ => Vec::new(),
+        };
+        InjectHeader { name: HeaderName::from_static(header), scheme: None, companions }
+    }
+}
+
+/// Alias to inject for `host` and how to write it, if any rule or built-in
+/// mapping covers the host.
+pub(crate) fn alias_for_host(policy: &Policy, host: &str) -> Option<(String, InjectHeader)> {
+    if let Some(rule) = policy.inject_rules.iter().find(|r| host_matches(host, &r.host)) {
+        return Some((rule.alias.clone(), InjectHeader::from_rule(rule)));
+    }
+    BUILT_IN
+        .iter()
+        .find(|(pattern, _, _)| host_matches(host, pattern))
+        .map(|(_, alias, header)| (alias.to_string(), InjectHeader::built_in(alias, *header)))
+}
+
+/// Header format for an alias the agent named in `X-Vault0-Alias`. Refused
+/// unless the policy sets `allow_alias_header` and an inject rule or built-in
+/// mapping assigns that alias to `host`, so an agent can't send one provider's
+/// key to another host.
+pub(crate) fn header_for_alias(policy: &Policy, host: &str, alias: &str) -> Result<InjectHeader, String> {
+    if !policy.allow_alias_header {
+        return Err("X-Vault0-Alias is disabled by policy (allow_alias_header)".to_string());
+    }
+    if let Some(rule) = policy.inject_rules.iter().find(|r| r.alias == alias && host_matches(host, &r.host)) {
+        return Ok(InjectHeader::from_rule(rule));
+    }
+    BUILT_IN
+        .iter()
+        .find(|(pattern, built_in, _)| *built_in == alias && host_matches(host, pattern))
+        .map(|(_, built_in, header)| InjectHeader::built_in(built_in, *header))
+        .ok_or_else(|| format!("X-Vault0-Alias '{}' is not mapped to {}", alias, host))
+}
+
+/// A 403 for a refused `X-Vault0-Alias`, recorded as blocked evidence.
+pub(crate) fn alias_refused(alias: &str, host: &str, method: &str, reason: String) -> Response {
+    evidence::push_structured(
+        "blocked",
+        &reason,
+        EvidenceFields {
+            domain: Some(host.to_string()),
+            method: Some(method.to_string()),
+            status: Some(StatusCode::FORBIDDEN.as_u16()),
+            check: Some("alias_header".to_string()),
+            alias: Some(alias.to_string()),
+            ..Default::default()
+        },
+    );
+    (StatusCode::FORBIDDEN, reason).into_response()
+}
+
+/// Write `key` into `headers` as `header` describes, replacing any value the
+/// agent sent under that name.
+pub(crate) fn apply(headers: &mut HeaderMap, header: &InjectHeader, key: &str) {
+    for (name, value) in &header.companions {
+        if !headers.contains_key(name) {
+            headers.insert(name.clone(), value.clone());
+        }
+    }
+    let value = match &header.scheme {
+        Some(scheme) => format!("{} {}", scheme, key),
+        None => key.to_string(),
+    };
+    match HeaderValue::from_str(&value) {
+        Ok(value) => {
+            headers.insert(header.name.clone(), value);
+        }
+        Err(_) => tracing::warn!("Injected key for {} is not a valid header value", header.name),
+    }
+}
+
+/// A 403 refusal when the vault key under `alias` is past its `expires_at`;
+/// forwarding a dead credential would only fail upstream.
+pub(crate) fn expired(alias: &str, host: &str) -> Option<Response> {
+    let expires_at = *proxy::state().read().ok()?.expires.get(alias)?;
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+    if now < expires_at {
+        return None;
+    }
+    let msg = format!("Vault key '{}' expired at {} and was not injected for {}", alias, expires_at, host);
+    evidence::push_structured(
+        "blocked",
+        &msg,
+        EvidenceFields {
+            domain: Some(host.to_string()),
+            status: Some(StatusCode::FORBIDDEN.as_u16()),
+            check: Some("key_expired".to_string()),
+            alias: Some(alias.to_string()),
+            ..Default::default()
+        },
+    );
+    Some((StatusCode::FORBIDDEN, msg).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn injected(policy: &Policy, host: &str) -> (String, HeaderMap) {
+        let (alias, header) = alias_for_host(policy, host).expect("host has an injection mapping");
+        let mut headers = HeaderMap::new();
+        apply(&mut headers, &header, "sk-test-key");
+        (alias, headers)
+    }
+
+    #[test]
+    fn anthropic_gets_x_api_key_and_no_authorization() {
+        let (alias, headers) = injected(&Policy::default(), "api.anthropic.com");
+        assert_eq!(alias, "anthropic");
+        assert_eq!(headers.get("x-api-key").unwrap(), "sk-test-key");
+        assert_eq!(headers.get("anthropic-version").unwrap(), ANTHROPIC_VERSION);
+        assert!(headers.get(AUTHORIZATION).is_none());
+    }
+
+    #[test]
+    fn openai_gets_bearer_authorization() {
+        let (alias, headers) = injected(&Policy::default(), "api.openai.com");
+        assert_eq!(alias, "openai");
+        assert_eq!(headers.get(AUTHORIZATION).unwrap(), "Bearer sk-test-key");
+        assert!(headers.get("x-api-key").is_none());
+    }
+
+    #[test]
+    fn alias_header_needs_policy_opt_in_and_a_mapping_to_the_host() {
+        let mut policy = Policy {
+            inject_rules: vec![InjectRule { host: "openai.com".into(), alias: "openai-team".into(), ..Default::default() }],
+            ..Policy::default()
+        };
+        assert!(header_for_alias(&policy, "api.openai.com", "openai-team").is_err());
+        policy.allow_alias_header = true;
+        assert!(header_for_alias(&policy, "api.openai.com", "openai-team").is_ok());
+        assert!(header_for_alias(&policy, "api.openai.com", "openai").is_ok());
+        let anthropic = header_for_alias(&policy, "api.anthropic.com", "anthropic").unwrap();
+        assert_eq!(anthropic.name.as_str(), "x-api-key");
+        assert!(header_for_alias(&policy, "evil.example", "openai").is_err());
+        assert!(header_for_alias(&policy, "api.anthropic.com", "openai-team").is_err());
+    }
+
+    #[test]
+    fn inject_rules_take_precedence_over_built_ins() {
+        let policy = Policy {
+            inject_rules: vec![InjectRule {
+                host: "anthropic.com".into(),
+                alias: "claude-team".into(),
+                header: Some("X-Api-Key".into()),
+                scheme: None,
+            }],
+            ..Policy::default()
+        };
+        let (alias, headers) = injected(&policy, "api.anthropic.com");
+        assert_eq!(alias, "claude-team");
+        assert_eq!(headers.get("x-api-key").unwrap(), "sk-test-key");
+        assert!(headers.get(AUTHORIZATION).is_none());
+    }
+}
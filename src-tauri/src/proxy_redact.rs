@@ -0,0 +1,47 @@
+//! Redaction of proxied bodies: `output_redact_patterns` on responses and
+//! `input_redact_patterns` on requests. Responses compressed with gzip, brotli
+//! or deflate are decoded by the client before they get here (and go back to
+//! the agent uncompressed); bodies that still aren't UTF-8 pass through
+//! unchanged.
+
+use regex::Regex;
+
+/// Compile the policy's patterns, skipping any that don't parse.
+pub(crate) fn compile(patterns: &[String]) -> Vec<Regex> {
+    patterns.iter().filter_map(|p| Regex::new(p).ok()).collect()
+}
+
+/// Replace every match in `body` with `[REDACTED]`.
+pub(crate) fn redact(body: &[u8], patterns: &[Regex]) -> Vec<u8> {
+    let mut text = match std::str::from_utf8(body) {
+        Ok(t) => t.to_string(),
+        Err(_) => return body.to_vec(),
+    };
+    for re in patterns {
+        text = re.replace_all(&text, "[REDACTED]").to_string();
+    }
+    text.into_bytes()
+}
+
+/// Compile `patterns` and redact one buffered body.
+pub(crate) fn redact_body(body: &[u8], patterns: &[String]) -> Vec<u8> {
+    redact(body, &compile(patterns))
+}
+
+/// Redact an outgoing request body. Returns the body and each pattern that
+/// matched, so the caller can record what was scrubbed.
+pub(crate) fn redact_request(body: &[u8], patterns: &[String]) -> (Vec<u8>, Vec<String>) {
+    let Ok(text) = std::str::from_utf8(body) else {
+        return (body.to_vec(), Vec::new());
+    };
+    let mut text = text.to_string();
+    let mut matched = Vec::new();
+    for pat in patterns {
+        let Ok(re) = Regex::new(pat) else { continue };
+        if re.is_match(&text) {
+            text = re.replace_all(&text, "[REDACTED]").to_string();
+            matched.push(pat.clone());
+        }
+    }
+    (text.into_bytes(), matched)
+}
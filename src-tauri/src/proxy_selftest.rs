@@ -0,0 +1,237 @@
+//! One-click "is my setup correct?" check. Sends real requests through the
+//! running proxy: one to a throwaway local echo server to confirm a vault key
+//! is injected, and one to a host the policy blocks to confirm it is refused.
+//! The echo request is recognised by a one-time nonce and let through on its
+//! own, so the shared policy is never loosened while agents are running.
+
+use axum::http::HeaderMap;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+use crate::proxy;
+
+const ECHO_PATH: &str = "/vault0-self-test";
+const ECHO_HOST: &str = "127.0.0.1";
+/// Carries the nonce of the self-test's echo request. Consumed by the proxy,
+/// never forwarded.
+pub(crate) const NONCE_HEADER: &str = "x-vault0-self-test";
+
+/// The echo request in flight: its port, nonce and the alias to inject.
+struct EchoRoute {
+    port: u16,
+    nonce: String,
+    alias: String,
+}
+
+static ROUTE: Lazy<Mutex<Option<EchoRoute>>> = Lazy::new(|| Mutex::new(None));
+/// Used as the blocked target when the policy has an allow list but no block list.
+const UNLISTED_HOST: &str = "vault0-self-test.invalid";
+
+#[derive(Debug, Serialize)]
+pub struct SelfTestCheck {
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SelfTestReport {
+    pub passed: bool,
+    pub checks: Vec<SelfTestCheck>,
+}
+
+fn check(name: &str, passed: bool, detail: impl Into<String>) -> SelfTestCheck {
+    SelfTestCheck { name: name.to_string(), passed, detail: detail.into() }
+}
+
+/// Echo server on an ephemeral loopback port that answers with the request
+/// headers as JSON. Dropping the returned sender shuts it down.
+async fn start_echo() -> Result<(u16, oneshot::Sender<()>), String> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.map_err(|e| format!("echo bind: {}", e))?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    let app = axum::Router::new().route(
+        ECHO_PATH,
+        axum::routing::any(|headers: HeaderMap| async move {
+            let echoed: BTreeMap<String, String> = headers
+                .iter()
+                .map(|(k, v)| (k.as_str().to_string(), v.to_str().unwrap_or("").to_string()))
+                .collect();
+            axum::Json(echoed)
+        }),
+    );
+    let (tx, rx) = oneshot::channel::<()>();
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, app)
+            .with_graceful_shutdown(async {
+                let _ = rx.await;
+            })
+            .await;
+    });
+    Ok((port, tx))
+}
+
+/// Alias to inject when the request is the self-test's own echo request,
+/// which the proxy then forwards without consulting the policy. Matches once.
+pub(crate) fn claim_echo(host: &str, port: Option<u16>, path: &str, headers: &HeaderMap) -> Option<String> {
+    let mut route = ROUTE.lock().ok()?;
+    let nonce = headers.get(NONCE_HEADER)?.to_str().ok()?;
+    let matches = route
+        .as_ref()
+        .is_some_and(|r| host == ECHO_HOST && port == Some(r.port) && path == ECHO_PATH && nonce == r.nonce);
+    matches.then(|| route.take()).flatten().map(|r| r.alias)
+}
+
+fn new_nonce() -> Result<String, String> {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).map_err(|e| format!("nonce: {e}"))?;
+    Ok(hex::encode(bytes))
+}
+
+fn proxied_client() -> Result<reqwest::Client, String> {
+    let proxy_url = format!("http://{}", crate::settings::proxy_addr());
+    reqwest::Client::builder()
+        .proxy(reqwest::Proxy::http(&proxy_url).map_err(|e| e.to_string())?)
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Send a request through the proxy to the local echo and check it saw a
+/// loaded secret as the bearer token, and not the alias header itself.
+async fn injection_checks(client: &reqwest::Client) -> Vec<SelfTestCheck> {
+    let loaded = proxy::state()
+        .read()
+        .ok()
+        .and_then(|s| s.vault.iter().next().map(|(alias, secret)| (alias.clone(), secret.clone())));
+    let Some((alias, secret)) = loaded else {
+        return vec![check("key_injection", false, "No secrets loaded in the proxy; unlock the vault first")];
+    };
+    let (port, _stop) = match start_echo().await {
+        Ok(echo) => echo,
+        Err(e) => return vec![check("key_injection", false, e)],
+    };
+
+    let nonce = match new_nonce() {
+        Ok(n) => n,
+        Err(e) => return vec![check("key_injection", false, e)],
+    };
+    if let Ok(mut route) = ROUTE.lock() {
+        *route = Some(EchoRoute { port, nonce: nonce.clone(), alias: alias.clone() });
+    }
+    // The alias header rides along only to check it is stripped.
+    let result = client
+        .get(format!("http://{}:{}{}", ECHO_HOST, port, ECHO_PATH))
+        .header(NONCE_HEADER, &nonce)
+        .header("x-vault0-alias", &alias)
+        .send()
+        .await;
+    if let Ok(mut route) = ROUTE.lock() {
+        *route = None;
+    }
+
+    let echoed: BTreeMap<String, String> = match result {
+        Ok(resp) if resp.status().is_success() => resp.json().await.unwrap_or_default(),
+        Ok(resp) => {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return vec![check("key_injection", false, format!("Proxy answered {}: {}", status, body))];
+        }
+        Err(e) => return vec![check("key_injection", false, format!("Request through proxy failed: {}", e))],
+    };
+    let injected = echoed.get("authorization").map(|a| a == &format!("Bearer {}", secret.as_str())).unwrap_or(false);
+    vec![
+        check(
+            "key_injection",
+            injected,
+            if injected {
+                format!("Secret '{}' reached the upstream as a bearer token", alias)
+            } else {
+                format!("Upstream did not receive secret '{}'", alias)
+            },
+        ),
+        check(
+            "alias_header_stripped",
+            !echoed.contains_key("x-vault0-alias"),
+            "X-Vault0-Alias must not be forwarded upstream",
+        ),
+    ]
+}
+
+/// Request a host the policy blocks and expect the proxy to refuse it.
+async fn blocked_check(client: &reqwest::Client) -> SelfTestCheck {
+    let target = proxy::state().read().ok().and_then(|s| {
+        let p = &s.policy;
+        p.block_domains
+            .iter()
+            .find(|d| !d.is_empty())
+            .cloned()
+            .or_else(|| (!p.allow_domains.is_empty()).then(|| UNLISTED_HOST.to_string()))
+    });
+    let Some(host) = target else {
+        return check("blocked_host", false, "Policy blocks nothing: no block_domains and no allow list");
+    };
+    match client.get(format!("http://{}/", host)).send().await {
+        Ok(resp) => {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            let refused = body.starts_with("Vault-0 policy denied");
+            check(
+                "blocked_host",
+                refused,
+                if refused {
+                    format!("{} refused with {}", host, status)
+                } else {
+                    format!("{} was not refused by policy ({})", host, status)
+                },
+            )
+        }
+        Err(e) => check("blocked_host", false, format!("Request to {} failed: {}", host, e)),
+    }
+}
+
+/// Exercise the proxy, vault and policy together and report each check.
+#[tauri::command]
+pub async fn proxy_self_test() -> Result<SelfTestReport, String> {
+    let health = proxy::health();
+    let mut checks = vec![check(
+        "proxy_reachable",
+        health.port_reachable,
+        match &health.bound_addr {
+            Some(addr) if health.port_reachable => format!("Listening on {}", addr),
+            Some(addr) => format!("Nothing answering on {}", addr),
+            None => "Proxy is not running".to_string(),
+        },
+    )];
+    if health.port_reachable {
+        let client = proxied_client()?;
+        checks.extend(injection_checks(&client).await);
+        checks.push(blocked_check(&client).await);
+    }
+    Ok(SelfTestReport { passed: checks.iter().all(|c| c.passed), checks })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_nonce(nonce: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(NONCE_HEADER, nonce.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn echo_route_needs_the_nonce_and_matches_once() {
+        *ROUTE.lock().unwrap() = Some(EchoRoute { port: 40123, nonce: "n0nce".into(), alias: "openai".into() });
+        assert_eq!(claim_echo(ECHO_HOST, Some(40123), ECHO_PATH, &HeaderMap::new()), None);
+        assert_eq!(claim_echo(ECHO_HOST, Some(40123), ECHO_PATH, &with_nonce("guess")), None);
+        assert_eq!(claim_echo(ECHO_HOST, Some(40124), ECHO_PATH, &with_nonce("n0nce")), None);
+        assert_eq!(claim_echo("evil.example", Some(40123), ECHO_PATH, &with_nonce("n0nce")), None);
+        assert_eq!(claim_echo(ECHO_HOST, Some(40123), ECHO_PATH, &with_nonce("n0nce")).as_deref(), Some("openai"));
+        assert_eq!(claim_echo(ECHO_HOST, Some(40123), ECHO_PATH, &with_nonce("n0nce")), None);
+    }
+}
@@ -0,0 +1,51 @@
+//! Streaming passthrough for server-sent events and chunked responses, so
+//! completions reach the agent as they are generated instead of after the
+//! whole body has been buffered.
+//!
+//! Redaction runs on each chunk as it arrives. A secret split across two
+//! chunks (or a chunk that ends mid UTF-8 character) is not matched, so
+//! streamed responses are scrubbed on a best-effort basis only.
+
+use axum::body::Body;
+use axum::http::{HeaderName, HeaderValue};
+use axum::response::Response;
+use futures_util::StreamExt;
+use reqwest::header::{HeaderMap, CONTENT_TYPE, TRANSFER_ENCODING};
+
+use crate::proxy_redact;
+
+/// Response headers describing the upstream framing, which no longer applies
+/// once the body is re-chunked (and possibly resized by redaction).
+const FRAMING_HEADERS: [&str; 3] = ["content-length", "transfer-encoding", "connection"];
+
+/// `text/event-stream` or chunked transfer encoding.
+pub(crate) fn is_streaming(headers: &HeaderMap) -> bool {
+    let has = |name, needle: &str| {
+        headers
+            .get_all(name)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .any(|v| v.to_ascii_lowercase().contains(needle))
+    };
+    has(CONTENT_TYPE, "text/event-stream") || has(TRANSFER_ENCODING, "chunked")
+}
+
+/// Forward `resp` to the agent chunk by chunk, redacting each chunk.
+pub(crate) fn respond(resp: reqwest::Response, redact_patterns: &[String]) -> Response {
+    let patterns = proxy_redact::compile(redact_patterns);
+    let mut builder = Response::builder().status(resp.status());
+    for (k, v) in resp.headers() {
+        if FRAMING_HEADERS.contains(&k.as_str()) {
+            continue;
+        }
+        if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(k.as_str().as_bytes()), HeaderValue::from_bytes(v.as_bytes())) {
+            builder = builder.header(name, value);
+        }
+    }
+    let chunks = resp
+        .bytes_stream()
+        .map(move |chunk| chunk.map(|bytes| proxy_redact::redact(&bytes, &patterns)));
+    builder
+        .body(Body::from_stream(chunks))
+        .unwrap_or_else(|_| Response::new(Body::from("internal error")))
+}
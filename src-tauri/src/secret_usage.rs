@@ -0,0 +1,120 @@
+//! Per-alias usage audit built from the persisted evidence log, so stale or
+//! over-scoped keys stand out.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::evidence::{self, LogEntry};
+
+#[derive(Debug, Serialize)]
+pub struct SecretUsage {
+    pub alias: String,
+    /// Provider recorded in the vault; `None` if the alias is no longer stored
+    /// (or the vault is locked).
+    pub provider: Option<String>,
+    /// Requests the proxy injected this secret into.
+    pub injections: u64,
+    /// Evidence timestamp (unix seconds with millis) of the latest injection.
+    pub last_used: Option<String>,
+    /// Hosts the secret was sent to, sorted.
+    pub hosts: Vec<String>,
+}
+
+#[derive(Default)]
+struct Tally {
+    injections: u64,
+    last_used: Option<String>,
+    hosts: BTreeSet<String>,
+}
+
+/// Whether `entry` records a request forwarded with its alias's key: an
+/// `allowed` entry, or the settled retry of an x402 payment. The `402 pending`
+/// entry before a settlement and `blocked` refusals sent nothing.
+fn is_injection(entry: &LogEntry) -> bool {
+    match entry.kind.as_str() {
+        "allowed" => true,
+        "payment" => entry.fields.status.is_some_and(|s| s != 402),
+        _ => false,
+    }
+}
+
+/// Per-alias tallies over `entries`, oldest first.
+fn tally(entries: impl IntoIterator<Item = LogEntry>) -> BTreeMap<String, Tally> {
+    let mut tallies: BTreeMap<String, Tally> = BTreeMap::new();
+    for entry in entries.into_iter().filter(is_injection) {
+        let Some(alias) = entry.fields.alias else { continue };
+        let tally = tallies.entry(alias).or_default();
+        tally.injections += 1;
+        // Entries are read oldest first.
+        tally.last_used = Some(entry.ts);
+        if let Some(domain) = entry.fields.domain {
+            tally.hosts.insert(domain);
+        }
+    }
+    tallies
+}
+
+/// Injection count, last use and target hosts per alias across the full
+/// evidence history. With the vault unlocked, aliases that were never injected
+/// are listed too, with a count of zero.
+#[tauri::command]
+pub fn secret_usage_report() -> Result<Vec<SecretUsage>, String> {
+    let mut tallies = tally(evidence::read_persisted());
+
+    let mut providers: BTreeMap<String, String> = BTreeMap::new();
+    if let Ok(entries) = crate::vault_store::vault_list_entries() {
+        for e in entries {
+            tallies.entry(e.alias.clone()).or_default();
+            providers.insert(e.alias, e.provider);
+        }
+    }
+
+    Ok(tallies
+        .into_iter()
+        .map(|(alias, t)| SecretUsage {
+            provider: providers.get(&alias).cloned(),
+            alias,
+            injections: t.injections,
+            last_used: t.last_used,
+            hosts: t.hosts.into_iter().collect(),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evidence::EvidenceFields;
+
+    fn logged(kind: &str, status: u16, domain: &str) -> LogEntry {
+        LogEntry {
+            ts: "1700000000.000".into(),
+            kind: kind.into(),
+            msg: String::new(),
+            fields: EvidenceFields {
+                domain: Some(domain.into()),
+                status: Some(status),
+                alias: Some("openai".into()),
+                ..Default::default()
+            },
+            seq: 0,
+            prev_hash: String::new(),
+            hash: String::new(),
+        }
+    }
+
+    #[test]
+    fn counts_one_entry_per_forwarded_request() {
+        let tallies = tally(vec![
+            logged("allowed", 200, "api.openai.com"),
+            // x402: pending then settled is one request.
+            logged("payment", 402, "paid.example"),
+            logged("payment", 200, "paid.example"),
+            // Refusals never sent the key.
+            logged("blocked", 403, "evil.example"),
+        ]);
+        let t = &tallies["openai"];
+        assert_eq!(t.injections, 2);
+        assert_eq!(t.hosts.iter().collect::<Vec<_>>(), ["api.openai.com", "paid.example"]);
+    }
+}
@@ -0,0 +1,190 @@
+//! Ephemeral .env writer (Option C): inject vault secrets into ~/.openclaw/.env,
+//! restart the OpenClaw daemon so it reads them, then scrub the file.
+
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::detect::home_dir;
+use crate::detect_platform;
+
+#[derive(Debug, Serialize)]
+pub struct SecureLaunchResult {
+    pub success: bool,
+    pub keys_injected: u32,
+    pub daemon_restarted: bool,
+    pub env_cleaned: bool,
+    pub detail: String,
+}
+
+fn openclaw_env_path() -> Result<PathBuf, String> {
+    let home = home_dir().ok_or("Home directory not found")?;
+    Ok(home.join(".openclaw").join(".env"))
+}
+
+/// Environment variable name a vault alias is exposed as (`openai-key` -> `OPENAI_KEY`).
+pub(crate) fn env_var_name(alias: &str) -> String {
+    alias.to_uppercase().replace('-', "_")
+}
+
+#[tauri::command]
+pub async fn launch_secure_agent() -> Result<SecureLaunchResult, String> {
+    // 1. Check vault is unlocked and get all entries
+    let entries = crate::vault_store::vault_list_entries()?;
+    if entries.is_empty() {
+        return Ok(SecureLaunchResult {
+            success: false,
+            keys_injected: 0,
+            daemon_restarted: false,
+            env_cleaned: false,
+            detail: "No secrets in vault. Add secrets first.".into(),
+        });
+    }
+
+    // 2. Build .env content from vault secrets
+    let mut env_lines: Vec<String> = Vec::new();
+    let mut count = 0u32;
+    for entry in &entries {
+        match crate::vault_store::vault_get_secret(entry.alias.clone()) {
+            Ok(value) => {
+                let key_name = env_var_name(&entry.alias);
+                env_lines.push(format!("{}={}", key_name, value));
+                count += 1;
+            }
+            Err(_) => continue,
+        }
+    }
+
+    if env_lines.is_empty() {
+        return Ok(SecureLaunchResult {
+            success: false,
+            keys_injected: 0,
+            daemon_restarted: false,
+            env_cleaned: false,
+            detail: "Could not read any secrets from vault.".into(),
+        });
+    }
+
+    // 3. Write ephemeral .env
+    let env_path = openclaw_env_path()?;
+    let env_content = env_lines.join("\n") + "\n";
+    fs::write(&env_path, &env_content).map_err(|e| format!("Write .env failed: {e}"))?;
+    tracing::info!("Ephemeral .env written with {} keys", count);
+
+    // 4. Restart OpenClaw daemon
+    let restart_method = restart_openclaw_daemon();
+    let daemon_restarted = restart_method.is_some();
+
+    // 5. Sleep 2 seconds to let daemon read .env
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+
+    // 6. Delete/zero .env
+    let env_cleaned = match fs::write(&env_path, "# Managed by Vault-0 - secrets injected at runtime\n") {
+        Ok(_) => {
+            tracing::info!("Ephemeral .env cleaned");
+            true
+        }
+        Err(e) => {
+            tracing::error!("Failed to clean .env: {e}");
+            false
+        }
+    };
+
+    // 7. Log to evidence
+    crate::evidence::push("info", &format!(
+        "Secure launch: {} keys injected, daemon restarted: {}, .env cleaned: {}",
+        count, daemon_restarted, env_cleaned
+    ));
+
+    Ok(SecureLaunchResult {
+        success: true,
+        keys_injected: count,
+        daemon_restarted,
+        env_cleaned,
+        detail: format!(
+            "{} secrets injected. Daemon {}. .env {}.",
+            count,
+            match restart_method {
+                Some(method) => format!("restarted via {}", method),
+                None => "restart failed (try manually)".to_string(),
+            },
+            if env_cleaned { "cleaned" } else { "cleanup failed" }
+        ),
+    })
+}
+
+fn run_ok(cmd: &mut Command) -> bool {
+    cmd.output().map(|o| o.status.success()).unwrap_or(false)
+}
+
+/// Restart the gateway daemon with the platform's service manager, falling back
+/// to the OpenClaw CLI and finally a SIGHUP. Returns the mechanism that worked.
+fn restart_openclaw_daemon() -> Option<&'static str> {
+    let method = if cfg!(target_os = "macos") {
+        restart_launchd()
+    } else if cfg!(windows) {
+        restart_windows_service()
+    } else {
+        restart_systemd()
+    };
+    let method = method.or_else(restart_via_cli).or_else(signal_gateway);
+    match method {
+        Some(m) => tracing::info!("Daemon restarted via {}", m),
+        None => tracing::warn!("Could not restart OpenClaw daemon automatically"),
+    }
+    method
+}
+
+fn restart_launchd() -> Option<&'static str> {
+    let uid = Command::new("id").arg("-u").output().ok()
+        .and_then(|o| String::from_utf8(o.stdout).ok())
+        .map(|s| s.trim().to_string())
+        .unwrap_or_default();
+    if uid.is_empty() {
+        return None;
+    }
+    let service = format!("gui/{}/ai.openclaw.gateway", uid);
+    run_ok(Command::new("launchctl").args(["kickstart", "-k", &service])).then_some("launchctl kickstart")
+}
+
+fn restart_systemd() -> Option<&'static str> {
+    ["openclaw-gateway", "openclaw"]
+        .iter()
+        .any(|unit| run_ok(Command::new("systemctl").args(["--user", "restart", unit])))
+        .then_some("systemctl --user restart")
+}
+
+fn restart_windows_service() -> Option<&'static str> {
+    let restarted = ["OpenClawGateway", "openclaw"].iter().any(|name| {
+        run_ok(Command::new("powershell").args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            &format!("Restart-Service -Name '{}' -ErrorAction Stop", name),
+        ]))
+    });
+    restarted.then_some("Restart-Service")
+}
+
+fn restart_via_cli() -> Option<&'static str> {
+    let cli = detect_platform::find_on_path("openclaw")?;
+    (run_ok(Command::new(&cli).arg("restart")) || run_ok(Command::new(&cli).args(["gateway", "--restart"])))
+        .then_some("openclaw restart")
+}
+
+/// Last resort on Unix: HUP the gateway process so it reloads its environment.
+fn signal_gateway() -> Option<&'static str> {
+    if cfg!(windows) {
+        return None;
+    }
+    let out = Command::new("sh")
+        .args(["-lc", "pgrep -f 'openclaw.*gateway' | head -1"])
+        .output()
+        .ok()?;
+    let pid = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    if pid.is_empty() {
+        return None;
+    }
+    run_ok(Command::new("kill").args(["-HUP", &pid])).then_some("SIGHUP")
+}
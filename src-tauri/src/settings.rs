@@ -0,0 +1,203 @@
+//! Persisted application settings.
+//! File: <config dir>/vault0/settings.json. Missing fields fall back to defaults.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use tracing::warn;
+
+use crate::detect_patterns::UserKeyPattern;
+use crate::evidence;
+use crate::networks::NetworkConfig;
+
+const SETTINGS_DIR: &str = "vault0";
+const SETTINGS_FILE: &str = "settings.json";
+/// Upper bound for the in-memory evidence and gateway rings.
+const MAX_RING_CAP: usize = 50_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Address the proxy listens on. Changes take effect the next time it starts.
+    pub proxy_bind: String,
+    pub proxy_port: u16,
+    /// Allow a non-loopback `proxy_bind`. Off by default: anyone who can reach
+    /// the proxy gets vault keys injected into their requests.
+    pub proxy_allow_remote: bool,
+    /// Limit on a whole upstream request, in seconds; `None` leaves it unbounded
+    /// (long model completions can take minutes).
+    pub upstream_timeout_secs: Option<u64>,
+    /// Largest request body the proxy buffers and forwards, in MiB. Bigger bodies get 413.
+    pub proxy_body_limit_mb: usize,
+    /// Per-request timeout for gateway health probes.
+    pub health_probe_timeout_secs: u64,
+    /// Gateway port assumed when openclaw.json doesn't set one.
+    pub gateway_default_port: u16,
+    /// Chain used for the wallet and for 402s that don't name one: `base` or `base-sepolia`.
+    pub wallet_network: String,
+    /// Chain id and USDC contract per network name, on top of the built-in `base`/`base-sepolia`.
+    pub networks: BTreeMap<String, NetworkConfig>,
+    /// Entries kept in the in-memory evidence ring (the on-disk log is separate).
+    pub evidence_log_cap: usize,
+    /// Gateway events kept in the in-memory timeline ring.
+    pub gateway_event_cap: usize,
+    /// Size in MiB at which the on-disk evidence log is rotated.
+    pub evidence_rotate_mb: u64,
+    /// Full `ws://` or `wss://` gateway URL; overrides `gateway.url`/`gateway.port` in openclaw.json.
+    pub gateway_url: Option<String>,
+    /// Accept self-signed or mismatched TLS certificates on `wss://` (local dev only).
+    pub gateway_tls_insecure: bool,
+    /// Consecutive gateway reconnect attempts before giving up; `None` retries forever.
+    pub gateway_max_reconnects: Option<u32>,
+    /// Sessions with no gateway events for this long drop out of `gateway_sessions`.
+    pub gateway_session_idle_secs: u64,
+    /// User-defined key patterns merged with the built-ins when scanning configs.
+    pub key_patterns: Vec<UserKeyPattern>,
+    /// Extra directories checked by `detect_openclaw`, in addition to the built-in search dirs.
+    pub extra_search_dirs: Vec<String>,
+    /// Hardening backups kept regardless of age.
+    pub backup_keep_count: usize,
+    /// Backups younger than this are kept even beyond `backup_keep_count`.
+    pub backup_keep_days: u64,
+    /// Extra readiness probe URLs (e.g. `http://127.0.0.1:9000/healthz`), tried after the built-in ones.
+    pub health_probe_targets: Vec<String>,
+    /// Script extension -> interpreter command for `launch_agent` (e.g. `"ts": "bun run"`),
+    /// overriding the built-in python3/node/npx tsx/sh choices.
+    pub agent_interpreters: BTreeMap<String, String>,
+    /// Port for the Prometheus `/metrics` endpoint; `None` keeps it off.
+    pub metrics_port: Option<u16>,
+    /// Address the metrics endpoint binds to. Loopback unless changed deliberately.
+    pub metrics_bind: String,
+    /// Desktop notification when the proxy blocks a request.
+    pub notify_blocked: bool,
+    /// Desktop notification when a 402 payment request arrives.
+    pub notify_payments: bool,
+    /// Lock the vault after this many seconds without a vault command or a key
+    /// injected by the proxy; 0 never locks.
+    pub vault_auto_lock_secs: u64,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            proxy_bind: "127.0.0.1".to_string(),
+            proxy_port: 3840,
+            proxy_allow_remote: false,
+            upstream_timeout_secs: None,
+            proxy_body_limit_mb: 10,
+            health_probe_timeout_secs: 2,
+            gateway_default_port: 18789,
+            wallet_network: "base".to_string(),
+            networks: BTreeMap::new(),
+            evidence_log_cap: 500,
+            gateway_event_cap: 500,
+            evidence_rotate_mb: 10,
+            gateway_url: None,
+            gateway_tls_insecure: false,
+            gateway_max_reconnects: None,
+            gateway_session_idle_secs: 30 * 60,
+            key_patterns: Vec::new(),
+            extra_search_dirs: Vec::new(),
+            backup_keep_count: 10,
+            backup_keep_days: 30,
+            health_probe_targets: Vec::new(),
+            agent_interpreters: BTreeMap::new(),
+            metrics_port: None,
+            metrics_bind: "127.0.0.1".to_string(),
+            notify_blocked: true,
+            notify_payments: true,
+            vault_auto_lock_secs: 900,
+        }
+    }
+}
+
+static SETTINGS: Lazy<RwLock<Settings>> = Lazy::new(|| RwLock::new(load_from_disk()));
+
+fn settings_path() -> Option<PathBuf> {
+    // Tests run on the defaults and never touch the user's settings file.
+    if cfg!(test) {
+        return None;
+    }
+    dirs::config_dir().map(|p| p.join(SETTINGS_DIR).join(SETTINGS_FILE))
+}
+
+fn load_from_disk() -> Settings {
+    let Some(path) = settings_path() else { return Settings::default() };
+    match fs::read_to_string(&path) {
+        Ok(s) => serde_json::from_str(&s).unwrap_or_else(|e| {
+            warn!("Ignoring invalid settings file {}: {}", path.display(), e);
+            Settings::default()
+        }),
+        Err(_) => Settings::default(),
+    }
+}
+
+fn save_to_disk(settings: &Settings) -> Result<(), String> {
+    let path = settings_path().ok_or("Config dir not found")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("mkdir: {e}"))?;
+    }
+    let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("write settings: {e}"))
+}
+
+/// Snapshot of the current settings.
+pub fn get() -> Settings {
+    SETTINGS.read().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// `bind:port` the proxy listens on.
+pub fn proxy_addr() -> String {
+    let s = get();
+    format!("{}:{}", s.proxy_bind, s.proxy_port)
+}
+
+/// Push settings that live in other modules' runtime state. Called at startup and after updates.
+pub fn apply(settings: &Settings) {
+    evidence::set_log_cap(settings.evidence_log_cap.clamp(1, MAX_RING_CAP));
+    crate::gateway_ws::set_event_cap(settings.gateway_event_cap.clamp(1, MAX_RING_CAP));
+    crate::metrics::apply(settings);
+}
+
+#[tauri::command]
+pub fn get_settings() -> Result<Settings, String> {
+    Ok(get())
+}
+
+/// Merge a partial settings object into the current settings, persist and apply it.
+#[tauri::command]
+pub fn update_settings(partial: serde_json::Value) -> Result<Settings, String> {
+    let patch = partial.as_object().ok_or("Settings update must be an object")?;
+    let mut guard = SETTINGS.write().map_err(|_| "settings lock")?;
+    let mut merged = serde_json::to_value(&*guard).map_err(|e| e.to_string())?;
+    if let Some(obj) = merged.as_object_mut() {
+        for (k, v) in patch {
+            obj.insert(k.clone(), v.clone());
+        }
+    }
+    let updated: Settings = serde_json::from_value(merged).map_err(|e| format!("invalid settings: {e}"))?;
+    save_to_disk(&updated)?;
+    *guard = updated.clone();
+    drop(guard);
+    apply(&updated);
+    Ok(updated)
+}
+
+/// Resize the in-memory evidence ring (clamped to 1..=50000) and keep the size.
+/// Returns the cap applied.
+#[tauri::command]
+pub fn set_evidence_cap(n: usize) -> Result<usize, String> {
+    let cap = n.clamp(1, MAX_RING_CAP);
+    update_settings(serde_json::json!({ "evidence_log_cap": cap })).map(|s| s.evidence_log_cap)
+}
+
+/// Resize the in-memory gateway event ring (clamped to 1..=50000) and keep the
+/// size. Returns the cap applied.
+#[tauri::command]
+pub fn set_gateway_event_cap(n: usize) -> Result<usize, String> {
+    let cap = n.clamp(1, MAX_RING_CAP);
+    update_settings(serde_json::json!({ "gateway_event_cap": cap })).map(|s| s.gateway_event_cap)
+}
@@ -0,0 +1,55 @@
+//! Backup and restore of the encrypted vault file. The file is copied as is, so
+//! a backup stays protected by the passphrase it was created with.
+
+use std::fs;
+use std::path::Path;
+use tracing::info;
+
+use crate::evidence;
+use crate::vault_store::{self, decrypt_entries, derive_key, parse_vault_file, vault_path};
+
+/// Copy the encrypted vault to `dest_path`.
+#[tauri::command]
+pub fn vault_export(dest_path: String) -> Result<(), String> {
+    let src = vault_path()?;
+    if !src.exists() {
+        return Err("No vault to export".into());
+    }
+    let dest = Path::new(&dest_path);
+    if let Some(parent) = dest.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("mkdir: {e}"))?;
+    }
+    fs::copy(&src, dest).map_err(|e| format!("export vault: {e}"))?;
+    info!("Vault exported to {}", dest.display());
+    evidence::push("info", &format!("Vault exported to {}", dest.display()));
+    Ok(())
+}
+
+/// Install the vault backup at `src_path` as the active vault, after checking
+/// that `passphrase` decrypts it. An existing vault is only replaced with
+/// `overwrite`. The imported vault is left unlocked and replaces the previous
+/// vault's keys in the proxy.
+#[tauri::command]
+pub fn vault_import(src_path: String, passphrase: String, overwrite: Option<bool>) -> Result<usize, String> {
+    let dest = vault_path()?;
+    if dest.exists() && !overwrite.unwrap_or(false) {
+        return Err("A vault already exists; pass overwrite to replace it".into());
+    }
+    let json = fs::read_to_string(&src_path).map_err(|e| format!("read backup: {e}"))?;
+    let (salt, nonce, ciphertext) = parse_vault_file(&json)?;
+    let key = derive_key(&passphrase, &salt)?;
+    let entries = decrypt_entries(&ciphertext, &nonce, &key)?;
+    let count = entries.len();
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("mkdir: {e}"))?;
+    }
+    let tmp = dest.with_extension("enc.tmp");
+    fs::write(&tmp, &json).map_err(|e| format!("write: {e}"))?;
+    fs::rename(&tmp, &dest).map_err(|e| format!("replace vault: {e}"))?;
+    vault_store::unload_from_proxy();
+    vault_store::set_unlocked(entries, key)?;
+    vault_store::load_into_proxy()?;
+    info!("Vault imported from {} ({} entries)", src_path, count);
+    evidence::push("info", &format!("Vault imported from {} ({} entries)", src_path, count));
+    Ok(count)
+}
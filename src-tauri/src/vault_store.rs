@@ -11,20 +11,35 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 use std::sync::RwLock;
+use std::time::{Duration, Instant};
 use once_cell::sync::Lazy;
 use tracing::info;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
 const VAULT_DIR: &str = "Vault0";
 const VAULT_FILE: &str = "vault.enc";
 const NONCE_LEN: usize = 12;
 const KEY_LEN: usize = 32;
+/// How often the idle auto-lock checks the vault.
+const AUTO_LOCK_POLL: Duration = Duration::from_secs(15);
+/// Entries expiring within this window are flagged in the evidence log on unlock.
+const EXPIRY_WARN_SECS: u64 = 7 * 24 * 3600;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
 pub struct VaultEntry {
     pub alias: String,
     pub provider: String,
     pub value: String,
     pub created_at: String,
+    /// Set when the value is replaced with `vault_update_entry`.
+    #[serde(default)]
+    pub updated_at: Option<String>,
+    /// Free-form labels for grouping, e.g. `work` or `test`.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Unix seconds after which the proxy refuses to inject the value.
+    #[serde(default)]
+    pub expires_at: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,24 +57,41 @@ struct VaultFile {
     ciphertext_hex: String,
 }
 
+/// Unlocked vault. The key and every entry are overwritten when it is dropped,
+/// on lock, auto-lock or replacement.
+#[derive(Zeroize, ZeroizeOnDrop)]
 struct VaultState {
     entries: Vec<VaultEntry>,
-    derived_key: [u8; KEY_LEN],
+    derived_key: Zeroizing<[u8; KEY_LEN]>,
     unlocked: bool,
+    /// Last read or write through a vault command, for the idle auto-lock.
+    #[zeroize(skip)]
+    last_access: Instant,
 }
 
+impl VaultState {
+    fn new(entries: Vec<VaultEntry>, derived_key: Zeroizing<[u8; KEY_LEN]>) -> Self {
+        VaultState { entries, derived_key, unlocked: true, last_access: Instant::now() }
+    }
+}
+
+
 static VAULT: Lazy<RwLock<Option<VaultState>>> = Lazy::new(|| RwLock::new(None));
 
 fn vault_dir() -> Result<PathBuf, String> {
+    // Tests keep their vault in a per-process temp dir, never the user's.
+    if cfg!(test) {
+        return Ok(std::env::temp_dir().join(format!("vault0-test-{}", std::process::id())));
+    }
     let base = dirs::data_dir().ok_or("Cannot determine app data directory")?;
     Ok(base.join(VAULT_DIR))
 }
 
-fn vault_path() -> Result<PathBuf, String> {
+pub(crate) fn vault_path() -> Result<PathBuf, String> {
     Ok(vault_dir()?.join(VAULT_FILE))
 }
 
-fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; KEY_LEN], String> {
+pub(crate) fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Zeroizing<[u8; KEY_LEN]>, String> {
     let argon2 = Argon2::default();
     let salt_str = SaltString::encode_b64(salt).map_err(|e| format!("salt encode: {e}"))?;
     let hash = argon2
@@ -70,7 +102,7 @@ fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; KEY_LEN], String> {
     if bytes.len() < KEY_LEN {
         return Err("argon2 output too short".into());
     }
-    let mut key = [0u8; KEY_LEN];
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
     key.copy_from_slice(&bytes[..KEY_LEN]);
     Ok(key)
 }
@@ -78,7 +110,7 @@ fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; KEY_LEN], String> {
 pub fn encrypt_bytes_with_vault_key(plaintext: &[u8]) -> Result<Vec<u8>, String> {
     let guard = VAULT.read().map_err(|_| "vault lock")?;
     let state = guard.as_ref().ok_or("Vault is locked")?;
-    let cipher = Aes256Gcm::new_from_slice(&state.derived_key).map_err(|e| format!("cipher init: {e}"))?;
+    let cipher = Aes256Gcm::new_from_slice(state.derived_key.as_ref()).map_err(|e| format!("cipher init: {e}"))?;
     let mut nonce_bytes = [0u8; NONCE_LEN];
     getrandom(&mut nonce_bytes).map_err(|e| format!("nonce gen: {e}"))?;
     let ciphertext = cipher.encrypt(Nonce::from_slice(&nonce_bytes), plaintext).map_err(|e| format!("encrypt: {e}"))?;
@@ -87,8 +119,22 @@ pub fn encrypt_bytes_with_vault_key(plaintext: &[u8]) -> Result<Vec<u8>, String>
     Ok(result)
 }
 
+/// Inverse of `encrypt_bytes_with_vault_key`: input is `nonce || ciphertext`.
+pub fn decrypt_bytes_with_vault_key(data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < NONCE_LEN {
+        return Err("Encrypted data too short".into());
+    }
+    let guard = VAULT.read().map_err(|_| "vault lock")?;
+    let state = guard.as_ref().ok_or("Vault is locked")?;
+    let cipher = Aes256Gcm::new_from_slice(state.derived_key.as_ref()).map_err(|e| format!("cipher init: {e}"))?;
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Decryption failed. Backup was made with a different vault?".to_string())
+}
+
 fn encrypt_entries(entries: &[VaultEntry], key: &[u8; KEY_LEN]) -> Result<(Vec<u8>, Vec<u8>), String> {
-    let plaintext = serde_json::to_vec(entries).map_err(|e| format!("serialize: {e}"))?;
+    let plaintext = Zeroizing::new(serde_json::to_vec(entries).map_err(|e| format!("serialize: {e}"))?);
     let cipher = Aes256Gcm::new_from_slice(key).map_err(|e| format!("cipher init: {e}"))?;
     let mut nonce_bytes = [0u8; NONCE_LEN];
     getrandom(&mut nonce_bytes).map_err(|e| format!("nonce gen: {e}"))?;
@@ -97,10 +143,12 @@ fn encrypt_entries(entries: &[VaultEntry], key: &[u8; KEY_LEN]) -> Result<(Vec<u
     Ok((nonce_bytes.to_vec(), ciphertext))
 }
 
-fn decrypt_entries(ciphertext: &[u8], nonce: &[u8], key: &[u8; KEY_LEN]) -> Result<Vec<VaultEntry>, String> {
+pub(crate) fn decrypt_entries(ciphertext: &[u8], nonce: &[u8], key: &[u8; KEY_LEN]) -> Result<Vec<VaultEntry>, String> {
     let cipher = Aes256Gcm::new_from_slice(key).map_err(|e| format!("cipher init: {e}"))?;
     let nonce = Nonce::from_slice(nonce);
-    let plaintext = cipher.decrypt(nonce, ciphertext).map_err(|_| "Decryption failed. Wrong passphrase?".to_string())?;
+    let plaintext = Zeroizing::new(
+        cipher.decrypt(nonce, ciphertext).map_err(|_| "Decryption failed. Wrong passphrase?".to_string())?,
+    );
     let entries: Vec<VaultEntry> = serde_json::from_slice(&plaintext).map_err(|e| format!("deserialize: {e}"))?;
     Ok(entries)
 }
@@ -120,21 +168,44 @@ fn write_vault_file(salt: &[u8], nonce: &[u8], ciphertext: &[u8]) -> Result<(),
     };
     let json = serde_json::to_string_pretty(&file).map_err(|e| format!("serialize file: {e}"))?;
     let path = vault_path()?;
-    fs::write(&path, json).map_err(|e| format!("write: {e}"))?;
+    // Write beside the vault and rename over it, so a crash mid-write never
+    // leaves a truncated vault behind.
+    let tmp = path.with_extension("enc.tmp");
+    fs::write(&tmp, json).map_err(|e| format!("write: {e}"))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("replace vault: {e}"))?;
     info!("Vault file written to {}", path.display());
     Ok(())
 }
 
+/// Re-encrypt the in-memory entries and write them under the current salt.
+fn persist(state: &VaultState) -> Result<(), String> {
+    let (nonce, ciphertext) = encrypt_entries(&state.entries, &state.derived_key)?;
+    let (salt, _, _) = read_vault_file()?;
+    write_vault_file(&salt, &nonce, &ciphertext)
+}
+
 fn read_vault_file() -> Result<(Vec<u8>, Vec<u8>, Vec<u8>), String> {
     let path = vault_path()?;
     let json = fs::read_to_string(&path).map_err(|e| format!("read vault: {e}"))?;
-    let file: VaultFile = serde_json::from_str(&json).map_err(|e| format!("parse vault: {e}"))?;
+    parse_vault_file(&json)
+}
+
+/// `(salt, nonce, ciphertext)` from the JSON of a vault file.
+pub(crate) fn parse_vault_file(json: &str) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>), String> {
+    let file: VaultFile = serde_json::from_str(json).map_err(|e| format!("parse vault: {e}"))?;
     let salt = hex::decode(&file.header.salt_hex).map_err(|e| format!("decode salt: {e}"))?;
     let nonce = hex::decode(&file.header.nonce_hex).map_err(|e| format!("decode nonce: {e}"))?;
     let ciphertext = hex::decode(&file.ciphertext_hex).map_err(|e| format!("decode ciphertext: {e}"))?;
     Ok((salt, nonce, ciphertext))
 }
 
+fn check_passphrase_strength(passphrase: &str) -> Result<(), String> {
+    if passphrase.len() < 12 {
+        return Err("Passphrase must be at least 12 characters".into());
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub fn vault_exists() -> bool {
     vault_path().map(|p| p.exists()).unwrap_or(false)
@@ -142,9 +213,7 @@ pub fn vault_exists() -> bool {
 
 #[tauri::command]
 pub fn vault_create(passphrase: String) -> Result<(), String> {
-    if passphrase.len() < 12 {
-        return Err("Passphrase must be at least 12 characters".into());
-    }
+    check_passphrase_strength(&passphrase)?;
     let mut salt = [0u8; 16];
     getrandom(&mut salt).map_err(|e| format!("salt gen: {e}"))?;
     let key = derive_key(&passphrase, &salt)?;
@@ -152,58 +221,236 @@ pub fn vault_create(passphrase: String) -> Result<(), String> {
     let (nonce, ciphertext) = encrypt_entries(&entries, &key)?;
     write_vault_file(&salt, &nonce, &ciphertext)?;
     let mut guard = VAULT.write().map_err(|_| "vault lock")?;
-    *guard = Some(VaultState {
-        entries,
-        derived_key: key,
-        unlocked: true,
-    });
+    *guard = Some(VaultState::new(entries, key));
     info!("Vault created and unlocked");
     Ok(())
 }
 
-#[tauri::command]
-pub fn vault_unlock(passphrase: String) -> Result<(), String> {
+/// Decrypt the vault file with `passphrase`, keep the entries in memory and
+/// load them into the proxy.
+/// Shared by the `vault_unlock` command and headless mode.
+pub fn unlock(passphrase: &str) -> Result<usize, String> {
     let (salt, nonce, ciphertext) = read_vault_file()?;
-    let key = derive_key(&passphrase, &salt)?;
+    let key = derive_key(passphrase, &salt)?;
     let entries = decrypt_entries(&ciphertext, &nonce, &key)?;
+    let count = entries.len();
+    warn_expiring(&entries);
+    set_unlocked(entries, key)?;
+    load_into_proxy()?;
+    info!("Vault unlocked ({} entries)", count);
+    Ok(count)
+}
+
+/// Evidence warnings for entries that have expired or will within a week.
+fn warn_expiring(entries: &[VaultEntry]) {
+    let now = unix_now();
+    for e in entries {
+        let Some(expires_at) = e.expires_at else { continue };
+        if expires_at <= now {
+            crate::evidence::push("warning", &format!("Vault key '{}' has expired", e.alias));
+        } else if expires_at - now <= EXPIRY_WARN_SECS {
+            let hours = (expires_at - now) / 3600;
+            crate::evidence::push("warning", &format!("Vault key '{}' expires in {}h", e.alias, hours));
+        }
+    }
+}
+
+/// Replace the in-memory vault with decrypted `entries` and their key.
+pub(crate) fn set_unlocked(entries: Vec<VaultEntry>, key: Zeroizing<[u8; KEY_LEN]>) -> Result<(), String> {
     let mut guard = VAULT.write().map_err(|_| "vault lock")?;
-    *guard = Some(VaultState {
-        entries,
-        derived_key: key,
-        unlocked: true,
-    });
-    info!("Vault unlocked ({} entries)", guard.as_ref().unwrap().entries.len());
+    *guard = Some(VaultState::new(entries, key));
     Ok(())
 }
 
+/// Mirror the unlocked vault into the proxy: every entry's key and expiry go
+/// into its alias maps, and aliases an earlier load brought in that the vault
+/// no longer has are dropped. Run after unlock and every entry change, so
+/// `proxy_inject::expired` sees the same expiry as the vault.
+pub fn load_into_proxy() -> Result<usize, String> {
+    let guard = VAULT.read().map_err(|_| "vault lock")?;
+    let vault = guard.as_ref().ok_or("Vault is locked")?;
+    let mut state = crate::proxy::state().write().map_err(|_| "state lock")?;
+    let stale: Vec<String> = state
+        .vault_aliases
+        .iter()
+        .filter(|alias| !vault.entries.iter().any(|e| &e.alias == *alias))
+        .cloned()
+        .collect();
+    for alias in &stale {
+        state.vault.remove(alias);
+        state.expires.remove(alias);
+        state.vault_aliases.remove(alias);
+    }
+    for e in &vault.entries {
+        state.vault.insert(e.alias.clone(), Zeroizing::new(e.value.clone()));
+        match e.expires_at {
+            Some(at) => state.expires.insert(e.alias.clone(), at),
+            None => state.expires.remove(&e.alias),
+        };
+        state.vault_aliases.insert(e.alias.clone());
+    }
+    Ok(vault.entries.len())
+}
+
+/// Drop the keys `load_into_proxy` brought in, with their expiry, and the
+/// client certificates decoded from the vault. Keys added with `set_secret` or
+/// a config scan stay. The values are zeroized as they are dropped.
+pub(crate) fn unload_from_proxy() {
+    if let Ok(mut state) = crate::proxy::state().write() {
+        for alias in std::mem::take(&mut state.vault_aliases) {
+            state.vault.remove(&alias);
+            state.expires.remove(&alias);
+        }
+    }
+    crate::proxy_tls::clear_identities();
+}
+
+/// Count a use of an unlocked key (e.g. a proxy injection) as vault access for
+/// the idle auto-lock.
+pub fn touch() {
+    if let Ok(mut guard) = VAULT.write() {
+        if let Some(state) = guard.as_mut() {
+            state.last_access = Instant::now();
+        }
+    }
+}
+
+#[tauri::command]
+pub fn vault_unlock(passphrase: String) -> Result<(), String> {
+    unlock(&passphrase).map(|_| ())
+}
+
 #[tauri::command]
 pub fn vault_lock() -> Result<(), String> {
     let mut guard = VAULT.write().map_err(|_| "vault lock")?;
     *guard = None;
+    drop(guard);
+    unload_from_proxy();
     info!("Vault locked");
     Ok(())
 }
 
+/// Lock the vault after `vault_auto_lock_secs` without a vault command or a
+/// key injected by the proxy; 0 turns auto-lock off.
+#[tauri::command]
+pub fn vault_set_auto_lock(secs: u64) -> Result<(), String> {
+    crate::settings::update_settings(serde_json::json!({ "vault_auto_lock_secs": secs })).map(|_| ())
+}
+
+/// Start the background check that locks an idle vault. Called once at startup.
+pub fn start_auto_lock() {
+    std::thread::spawn(|| loop {
+        std::thread::sleep(AUTO_LOCK_POLL);
+        let secs = crate::settings::get().vault_auto_lock_secs;
+        if secs == 0 {
+            continue;
+        }
+        let Ok(mut guard) = VAULT.write() else { continue };
+        if guard.as_ref().is_some_and(|v| v.last_access.elapsed() >= Duration::from_secs(secs)) {
+            *guard = None;
+            drop(guard);
+            unload_from_proxy();
+            info!("Vault auto-locked after {}s idle", secs);
+            crate::evidence::push("info", &format!("Vault auto-locked after {}s without use", secs));
+        }
+    });
+}
+
+/// Re-encrypt the vault under a new passphrase and a fresh salt. The vault must
+/// be unlocked and `old` must decrypt the file on disk.
+#[tauri::command]
+pub fn vault_change_passphrase(old: String, new: String) -> Result<(), String> {
+    check_passphrase_strength(&new)?;
+    let mut guard = VAULT.write().map_err(|_| "vault lock")?;
+    let state = guard.as_mut().ok_or("Vault is locked")?;
+    let (salt, nonce, ciphertext) = read_vault_file()?;
+    let old_key = derive_key(&old, &salt)?;
+    decrypt_entries(&ciphertext, &nonce, &old_key).map_err(|_| "Current passphrase is incorrect".to_string())?;
+    let mut new_salt = [0u8; 16];
+    getrandom(&mut new_salt).map_err(|e| format!("salt gen: {e}"))?;
+    let new_key = derive_key(&new, &new_salt)?;
+    let (nonce, ciphertext) = encrypt_entries(&state.entries, &new_key)?;
+    write_vault_file(&new_salt, &nonce, &ciphertext)?;
+    state.derived_key = new_key;
+    state.last_access = Instant::now();
+    info!("Vault passphrase changed");
+    crate::evidence::push("info", "Vault passphrase changed");
+    Ok(())
+}
+
 #[tauri::command]
 pub fn vault_is_unlocked() -> bool {
     VAULT.read().map(|g| g.as_ref().map(|v| v.unlocked).unwrap_or(false)).unwrap_or(false)
 }
 
+/// Trimmed, non-empty, de-duplicated tags in their original order.
+fn clean_tags(tags: Vec<String>) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !out.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            out.push(tag.to_string());
+        }
+    }
+    out
+}
+
 #[tauri::command]
-pub fn vault_add_entry(alias: String, value: String, provider: String) -> Result<(), String> {
+pub fn vault_add_entry(
+    alias: String,
+    value: String,
+    provider: String,
+    tags: Option<Vec<String>>,
+    expires_at: Option<u64>,
+) -> Result<(), String> {
     let mut guard = VAULT.write().map_err(|_| "vault lock")?;
     let state = guard.as_mut().ok_or("Vault is locked")?;
+    state.last_access = Instant::now();
     state.entries.retain(|e| e.alias != alias);
     state.entries.push(VaultEntry {
         alias,
         provider,
         value,
         created_at: chrono_now(),
+        updated_at: None,
+        tags: clean_tags(tags.unwrap_or_default()),
+        expires_at,
     });
-    let (nonce, ciphertext) = encrypt_entries(&state.entries, &state.derived_key)?;
-    let (salt, _, _) = read_vault_file()?;
-    write_vault_file(&salt, &nonce, &ciphertext)?;
-    Ok(())
+    persist(state)?;
+    drop(guard);
+    load_into_proxy().map(|_| ())
+}
+
+/// Replace the value of an existing entry, keeping its alias, provider and
+/// `created_at`. `expires_at` sets a new expiry and `clear_expiry` removes it;
+/// with neither the old expiry stays. Errors if there is no entry with `alias`.
+#[tauri::command]
+pub fn vault_update_entry(
+    alias: String,
+    value: String,
+    expires_at: Option<u64>,
+    clear_expiry: Option<bool>,
+) -> Result<(), String> {
+    let mut guard = VAULT.write().map_err(|_| "vault lock")?;
+    let state = guard.as_mut().ok_or("Vault is locked")?;
+    state.last_access = Instant::now();
+    let entry = state
+        .entries
+        .iter_mut()
+        .find(|e| e.alias == alias)
+        .ok_or(format!("No entry with alias '{alias}'"))?;
+    let mut old = std::mem::replace(&mut entry.value, value);
+    old.zeroize();
+    if clear_expiry.unwrap_or(false) {
+        entry.expires_at = None;
+    } else if expires_at.is_some() {
+        entry.expires_at = expires_at;
+    }
+    entry.updated_at = Some(chrono_now());
+    persist(state)?;
+    drop(guard);
+    // Requests already routed through the proxy pick up the new key at once.
+    load_into_proxy().map(|_| ())
 }
 
 #[derive(Serialize)]
@@ -212,15 +459,18 @@ pub struct VaultEntryInfo {
     pub provider: String,
     pub preview: String,
     pub created_at: String,
+    pub updated_at: Option<String>,
+    pub tags: Vec<String>,
+    pub expires_at: Option<u64>,
 }
 
-#[tauri::command]
-pub fn vault_list_entries() -> Result<Vec<VaultEntryInfo>, String> {
-    let guard = VAULT.read().map_err(|_| "vault lock")?;
-    let state = guard.as_ref().ok_or("Vault is locked")?;
-    Ok(state.entries.iter().map(|e| {
-        let preview = if e.value.len() > 6 {
-            format!("{}...{}", &e.value[..3], &e.value[e.value.len()-3..])
+impl VaultEntryInfo {
+    fn of(e: &VaultEntry) -> Self {
+        let chars: Vec<char> = e.value.chars().collect();
+        let preview = if chars.len() > 6 {
+            let head: String = chars[..3].iter().collect();
+            let tail: String = chars[chars.len() - 3..].iter().collect();
+            format!("{}...{}", head, tail)
         } else {
             "****".to_string()
         };
@@ -229,14 +479,54 @@ pub fn vault_list_entries() -> Result<Vec<VaultEntryInfo>, String> {
             provider: e.provider.clone(),
             preview,
             created_at: e.created_at.clone(),
+            updated_at: e.updated_at.clone(),
+            tags: e.tags.clone(),
+            expires_at: e.expires_at,
         }
-    }).collect())
+    }
+}
+
+#[tauri::command]
+pub fn vault_list_entries() -> Result<Vec<VaultEntryInfo>, String> {
+    let mut guard = VAULT.write().map_err(|_| "vault lock")?;
+    let state = guard.as_mut().ok_or("Vault is locked")?;
+    state.last_access = Instant::now();
+    Ok(state.entries.iter().map(VaultEntryInfo::of).collect())
+}
+
+/// Entries carrying `tag`, compared case-insensitively.
+#[tauri::command]
+pub fn vault_list_entries_by_tag(tag: String) -> Result<Vec<VaultEntryInfo>, String> {
+    let mut guard = VAULT.write().map_err(|_| "vault lock")?;
+    let state = guard.as_mut().ok_or("Vault is locked")?;
+    state.last_access = Instant::now();
+    let tag = tag.trim();
+    Ok(state
+        .entries
+        .iter()
+        .filter(|e| e.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+        .map(VaultEntryInfo::of)
+        .collect())
+}
+
+/// Entries that expire within `within_secs` from now, including any already
+/// expired, soonest first.
+#[tauri::command]
+pub fn vault_expiring_soon(within_secs: u64) -> Result<Vec<VaultEntryInfo>, String> {
+    let mut guard = VAULT.write().map_err(|_| "vault lock")?;
+    let state = guard.as_mut().ok_or("Vault is locked")?;
+    state.last_access = Instant::now();
+    let cutoff = unix_now().saturating_add(within_secs);
+    let mut soon: Vec<&VaultEntry> = state.entries.iter().filter(|e| e.expires_at.is_some_and(|at| at <= cutoff)).collect();
+    soon.sort_by_key(|e| e.expires_at);
+    Ok(soon.into_iter().map(VaultEntryInfo::of).collect())
 }
 
 #[tauri::command]
 pub fn vault_get_secret(alias: String) -> Result<String, String> {
-    let guard = VAULT.read().map_err(|_| "vault lock")?;
-    let state = guard.as_ref().ok_or("Vault is locked")?;
+    let mut guard = VAULT.write().map_err(|_| "vault lock")?;
+    let state = guard.as_mut().ok_or("Vault is locked")?;
+    state.last_access = Instant::now();
     state.entries.iter().find(|e| e.alias == alias)
         .map(|e| e.value.clone())
         .ok_or(format!("No entry with alias '{alias}'"))
@@ -246,11 +536,11 @@ pub fn vault_get_secret(alias: String) -> Result<String, String> {
 pub fn vault_delete_entry(alias: String) -> Result<(), String> {
     let mut guard = VAULT.write().map_err(|_| "vault lock")?;
     let state = guard.as_mut().ok_or("Vault is locked")?;
+    state.last_access = Instant::now();
     state.entries.retain(|e| e.alias != alias);
-    let (nonce, ciphertext) = encrypt_entries(&state.entries, &state.derived_key)?;
-    let (salt, _, _) = read_vault_file()?;
-    write_vault_file(&salt, &nonce, &ciphertext)?;
-    Ok(())
+    persist(state)?;
+    drop(guard);
+    load_into_proxy().map(|_| ())
 }
 
 #[tauri::command]
@@ -262,14 +552,127 @@ pub fn vault_delete_file() -> Result<(), String> {
     }
     let mut guard = VAULT.write().map_err(|_| "vault lock")?;
     *guard = None;
+    drop(guard);
+    unload_from_proxy();
     Ok(())
 }
 
-fn chrono_now() -> String {
-    let secs = std::time::SystemTime::now()
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
-        .as_secs();
-    format!("{secs}")
+        .as_secs()
+}
+
+fn chrono_now() -> String {
+    unix_now().to_string()
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Mutex, MutexGuard};
+
+    /// The vault is process-wide, so tests that create one take turns.
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    fn fresh_vault(passphrase: &str) -> MutexGuard<'static, ()> {
+        let turn = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        vault_delete_file().unwrap();
+        vault_create(passphrase.into()).unwrap();
+        turn
+    }
+
+    #[test]
+    fn round_trip_survives_passphrase_change_and_relock() {
+        let _turn = fresh_vault("first passphrase");
+        assert!(vault_path().unwrap().starts_with(std::env::temp_dir()));
+        vault_add_entry("openai".into(), "sk-round-trip".into(), "openai".into(), None, None).unwrap();
+        vault_change_passphrase("first passphrase".into(), "second passphrase".into()).unwrap();
+        vault_lock().unwrap();
+        assert!(!vault_is_unlocked());
+        assert!(vault_get_secret("openai".into()).is_err());
+
+        assert!(unlock("first passphrase").is_err());
+        assert_eq!(unlock("second passphrase").unwrap(), 1);
+        assert_eq!(vault_get_secret("openai".into()).unwrap(), "sk-round-trip");
+
+        vault_delete_file().unwrap();
+    }
+
+    #[test]
+    fn expiry_set_in_the_app_is_enforced_by_the_proxy() {
+        let _turn = fresh_vault("expiry passphrase");
+        let alias = "gui-expiring";
+        // The key reaches the proxy through the app first, without an expiry.
+        crate::set_secret(alias.into(), "sk-gui".into()).unwrap();
+        assert!(crate::proxy_inject::expired(alias, "api.openai.com").is_none());
+
+        vault_add_entry(alias.into(), "sk-gui".into(), "openai".into(), None, Some(1)).unwrap();
+        assert!(crate::proxy_inject::expired(alias, "api.openai.com").is_some());
+
+        vault_lock().unwrap();
+        unlock("expiry passphrase").unwrap();
+        assert!(crate::proxy_inject::expired(alias, "api.openai.com").is_some());
+
+        vault_delete_entry(alias.into()).unwrap();
+        assert!(!crate::proxy::state().read().unwrap().vault.contains_key(alias));
+        assert!(crate::proxy_inject::expired(alias, "api.openai.com").is_none());
+        vault_delete_file().unwrap();
+    }
+
+    #[test]
+    fn lock_unloads_only_keys_that_came_from_the_vault() {
+        let _turn = fresh_vault("unload passphrase");
+        crate::set_secret("scanned-key".into(), "sk-scanned".into()).unwrap();
+        vault_add_entry("vaulted-key".into(), "sk-vaulted".into(), "openai".into(), None, None).unwrap();
+        assert!(crate::proxy::state().read().unwrap().vault.contains_key("vaulted-key"));
+
+        vault_lock().unwrap();
+        let state = crate::proxy::state().read().unwrap();
+        assert!(!state.vault.contains_key("vaulted-key"));
+        assert_eq!(state.vault.get("scanned-key").map(|v| v.as_str()), Some("sk-scanned"));
+        drop(state);
+        vault_delete_file().unwrap();
+    }
+
+    #[test]
+    fn update_can_rotate_or_clear_the_expiry() {
+        let _turn = fresh_vault("rotate passphrase");
+        let alias = "rotating-key";
+        let expiry = || crate::proxy::state().read().unwrap().expires.get(alias).copied();
+        vault_add_entry(alias.into(), "sk-1".into(), "openai".into(), None, Some(1)).unwrap();
+
+        let later = unix_now() + 3600;
+        vault_update_entry(alias.into(), "sk-2".into(), Some(later), None).unwrap();
+        assert_eq!(expiry(), Some(later));
+        assert!(crate::proxy_inject::expired(alias, "api.openai.com").is_none());
+
+        vault_update_entry(alias.into(), "sk-3".into(), None, None).unwrap();
+        assert_eq!(expiry(), Some(later));
+
+        vault_update_entry(alias.into(), "sk-4".into(), None, Some(true)).unwrap();
+        assert_eq!(expiry(), None);
+        assert_eq!(vault_get_secret(alias.into()).unwrap(), "sk-4");
+        vault_delete_file().unwrap();
+    }
+
+    #[test]
+    fn import_replaces_the_previous_vaults_keys_in_the_proxy() {
+        let _turn = fresh_vault("backup passphrase");
+        vault_add_entry("kept-key".into(), "sk-kept".into(), "openai".into(), None, None).unwrap();
+        let backup = std::env::temp_dir().join(format!("vault0-backup-test-{}.enc", std::process::id()));
+        crate::vault_backup::vault_export(backup.display().to_string()).unwrap();
+        vault_add_entry("dropped-key".into(), "sk-dropped".into(), "openai".into(), None, Some(unix_now() + 60)).unwrap();
+
+        let imported = crate::vault_backup::vault_import(backup.display().to_string(), "backup passphrase".into(), Some(true));
+        let _ = fs::remove_file(&backup);
+        assert_eq!(imported.unwrap(), 1);
+        let state = crate::proxy::state().read().unwrap();
+        assert!(state.vault.contains_key("kept-key"));
+        assert!(!state.vault.contains_key("dropped-key"));
+        assert!(!state.expires.contains_key("dropped-key"));
+        drop(state);
+        vault_delete_file().unwrap();
+    }
+}
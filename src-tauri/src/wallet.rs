@@ -13,6 +13,8 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
+use crate::x402::PaymentIntent;
+
 sol! {
     struct TransferWithAuthorization {
         address from;
@@ -116,7 +118,7 @@ pub fn create_wallet() -> Result<CreateWalletResult, String> {
             has_wallet: true,
             address,
             balance_cents: 0,
-            network: "base".to_string(),
+            network: crate::settings::get().wallet_network,
         },
         recovery_phrase: phrase,
     })
@@ -147,7 +149,7 @@ pub fn import_wallet(mnemonic_phrase: String) -> Result<WalletInfo, String> {
         has_wallet: true,
         address,
         balance_cents: 0,
-        network: "base".to_string(),
+        network: crate::settings::get().wallet_network,
     })
 }
 
@@ -159,7 +161,7 @@ pub fn get_wallet_info() -> Result<WalletInfo, String> {
             has_wallet: false,
             address: String::new(),
             balance_cents: 0,
-            network: "base".to_string(),
+            network: crate::settings::get().wallet_network,
         });
     }
     let s = fs::read_to_string(&meta_p).map_err(|e| e.to_string())?;
@@ -168,7 +170,7 @@ pub fn get_wallet_info() -> Result<WalletInfo, String> {
         has_wallet: true,
         address: meta.address,
         balance_cents: 0,
-        network: "base".to_string(),
+        network: crate::settings::get().wallet_network,
     })
 }
 
@@ -186,22 +188,52 @@ pub async fn sign_x402_payment(
 ) -> Result<String, String> {
     let phrase = load_mnemonic()?;
     let signer = signer_from_phrase(&phrase)?;
+    sign_transfer(&signer, amount_cents, &recipient, &network).await
+}
+
+/// Sign several intents with one Keychain read. Signatures come back in the
+/// same order; the first intent that can't be signed fails the whole batch.
+#[tauri::command]
+pub async fn sign_x402_batch(intents: Vec<PaymentIntent>) -> Result<Vec<String>, String> {
+    if intents.is_empty() {
+        return Ok(Vec::new());
+    }
+    let phrase = load_mnemonic()?;
+    let signer = signer_from_phrase(&phrase)?;
+    let mut signatures = Vec::with_capacity(intents.len());
+    for (i, intent) in intents.iter().enumerate() {
+        let sig = sign_transfer(&signer, intent.amount_cents, &intent.recipient, &intent.network)
+            .await
+            .map_err(|e| format!("intent {}: {}", i, e))?;
+        signatures.push(sig);
+    }
+    Ok(signatures)
+}
+
+/// EIP-3009 TransferWithAuthorization signature from `signer`.
+async fn sign_transfer(
+    signer: &PrivateKeySigner,
+    amount_cents: u64,
+    recipient: &str,
+    network: &str,
+) -> Result<String, String> {
     let from = signer.address();
 
     let to = recipient
         .parse::<Address>()
         .map_err(|_| "Invalid recipient address".to_string())?;
 
-    let chain_id: u64 = match network.as_str() {
-        "base" => 8453,
-        "base-sepolia" => 84532,
-        _ => 8453,
-    };
+    let net = crate::networks::lookup(network)?;
+    let usdc = net
+        .usdc_address
+        .parse::<Address>()
+        .map_err(|_| format!("Invalid USDC address for {}", network))?;
 
     let domain = eip712_domain! {
-        name: "USD Coin",
-        version: "2",
-        chain_id: chain_id,
+        name: net.usdc_name,
+        version: net.usdc_version,
+        chain_id: net.chain_id,
+        verifying_contract: usdc,
     };
 
     let valid_after = U256::ZERO;
@@ -225,3 +257,14 @@ pub async fn sign_x402_payment(
     let sig = signer.sign_hash(&hash).await.map_err(|e| e.to_string())?;
     Ok(format!("0x{}", hex::encode(sig.as_bytes())))
 }
+
+/// Sign a raw 32-byte digest with the wallet key. Returns the signature hex and signer address.
+pub async fn sign_digest(digest: [u8; 32]) -> Result<(String, String), String> {
+    let phrase = load_mnemonic()?;
+    let signer = signer_from_phrase(&phrase)?;
+    let sig = signer
+        .sign_hash(&B256::from(digest))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok((format!("0x{}", hex::encode(sig.as_bytes())), address_string(signer.address())))
+}
@@ -41,8 +41,8 @@ pub fn parse_402_required(headers: &[(String, String)], body: &[u8]) -> Option<P
                     network: parsed
                         .get("network")
                         .and_then(|v| v.as_str())
-                        .unwrap_or("base")
-                        .to_string(),
+                        .map(String::from)
+                        .unwrap_or_else(|| crate::settings::get().wallet_network),
                     resource: parsed.get("resource").and_then(|v| v.as_str()).map(String::from),
                 });
             }
@@ -65,8 +65,8 @@ pub fn parse_402_required(headers: &[(String, String)], body: &[u8]) -> Option<P
                     network: parsed
                         .get("network")
                         .and_then(|v| v.as_str())
-                        .unwrap_or("base")
-                        .to_string(),
+                        .map(String::from)
+                        .unwrap_or_else(|| crate::settings::get().wallet_network),
                     resource: parsed.get("resource").and_then(|v| v.as_str()).map(String::from),
                 });
             }
@@ -75,12 +75,13 @@ pub fn parse_402_required(headers: &[(String, String)], body: &[u8]) -> Option<P
     Some(PaymentIntent {
         amount_cents: 0,
         recipient: String::new(),
-        network: "base".to_string(),
+        network: crate::settings::get().wallet_network,
         resource: None,
     })
 }
 
 pub fn record_pending(intent: PaymentIntent) -> String {
+    crate::notify::payment_pending(intent.amount_cents, &intent.recipient);
     let id = format!("pay_{}", std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis());
     let ts = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -104,7 +105,7 @@ pub fn record_pending(intent: PaymentIntent) -> String {
 pub fn get_wallet_balance() -> Result<WalletBalance, String> {
     Ok(WalletBalance {
         balance_cents: 0,
-        network: "base".to_string(),
+        network: crate::settings::get().wallet_network,
         address: "0x0000...0000".to_string(),
     })
 }
@@ -120,6 +121,49 @@ pub fn get_pending_402() -> Result<Vec<PendingPayment>, String> {
     Ok(g.iter().cloned().collect())
 }
 
+/// All-in cost of settling an intent.
+#[derive(Debug, Clone, Serialize)]
+pub struct SettlementEstimate {
+    pub amount_cents: u64,
+    /// How the payment settles. Always `facilitator`: the signed authorization
+    /// goes back to the server, whose facilitator submits it and pays the gas.
+    pub path: String,
+    pub total_cents: u64,
+    /// Why the estimate may not hold, e.g. an unknown network.
+    pub note: Option<String>,
+}
+
+impl SettlementEstimate {
+    /// Whether the all-in cost is within `cap`.
+    pub fn within_cap(&self, cap: u64) -> bool {
+        self.total_cents <= cap
+    }
+}
+
+/// Cost of settling `intent`. Nothing is submitted on-chain from here, so no
+/// gas is paid on top of the amount.
+pub fn estimate_intent(intent: &PaymentIntent) -> SettlementEstimate {
+    SettlementEstimate {
+        amount_cents: intent.amount_cents,
+        path: "facilitator".into(),
+        total_cents: intent.amount_cents,
+        note: crate::networks::lookup(&intent.network).err(),
+    }
+}
+
+/// Estimate the all-in cost of a pending 402 before approving it.
+#[tauri::command]
+pub fn estimate_settlement(id: String) -> Result<SettlementEstimate, String> {
+    let intent = {
+        let g = PENDING.read().map_err(|_| "lock")?;
+        g.iter()
+            .find(|p| p.id == id)
+            .map(|p| p.intent.clone())
+            .ok_or_else(|| format!("No pending payment with id {}", id))?
+    };
+    Ok(estimate_intent(&intent))
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WalletBalance {
     pub balance_cents: u64,